@@ -0,0 +1,311 @@
+//! Threshold checks on calculated statistics, such as `mean<10000` or `count>=1`.
+//!
+//! A check names a statistic, a comparison operator and a number. It is applied to the
+//! statistics of every column (and group), and is violated when the comparison is false
+//! or when the statistic couldn't be calculated.
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{Result, anyhow, bail};
+use polars::prelude::*;
+
+use crate::stats::SelectedStats;
+
+/// The statistics that can be named in a check, besides requested quantiles (`q0.95`).
+const STAT_NAMES: &[&str] = &[
+    "count",
+    "null_count",
+    "null_percent",
+    "numeric_null_count",
+    "numeric_null_percent",
+    "n_unique",
+    "min",
+    "max",
+    "sum",
+    "mean",
+    "median",
+    "std",
+    "var",
+    "weighted_sum",
+    "weighted_mean",
+    "std_error",
+    "ci_lower",
+    "ci_upper",
+    "mad",
+    "geometric_mean",
+    "harmonic_mean",
+    "trimmed_mean",
+    "winsorized_mean",
+    "skew",
+    "kurtosis",
+];
+
+/// A comparison operator of a check.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+    NotEqual,
+}
+
+impl Comparison {
+    /// The operators, longest first so that `<=` isn't read as `<`.
+    pub(crate) const ALL: [(&'static str, Comparison); 6] = [
+        ("<=", Comparison::LessOrEqual),
+        (">=", Comparison::GreaterOrEqual),
+        ("==", Comparison::Equal),
+        ("!=", Comparison::NotEqual),
+        ("<", Comparison::Less),
+        (">", Comparison::Greater),
+    ];
+
+    pub(crate) fn symbol(self) -> &'static str {
+        Self::ALL
+            .iter()
+            .find(|(_, op)| *op == self)
+            .map(|(symbol, _)| *symbol)
+            .expect("every comparison has a symbol")
+    }
+
+    /// Builds an expression comparing `value` with `threshold`.
+    pub(crate) fn expr(self, value: Expr, threshold: Expr) -> Expr {
+        match self {
+            Comparison::Less => value.lt(threshold),
+            Comparison::LessOrEqual => value.lt_eq(threshold),
+            Comparison::Greater => value.gt(threshold),
+            Comparison::GreaterOrEqual => value.gt_eq(threshold),
+            Comparison::Equal => value.eq(threshold),
+            Comparison::NotEqual => value.neq(threshold),
+        }
+    }
+
+    fn holds(self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparison::Less => value < threshold,
+            Comparison::LessOrEqual => value <= threshold,
+            Comparison::Greater => value > threshold,
+            Comparison::GreaterOrEqual => value >= threshold,
+            Comparison::Equal => value == threshold,
+            Comparison::NotEqual => value != threshold,
+        }
+    }
+}
+
+/// A single threshold check, parsed from a string like `mean<10000`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assertion {
+    /// The name of the statistic to check.
+    pub stat: String,
+    /// How the statistic is compared with the threshold.
+    pub comparison: Comparison,
+    /// The value the statistic is compared with.
+    pub threshold: f64,
+}
+
+impl FromStr for Assertion {
+    type Err = anyhow::Error;
+
+    fn from_str(source: &str) -> Result<Self> {
+        let (position, symbol, comparison) = Comparison::ALL
+            .iter()
+            .filter_map(|(symbol, op)| source.find(symbol).map(|pos| (pos, *symbol, *op)))
+            // The leftmost operator wins; on a tie the longer one, which comes first.
+            .min_by_key(|(pos, _, _)| *pos)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Missing comparison operator in '{}', expected one of <, <=, >, >=, ==, !=",
+                    source
+                )
+            })?;
+
+        let stat = source[..position].trim().to_lowercase();
+        let threshold = source[position + symbol.len()..].trim();
+        if !STAT_NAMES.contains(&stat.as_str()) && quantile_of(&stat).is_none() {
+            bail!(
+                "Unknown statistic '{}' in '{}', expected one of {} or a quantile like q0.95",
+                stat,
+                source,
+                STAT_NAMES.join(", ")
+            );
+        }
+        let threshold = threshold
+            .parse()
+            .map_err(|_| anyhow!("Invalid threshold '{}' in '{}'", threshold, source))?;
+
+        Ok(Assertion {
+            stat,
+            comparison,
+            threshold,
+        })
+    }
+}
+
+impl fmt::Display for Assertion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}",
+            self.stat,
+            self.comparison.symbol(),
+            self.threshold
+        )
+    }
+}
+
+impl Assertion {
+    /// Checks the statistics of one column, returning a description of the violation if
+    /// the check fails.
+    pub fn check(&self, stats: &SelectedStats) -> Result<Option<String>> {
+        let subject = match &stats.group {
+            Some(group) => format!(
+                "'{}' where '{}' is {}",
+                stats.column,
+                group.column,
+                group.value.as_deref().unwrap_or("null")
+            ),
+            None => format!("'{}'", stats.column),
+        };
+        let subject = match &stats.file {
+            Some(file) => format!("{} in '{}'", subject, file),
+            None => subject,
+        };
+        Ok(match stat_value(stats, &self.stat)? {
+            Some(value) if self.comparison.holds(value, self.threshold) => None,
+            Some(value) => Some(format!(
+                "{} failed for {}: {} is {}",
+                self, subject, self.stat, value
+            )),
+            None => Some(format!(
+                "{} failed for {}: {} is not available",
+                self, subject, self.stat
+            )),
+        })
+    }
+}
+
+/// The error returned when one or more checks fail, listing every violation.
+#[derive(Debug)]
+pub struct AssertionsFailed(pub Vec<String>);
+
+impl fmt::Display for AssertionsFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} assertion(s) failed", self.0.len())?;
+        for violation in &self.0 {
+            write!(f, "\n  {}", violation)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for AssertionsFailed {}
+
+/// Applies every check to every set of statistics, failing with [`AssertionsFailed`] if
+/// any of them are violated.
+pub fn check_all(assertions: &[Assertion], stats: &[SelectedStats]) -> Result<()> {
+    let mut violations = Vec::new();
+    for assertion in assertions {
+        for s in stats {
+            violations.extend(assertion.check(s)?);
+        }
+    }
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(AssertionsFailed(violations).into())
+    }
+}
+
+/// Parses the quantile out of a quantile statistic name such as `q0.95`.
+fn quantile_of(stat: &str) -> Option<f64> {
+    stat.strip_prefix('q')?.parse().ok()
+}
+
+/// Looks up a statistic by name, as a number.
+fn stat_value(stats: &SelectedStats, stat: &str) -> Result<Option<f64>> {
+    Ok(match stat {
+        "count" => Some(stats.count as f64),
+        "null_count" => Some(stats.null_count as f64),
+        "null_percent" => stats.null_percent,
+        "numeric_null_count" => Some(stats.numeric_null_count as f64),
+        "numeric_null_percent" => stats.numeric_null_percent,
+        "n_unique" => Some(stats.n_unique as f64),
+        "min" => stats.min,
+        "max" => stats.max,
+        "sum" => stats.sum,
+        "mean" => stats.mean,
+        "median" => stats.median,
+        "std" => stats.std,
+        "var" => stats.var,
+        "weighted_sum" | "weighted_mean" => {
+            let Some(weighted) = &stats.weighted else {
+                bail!("Checking {} requires --weight-column", stat);
+            };
+            if stat == "weighted_sum" {
+                weighted.sum
+            } else {
+                weighted.mean
+            }
+        }
+        "std_error" | "ci_lower" | "ci_upper" => {
+            let Some(confidence) = &stats.confidence else {
+                bail!("Checking {} requires --ci", stat);
+            };
+            match stat {
+                "std_error" => confidence.std_error,
+                "ci_lower" => confidence.lower,
+                _ => confidence.upper,
+            }
+        }
+        "mad" => match &stats.mad {
+            Some(mad) => mad.mad,
+            None => bail!("Checking {} requires --mad", stat),
+        },
+        "geometric_mean" | "harmonic_mean" => {
+            let Some(extended) = &stats.extended else {
+                bail!("Checking {} requires --extended", stat);
+            };
+            if stat == "geometric_mean" {
+                extended.geometric_mean
+            } else {
+                extended.harmonic_mean
+            }
+        }
+        "trimmed_mean" => match &stats.trimmed {
+            Some(trimmed) => trimmed.mean,
+            None => bail!("Checking {} requires --trim", stat),
+        },
+        "winsorized_mean" => match &stats.winsorized {
+            Some(winsorized) => winsorized.mean,
+            None => bail!("Checking {} requires --trim and --winsorize", stat),
+        },
+        "skew" | "kurtosis" => {
+            let Some(moments) = &stats.moments else {
+                bail!("Checking {} requires --moments", stat);
+            };
+            if stat == "skew" {
+                moments.skew
+            } else {
+                moments.kurtosis
+            }
+        }
+        _ => {
+            let quantile =
+                quantile_of(stat).ok_or_else(|| anyhow!("Unknown statistic '{}'", stat))?;
+            stats
+                .quantiles
+                .iter()
+                .find(|q| q.quantile == quantile)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Checking {} requires the quantile to be requested with -q or -p",
+                        stat
+                    )
+                })?
+                .value
+        }
+    })
+}
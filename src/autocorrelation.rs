@@ -0,0 +1,129 @@
+//! Autocorrelation of a numeric column ordered in time, to spot seasonality such as a
+//! weekly pattern in daily volumes.
+
+use anyhow::{Result, bail};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::collect;
+use crate::stats::NumericParsing;
+
+/// The lags checked when none are given: up to two weeks of daily values.
+pub const DEFAULT_LAGS: std::ops::RangeInclusive<usize> = 1..=14;
+
+/// Options controlling the autocorrelation.
+#[derive(Debug, Clone, Default)]
+pub struct AutocorrelationOptions {
+    /// The lags, in rows, to calculate the autocorrelation at. [`DEFAULT_LAGS`] when empty.
+    pub lags: Vec<usize>,
+    /// Order the rows by this column first, rather than keeping the file order.
+    pub order_by: Option<String>,
+    /// How text values are cleaned before being parsed as numbers.
+    pub parsing: NumericParsing,
+}
+
+/// The autocorrelation at one lag.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Lag {
+    /// The distance in rows between the paired values.
+    pub lag: usize,
+    /// The autocorrelation coefficient, between -1 and 1.
+    pub acf: Option<f64>,
+    /// Whether the coefficient lies outside the approximate 95% bounds of a series
+    /// without autocorrelation.
+    pub significant: bool,
+}
+
+/// The autocorrelation of a column at several lags.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Autocorrelation {
+    /// The name of the analyzed column.
+    pub column: String,
+    /// The column the rows were ordered by, if any.
+    pub order_by: Option<String>,
+    /// The number of numeric values in the series.
+    pub count: usize,
+    /// The approximate 95% bound, ±1.96 / √count, beyond which a coefficient is flagged.
+    pub bound: f64,
+    pub lags: Vec<Lag>,
+}
+
+/// Calculates the autocorrelation of a column at every lag in a single pass.
+///
+/// Missing and unparseable values are left out of the series first, so lags count the
+/// remaining values. Each coefficient is the sample autocorrelation: the sum of the
+/// products of the deviations from the mean `lag` rows apart, divided by the sum of the
+/// squared deviations.
+pub fn compute_autocorrelation(
+    mut lf: LazyFrame,
+    column_name: &str,
+    options: &AutocorrelationOptions,
+) -> Result<Autocorrelation> {
+    options.parsing.check_strict(&lf, column_name)?;
+    let mut lags: Vec<usize> = if options.lags.is_empty() {
+        DEFAULT_LAGS.collect()
+    } else {
+        options.lags.clone()
+    };
+    lags.sort();
+    lags.dedup();
+    if lags.contains(&0) {
+        bail!("Lags must be at least 1");
+    }
+
+    if let Some(order_by) = options.order_by.as_deref() {
+        lf = lf.sort(
+            [order_by],
+            SortMultipleOptions::default().with_nulls_last(true),
+        );
+    }
+    let series = lf
+        .select([options.parsing.to_float(column_name).alias(column_name)])
+        .drop_nulls(None);
+
+    let deviation = col(column_name) - col(column_name).mean();
+    let mut aggregations = vec![
+        len().alias("count"),
+        deviation.clone().pow(2).sum().alias("variance"),
+    ];
+    for lag in &lags {
+        let lagged = deviation.clone().shift(lit(*lag as i64));
+        aggregations.push(
+            (deviation.clone() * lagged)
+                .sum()
+                .alias(format!("lag_{}", lag)),
+        );
+    }
+    let df = collect(series.select(aggregations))?;
+
+    let count = df.column("count")?.get(0)?.try_extract::<u32>()? as usize;
+    if count < 2 {
+        bail!(
+            "Column '{}' needs at least two numeric values for an autocorrelation, found {}",
+            column_name,
+            count
+        );
+    }
+    let variance: f64 = df.column("variance")?.get(0)?.try_extract()?;
+    let bound = 1.96 / (count as f64).sqrt();
+    let lags = lags
+        .into_iter()
+        .map(|lag| {
+            let covariance: f64 = df.column(&format!("lag_{}", lag))?.get(0)?.try_extract()?;
+            let acf = (variance > 0.0 && lag < count).then(|| covariance / variance);
+            Ok(Lag {
+                lag,
+                acf,
+                significant: acf.is_some_and(|acf| acf.abs() > bound),
+            })
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(Autocorrelation {
+        column: column_name.to_string(),
+        order_by: options.order_by.clone(),
+        count,
+        bound,
+        lags,
+    })
+}
@@ -0,0 +1,613 @@
+//! Reading of Avro object container files, such as Kafka topic dumps.
+//!
+//! The file's records must be records themselves; each field becomes a column. Fields
+//! map onto Polars types as follows, with a union of `null` and one other type read as
+//! that type with missing values:
+//!
+//! - `boolean`: Boolean
+//! - `int` and `long`: Int64
+//! - `float`, `double` and `decimal`: Float64
+//! - `string`, `bytes`, `enum` and `fixed`: String
+//! - `date`: Date
+//! - `timestamp-millis` and `timestamp-micros`, local or not: Datetime
+//! - records, arrays, maps and other unions: String, holding the value as JSON
+//!
+//! Blocks may be uncompressed or compressed with the `deflate` or `zstandard` codecs.
+
+use std::collections::HashMap;
+use std::io::Read;
+
+use anyhow::{Context, Result, anyhow, bail};
+use flate2::read::DeflateDecoder;
+use polars::prelude::*;
+use serde_json::{Map, Value};
+
+/// The bytes every object container file starts with.
+const MAGIC: &[u8] = b"Obj\x01";
+
+/// The length of the marker that follows the header and every block.
+const SYNC_LENGTH: usize = 16;
+
+/// Reads an object container file into a DataFrame.
+pub fn read_avro(data: &[u8]) -> Result<DataFrame> {
+    let mut cursor = Cursor { data, position: 0 };
+    if cursor.take(MAGIC.len())? != MAGIC {
+        bail!("Not an Avro object container file");
+    }
+
+    // The header is a map of metadata, written in blocks like any Avro map.
+    let metadata = Schema::Map(Box::new(Schema::Bytes));
+    let metadata = metadata.decode(&mut cursor)?;
+    let text = |key: &str| metadata.get(key).and_then(Value::as_str);
+    let schema_json: Value = serde_json::from_str(
+        text("avro.schema").ok_or_else(|| anyhow!("The Avro file has no schema"))?,
+    )
+    .context("Invalid Avro schema")?;
+    let schema = Schema::parse(&schema_json, &mut HashMap::new())?;
+    let codec = text("avro.codec").unwrap_or("null").to_string();
+    let sync = cursor.take(SYNC_LENGTH)?.to_vec();
+
+    let Schema::Record(fields) = schema else {
+        bail!("The Avro file's records must be records to be read as rows");
+    };
+    let mut values: Vec<Vec<Value>> = vec![Vec::new(); fields.len()];
+    while cursor.position < data.len() {
+        let count = cursor.read_long()?;
+        let size = usize::try_from(cursor.read_long()?)?;
+        let block = decompress(&codec, cursor.take(size)?)?;
+        if cursor.take(SYNC_LENGTH)? != sync {
+            bail!("Corrupt Avro file: a block isn't followed by the sync marker");
+        }
+
+        let mut block = Cursor {
+            data: &block,
+            position: 0,
+        };
+        for _ in 0..count {
+            for ((_, schema), column) in fields.iter().zip(&mut values) {
+                column.push(schema.decode(&mut block)?);
+            }
+        }
+    }
+
+    let columns = fields
+        .iter()
+        .zip(values)
+        .map(|((name, schema), values)| to_column(name, schema, values))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(DataFrame::new(columns)?)
+}
+
+/// Decompresses a block with the file's codec.
+fn decompress(codec: &str, block: &[u8]) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    match codec {
+        "null" => buf.extend_from_slice(block),
+        "deflate" => {
+            DeflateDecoder::new(block).read_to_end(&mut buf)?;
+        }
+        "zstandard" => {
+            zstd::Decoder::new(block)?.read_to_end(&mut buf)?;
+        }
+        _ => bail!("Unsupported Avro codec '{}'", codec),
+    }
+    Ok(buf)
+}
+
+/// Builds a column of the type matching the field's schema.
+fn to_column(name: &str, schema: &Schema, values: Vec<Value>) -> Result<Column> {
+    let name = PlSmallStr::from(name);
+    let column = match schema.without_null() {
+        Schema::Boolean => Column::new(name, values.iter().map(Value::as_bool).collect::<Vec<_>>()),
+        Schema::Int | Schema::Long => {
+            Column::new(name, values.iter().map(Value::as_i64).collect::<Vec<_>>())
+        }
+        Schema::Float | Schema::Double | Schema::Decimal { .. } => {
+            Column::new(name, values.iter().map(Value::as_f64).collect::<Vec<_>>())
+        }
+        Schema::Date => {
+            let days: Vec<Option<i32>> = values
+                .iter()
+                .map(|v| v.as_i64().map(|d| d as i32))
+                .collect();
+            Column::new(name, days).cast(&DataType::Date)?
+        }
+        Schema::Timestamp { millis } => {
+            let scale = if *millis { 1000 } else { 1 };
+            let micros: Vec<Option<i64>> = values
+                .iter()
+                .map(|v| v.as_i64().and_then(|t| t.checked_mul(scale)))
+                .collect();
+            Column::new(name, micros).cast(&DataType::Datetime(TimeUnit::Microseconds, None))?
+        }
+        Schema::String | Schema::Bytes | Schema::Enum(_) | Schema::Fixed(_) => Column::new(
+            name,
+            values
+                .iter()
+                .map(|v| v.as_str().map(str::to_string))
+                .collect::<Vec<_>>(),
+        ),
+        _ => Column::new(
+            name,
+            values
+                .iter()
+                .map(|v| (!v.is_null()).then(|| v.to_string()))
+                .collect::<Vec<_>>(),
+        ),
+    };
+    Ok(column)
+}
+
+/// An Avro schema, with named types resolved.
+#[derive(Debug, Clone)]
+enum Schema {
+    Null,
+    Boolean,
+    Int,
+    Long,
+    Float,
+    Double,
+    Bytes,
+    String,
+    Record(Vec<(String, Schema)>),
+    Enum(Vec<String>),
+    Array(Box<Schema>),
+    Map(Box<Schema>),
+    Union(Vec<Schema>),
+    Fixed(usize),
+    Date,
+    Timestamp {
+        millis: bool,
+    },
+    /// A decimal stored as the big-endian two's complement bytes of its unscaled value,
+    /// with a fixed size or of varying length.
+    Decimal {
+        scale: i32,
+        size: Option<usize>,
+    },
+}
+
+impl Schema {
+    /// Parses a schema from its JSON, resolving references to the named types defined
+    /// before them.
+    fn parse(json: &Value, names: &mut HashMap<String, Schema>) -> Result<Schema> {
+        match json {
+            Value::String(name) => Self::parse_name(name, names),
+            Value::Array(branches) => Ok(Schema::Union(
+                branches
+                    .iter()
+                    .map(|branch| Self::parse(branch, names))
+                    .collect::<Result<_>>()?,
+            )),
+            Value::Object(object) => Self::parse_object(object, names),
+            _ => bail!("Invalid Avro schema {}", json),
+        }
+    }
+
+    fn parse_name(name: &str, names: &HashMap<String, Schema>) -> Result<Schema> {
+        Ok(match name {
+            "null" => Schema::Null,
+            "boolean" => Schema::Boolean,
+            "int" => Schema::Int,
+            "long" => Schema::Long,
+            "float" => Schema::Float,
+            "double" => Schema::Double,
+            "bytes" => Schema::Bytes,
+            "string" => Schema::String,
+            _ => names
+                .get(name)
+                .or_else(|| names.get(name.rsplit('.').next().unwrap_or(name)))
+                .cloned()
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Unknown Avro type '{}'; recursive types aren't supported",
+                        name
+                    )
+                })?,
+        })
+    }
+
+    fn parse_object(
+        object: &Map<String, Value>,
+        names: &mut HashMap<String, Schema>,
+    ) -> Result<Schema> {
+        let field = |key: &str| {
+            object
+                .get(key)
+                .ok_or_else(|| anyhow!("Avro schema {:?} lacks '{}'", object, key))
+        };
+        let type_name = match field("type")? {
+            Value::String(type_name) => type_name.as_str(),
+            nested => return Self::parse(nested, names),
+        };
+        let size = || -> Result<usize> {
+            Ok(field("size")?
+                .as_u64()
+                .ok_or_else(|| anyhow!("Invalid size of Avro fixed type"))? as usize)
+        };
+
+        let logical_type = object.get("logicalType").and_then(Value::as_str);
+        let schema = match (type_name, logical_type) {
+            ("int", Some("date")) => Schema::Date,
+            ("long", Some("timestamp-millis" | "local-timestamp-millis")) => {
+                Schema::Timestamp { millis: true }
+            }
+            ("long", Some("timestamp-micros" | "local-timestamp-micros")) => {
+                Schema::Timestamp { millis: false }
+            }
+            ("bytes" | "fixed", Some("decimal")) => Schema::Decimal {
+                scale: object.get("scale").and_then(Value::as_i64).unwrap_or(0) as i32,
+                size: if type_name == "fixed" {
+                    Some(size()?)
+                } else {
+                    None
+                },
+            },
+            ("record" | "error", _) => {
+                let fields = field("fields")?
+                    .as_array()
+                    .ok_or_else(|| anyhow!("The fields of an Avro record must be a list"))?;
+                let mut parsed = Vec::with_capacity(fields.len());
+                for f in fields {
+                    let name = f
+                        .get("name")
+                        .and_then(Value::as_str)
+                        .ok_or_else(|| anyhow!("An Avro record field lacks a name"))?;
+                    let schema = Self::parse(
+                        f.get("type")
+                            .ok_or_else(|| anyhow!("Avro field '{}' lacks a type", name))?,
+                        names,
+                    )?;
+                    parsed.push((name.to_string(), schema));
+                }
+                Schema::Record(parsed)
+            }
+            ("enum", _) => Schema::Enum(
+                field("symbols")?
+                    .as_array()
+                    .ok_or_else(|| anyhow!("The symbols of an Avro enum must be a list"))?
+                    .iter()
+                    .map(|s| s.as_str().unwrap_or_default().to_string())
+                    .collect(),
+            ),
+            ("fixed", _) => Schema::Fixed(size()?),
+            ("array", _) => Schema::Array(Box::new(Self::parse(field("items")?, names)?)),
+            ("map", _) => Schema::Map(Box::new(Self::parse(field("values")?, names)?)),
+            (primitive, _) => Self::parse_name(primitive, names)?,
+        };
+
+        if let Some(name) = object.get("name").and_then(Value::as_str) {
+            if let Some(namespace) = object.get("namespace").and_then(Value::as_str) {
+                names.insert(format!("{}.{}", namespace, name), schema.clone());
+            }
+            names.insert(name.to_string(), schema.clone());
+        }
+        Ok(schema)
+    }
+
+    /// Returns the other type of a union of `null` and one other type.
+    fn without_null(&self) -> &Schema {
+        match self {
+            Schema::Union(branches) => {
+                let mut others = branches.iter().filter(|b| !matches!(b, Schema::Null));
+                match (others.next(), others.next()) {
+                    (Some(only), None) => only,
+                    _ => self,
+                }
+            }
+            _ => self,
+        }
+    }
+
+    /// Decodes a value of this type.
+    fn decode(&self, cursor: &mut Cursor) -> Result<Value> {
+        Ok(match self {
+            Schema::Null => Value::Null,
+            Schema::Boolean => Value::Bool(cursor.take(1)?[0] != 0),
+            Schema::Int | Schema::Long | Schema::Date | Schema::Timestamp { .. } => {
+                cursor.read_long()?.into()
+            }
+            Schema::Float => f32::from_le_bytes(cursor.take(4)?.try_into()?).into(),
+            Schema::Double => f64::from_le_bytes(cursor.take(8)?.try_into()?).into(),
+            Schema::Bytes | Schema::String => {
+                let length = usize::try_from(cursor.read_long()?)?;
+                String::from_utf8_lossy(cursor.take(length)?).into()
+            }
+            Schema::Fixed(size) => String::from_utf8_lossy(cursor.take(*size)?).into(),
+            Schema::Decimal { scale, size } => {
+                let length = match size {
+                    Some(size) => *size,
+                    None => usize::try_from(cursor.read_long()?)?,
+                };
+                let bytes = cursor.take(length)?;
+                // Sign-extend the big-endian two's complement value.
+                let mut unscaled: i128 = if bytes.first().is_some_and(|b| b & 0x80 != 0) {
+                    -1
+                } else {
+                    0
+                };
+                for &b in bytes {
+                    unscaled = (unscaled << 8) | b as i128;
+                }
+                (unscaled as f64 / 10f64.powi(*scale)).into()
+            }
+            Schema::Enum(symbols) => {
+                let index = usize::try_from(cursor.read_long()?)?;
+                symbols
+                    .get(index)
+                    .ok_or_else(|| anyhow!("Avro enum index {} is out of range", index))?
+                    .clone()
+                    .into()
+            }
+            Schema::Union(branches) => {
+                let index = usize::try_from(cursor.read_long()?)?;
+                branches
+                    .get(index)
+                    .ok_or_else(|| anyhow!("Avro union index {} is out of range", index))?
+                    .decode(cursor)?
+            }
+            Schema::Record(fields) => {
+                let mut object = Map::new();
+                for (name, schema) in fields {
+                    object.insert(name.clone(), schema.decode(cursor)?);
+                }
+                Value::Object(object)
+            }
+            Schema::Array(items) => {
+                let mut array = Vec::new();
+                cursor.read_blocks(|cursor| {
+                    array.push(items.decode(cursor)?);
+                    Ok(())
+                })?;
+                Value::Array(array)
+            }
+            Schema::Map(values) => {
+                let mut object = Map::new();
+                cursor.read_blocks(|cursor| {
+                    let key = Schema::String.decode(cursor)?;
+                    let key = key.as_str().unwrap_or_default().to_string();
+                    object.insert(key, values.decode(cursor)?);
+                    Ok(())
+                })?;
+                Value::Object(object)
+            }
+        })
+    }
+}
+
+/// A position in Avro-encoded data.
+struct Cursor<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Cursor<'a> {
+    /// Takes the next `length` bytes.
+    fn take(&mut self, length: usize) -> Result<&'a [u8]> {
+        let truncated = || anyhow!("Truncated Avro data");
+        // The length may come from a corrupt file, so it mustn't overflow the position.
+        let end = self.position.checked_add(length).ok_or_else(truncated)?;
+        let bytes = self.data.get(self.position..end).ok_or_else(truncated)?;
+        self.position = end;
+        Ok(bytes)
+    }
+
+    /// Reads a zigzag-encoded variable-length integer.
+    fn read_long(&mut self) -> Result<i64> {
+        let mut value: u64 = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok((value >> 1) as i64 ^ -((value & 1) as i64));
+            }
+        }
+        bail!("Invalid Avro integer")
+    }
+
+    /// Calls `item` for every item of an array or map, which are written in blocks each
+    /// preceded by its item count.
+    fn read_blocks(&mut self, mut item: impl FnMut(&mut Self) -> Result<()>) -> Result<()> {
+        loop {
+            let count = self.read_long()?;
+            if count == 0 {
+                return Ok(());
+            }
+            if count < 0 {
+                // A negative count is followed by the block's size in bytes.
+                self.read_long()?;
+            }
+            for _ in 0..count.unsigned_abs() {
+                item(self)?;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::Compression;
+    use flate2::write::DeflateEncoder;
+
+    use super::*;
+
+    const SYNC: [u8; SYNC_LENGTH] = *b"0123456789abcdef";
+
+    /// Encodes a long the way Avro does: zigzag, then as a variable-length integer.
+    fn long(n: i64) -> Vec<u8> {
+        let mut zigzag = ((n << 1) ^ (n >> 63)) as u64;
+        let mut bytes = Vec::new();
+        loop {
+            let byte = (zigzag & 0x7f) as u8;
+            zigzag >>= 7;
+            if zigzag == 0 {
+                bytes.push(byte);
+                return bytes;
+            }
+            bytes.push(byte | 0x80);
+        }
+    }
+
+    fn string(s: &str) -> Vec<u8> {
+        [long(s.len() as i64), s.as_bytes().to_vec()].concat()
+    }
+
+    /// Builds an object container file with one block of `count` records.
+    fn container(schema: &str, codec: &str, count: i64, records: &[u8]) -> Vec<u8> {
+        let block = match codec {
+            "deflate" => {
+                let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(records).unwrap();
+                encoder.finish().unwrap()
+            }
+            "zstandard" => zstd::encode_all(records, 0).unwrap(),
+            _ => records.to_vec(),
+        };
+        [
+            MAGIC.to_vec(),
+            long(2),
+            string("avro.schema"),
+            string(schema),
+            string("avro.codec"),
+            string(codec),
+            long(0),
+            SYNC.to_vec(),
+            long(count),
+            long(block.len() as i64),
+            block,
+            SYNC.to_vec(),
+        ]
+        .concat()
+    }
+
+    fn decode(schema: &Schema, bytes: &[u8]) -> Result<Value> {
+        schema.decode(&mut Cursor {
+            data: bytes,
+            position: 0,
+        })
+    }
+
+    #[test]
+    fn reads_zigzag_longs() {
+        for n in [0, -1, 1, -64, 64, 1 << 40, i64::MAX, i64::MIN] {
+            let bytes = long(n);
+            let mut cursor = Cursor {
+                data: &bytes,
+                position: 0,
+            };
+            assert_eq!(cursor.read_long().unwrap(), n);
+            assert_eq!(cursor.position, bytes.len());
+        }
+        assert_eq!(long(-1), [0x01]);
+        assert_eq!(long(64), [0x80, 0x01]);
+        assert!(decode(&Schema::Long, &[0xff; 10]).is_err());
+    }
+
+    #[test]
+    fn rejects_lengths_past_the_data() {
+        let mut cursor = Cursor {
+            data: b"abc",
+            position: 1,
+        };
+        let err = cursor.take(usize::MAX).unwrap_err();
+        assert_eq!(err.to_string(), "Truncated Avro data");
+        assert!(decode(&Schema::String, &long(i64::MAX)).is_err());
+    }
+
+    #[test]
+    fn reads_arrays_and_maps_in_blocks() {
+        let items = Schema::Array(Box::new(Schema::Long));
+        // A block with a positive count, then one with a negative count and its size.
+        let bytes = [
+            long(1),
+            long(7),
+            long(-2),
+            long(2),
+            long(1),
+            long(2),
+            long(0),
+        ]
+        .concat();
+        assert_eq!(
+            decode(&items, &bytes).unwrap(),
+            serde_json::json!([7, 1, 2])
+        );
+
+        let map = Schema::Map(Box::new(Schema::Boolean));
+        let bytes = [long(-1), long(3), string("ok"), vec![1], long(0)].concat();
+        assert_eq!(
+            decode(&map, &bytes).unwrap(),
+            serde_json::json!({"ok": true})
+        );
+    }
+
+    #[test]
+    fn reads_fixed_and_bytes_decimals() {
+        let fixed = Schema::Decimal {
+            scale: 2,
+            size: Some(2),
+        };
+        // -200 in two's complement.
+        assert_eq!(
+            decode(&fixed, &[0xff, 0x38]).unwrap(),
+            serde_json::json!(-2.0)
+        );
+        let bytes = Schema::Decimal {
+            scale: 2,
+            size: None,
+        };
+        // 12345, preceded by its length.
+        let value = decode(&bytes, &[long(2), vec![0x30, 0x39]].concat()).unwrap();
+        assert_eq!(value, serde_json::json!(123.45));
+    }
+
+    #[test]
+    fn reads_nullable_unions_as_missing_values() {
+        let schema = r#"{"type": "record", "name": "Row", "fields": [
+            {"name": "n", "type": ["null", "long"]},
+            {"name": "s", "type": ["null", "string"]}
+        ]}"#;
+        let records = [long(1), long(5), long(0), long(0), long(1), string("x")].concat();
+        let df = read_avro(&container(schema, "null", 2, &records)).unwrap();
+        let n: Vec<Option<i64>> = df.column("n").unwrap().i64().unwrap().into_iter().collect();
+        assert_eq!(n, [Some(5), None]);
+        let s: Vec<Option<&str>> = df.column("s").unwrap().str().unwrap().into_iter().collect();
+        assert_eq!(s, [None, Some("x")]);
+    }
+
+    #[test]
+    fn reads_deflate_and_zstandard_blocks() {
+        let schema = r#"{"type": "record", "name": "Row", "fields": [
+            {"name": "amount", "type": "double"},
+            {"name": "day", "type": {"type": "int", "logicalType": "date"}}
+        ]}"#;
+        let records = [
+            1.5f64.to_le_bytes().to_vec(),
+            long(1),
+            (-2.5f64).to_le_bytes().to_vec(),
+            long(365),
+        ]
+        .concat();
+        let expected = read_avro(&container(schema, "null", 2, &records)).unwrap();
+        assert_eq!(expected.column("day").unwrap().dtype(), &DataType::Date);
+        for codec in ["deflate", "zstandard"] {
+            let df = read_avro(&container(schema, codec, 2, &records)).unwrap();
+            assert!(df.equals(&expected), "{}", codec);
+        }
+        let err = read_avro(&container(schema, "snappy", 2, &records)).unwrap_err();
+        assert_eq!(err.to_string(), "Unsupported Avro codec 'snappy'");
+    }
+
+    #[test]
+    fn rejects_a_block_without_the_sync_marker() {
+        let schema =
+            r#"{"type": "record", "name": "Row", "fields": [{"name": "n", "type": "long"}]}"#;
+        let mut data = container(schema, "null", 1, &long(3));
+        let last = data.len() - 1;
+        data[last] ^= 1;
+        assert!(read_avro(&data).is_err());
+        assert!(read_avro(b"PAR1").is_err());
+    }
+}
@@ -0,0 +1,155 @@
+//! Bootstrap confidence intervals, which resample the values instead of assuming how
+//! they're distributed, so they also suit skewed data and statistics such as the median.
+
+use anyhow::{Result, bail};
+use polars::prelude::*;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::collect;
+use crate::stats::NumericParsing;
+
+/// Options controlling the bootstrap.
+#[derive(Debug, Clone)]
+pub struct BootstrapOptions {
+    /// The number of resamples to draw.
+    pub resamples: usize,
+    /// The confidence level of the intervals, e.g. 0.95.
+    pub level: f64,
+    /// Quantiles to find intervals for besides the mean and median, each between 0 and 1.
+    pub quantiles: Vec<f64>,
+    /// The seed of the first resample; each further resample adds one, so the intervals
+    /// don't depend on how the resamples are spread over the threads.
+    pub seed: u64,
+    /// How text values are cleaned before being parsed as numbers.
+    pub parsing: NumericParsing,
+}
+
+/// The bootstrap confidence interval of one statistic.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BootstrapInterval {
+    /// The statistic: `mean`, `median` or `quantile`.
+    pub statistic: String,
+    /// The quantile, for a quantile.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quantile: Option<f64>,
+    /// The statistic of the values themselves.
+    pub estimate: f64,
+    /// The lower bound of the interval: a quantile of the statistic across the resamples.
+    pub lower: f64,
+    /// The upper bound of the interval.
+    pub upper: f64,
+}
+
+/// The bootstrap confidence intervals of a column.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bootstrap {
+    /// The name of the analyzed column.
+    pub column: String,
+    /// The number of numeric values, which is also the size of every resample.
+    pub count: usize,
+    /// The number of resamples drawn.
+    pub resamples: usize,
+    /// The confidence level of the intervals.
+    pub level: f64,
+    /// The intervals of the mean and median, then of each quantile.
+    pub intervals: Vec<BootstrapInterval>,
+}
+
+/// Calculates percentile bootstrap intervals for the mean, median and quantiles of a
+/// column.
+///
+/// Each resample draws as many values as the column has, with replacement, and the
+/// bounds of an interval are the quantiles of the statistic across the resamples. The
+/// column's numeric values are loaded into memory, and the resamples are spread over
+/// all threads.
+pub fn compute_bootstrap(
+    lf: LazyFrame,
+    column_name: &str,
+    options: &BootstrapOptions,
+) -> Result<Bootstrap> {
+    options.parsing.check_strict(&lf, column_name)?;
+    if options.resamples == 0 {
+        bail!("The bootstrap needs at least one resample");
+    }
+    if !(options.level > 0.0 && options.level < 1.0) {
+        bail!(
+            "The confidence level must be between 0 and 1, got {}",
+            options.level
+        );
+    }
+
+    let df = collect(
+        lf.select([options.parsing.to_float(column_name).alias(column_name)])
+            .drop_nulls(None),
+    )?;
+    let values = df.column(column_name)?.as_materialized_series().clone();
+    let count = values.len();
+    if count < 2 {
+        bail!(
+            "Column '{}' needs at least two numeric values for a bootstrap, found {}",
+            column_name,
+            count
+        );
+    }
+
+    // The statistics of one sample, in the order of the intervals.
+    let statistics = |sample: &Series| -> Result<Vec<f64>> {
+        let sorted: Vec<f64> = sample
+            .sort(SortOptions::default())?
+            .f64()?
+            .into_no_null_iter()
+            .collect();
+        let mut results = vec![
+            sorted.iter().sum::<f64>() / sorted.len() as f64,
+            quantile(&sorted, 0.5),
+        ];
+        results.extend(options.quantiles.iter().map(|&q| quantile(&sorted, q)));
+        Ok(results)
+    };
+    let estimates = statistics(&values)?;
+    let resampled = (0..options.resamples)
+        .into_par_iter()
+        .map(|i| {
+            let seed = options.seed.wrapping_add(i as u64);
+            statistics(&values.sample_n(count, true, false, Some(seed))?)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let tail = (1.0 - options.level) / 2.0;
+    let names = ["mean", "median"]
+        .into_iter()
+        .map(|name| (name, None))
+        .chain(options.quantiles.iter().map(|&q| ("quantile", Some(q))));
+    let intervals = names
+        .zip(estimates)
+        .enumerate()
+        .map(|(i, ((statistic, quantile_of), estimate))| {
+            let mut distribution: Vec<f64> = resampled.iter().map(|r| r[i]).collect();
+            distribution.sort_by(f64::total_cmp);
+            BootstrapInterval {
+                statistic: statistic.to_string(),
+                quantile: quantile_of,
+                estimate,
+                lower: quantile(&distribution, tail),
+                upper: quantile(&distribution, 1.0 - tail),
+            }
+        })
+        .collect();
+
+    Ok(Bootstrap {
+        column: column_name.to_string(),
+        count,
+        resamples: options.resamples,
+        level: options.level,
+        intervals,
+    })
+}
+
+/// Returns the quantile of sorted values, interpolating linearly like the `stats` command.
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q * (sorted.len() - 1) as f64;
+    let below = position.floor() as usize;
+    let above = position.ceil() as usize;
+    sorted[below] + (sorted[above] - sorted[below]) * (position - below as f64)
+}
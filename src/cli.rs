@@ -0,0 +1,1546 @@
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use csv_stats::assertions::Assertion;
+use csv_stats::autocorrelation::AutocorrelationOptions;
+use csv_stats::benford::BenfordOptions;
+use csv_stats::columns::ColumnLookup;
+use csv_stats::correlation::{CorrelationMethod, CorrelationOptions};
+use csv_stats::drift::{DriftOptions, Tolerance};
+use csv_stats::engine::ExecutionEngine;
+use csv_stats::export::ExportOptions;
+use csv_stats::histogram::HistogramOptions;
+use csv_stats::input::{InputFormat, InputOptions, Sample};
+use csv_stats::keys::KeyOptions;
+use csv_stats::metrics::PushTarget;
+use csv_stats::normalize::{NormalizeMethod, NormalizeOptions};
+use csv_stats::outliers::{OutlierMethod, OutlierOptions};
+use csv_stats::output::{FileFormat, OutputFormat};
+use csv_stats::profile::ProfileOptions;
+use csv_stats::report::ProfileFormat;
+use csv_stats::rolling::{RollingOptions, RollingWindow, SmoothingOptions};
+use csv_stats::schema::DtypeOverride;
+use csv_stats::server::ServerOptions;
+use csv_stats::significance::SignificanceOptions;
+use csv_stats::stats::{GroupSort, NumericParsing, StatsOptions, TimeBuckets};
+use csv_stats::tui::TuiOptions;
+use csv_stats::validation::ValidationOptions;
+
+/// A CLI tool to calculate statistics for columns in a CSV file.
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub struct Cli {
+    /// The command to run.
+    #[command(subcommand)]
+    pub command: Command,
+
+    /// The Polars engine used to run queries. Streaming keeps memory use roughly
+    /// constant, so files larger than RAM can be analyzed.
+    #[arg(long, global = true, value_enum, default_value_t = ExecutionEngine::Streaming)]
+    pub engine: ExecutionEngine,
+
+    /// Use the settings of this named profile from csv-stats.toml, e.g. `aml` for the
+    /// `[profile.aml]` table.
+    #[arg(long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Show a spinner while scanning, even when stderr isn't a terminal. It is shown
+    /// automatically on terminals.
+    #[arg(long, global = true)]
+    pub progress: bool,
+
+    /// Never show the spinner.
+    #[arg(long, global = true, conflicts_with = "progress")]
+    pub no_progress: bool,
+}
+
+/// The available subcommands.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Calculate statistics for a numeric column.
+    Stats(Box<StatsArgs>),
+    /// Summarize every numeric column in a table, similar to pandas `describe()`.
+    Describe(DescribeArgs),
+    /// Render an ASCII histogram of a numeric column.
+    Hist(HistArgs),
+    /// List the most frequent values of a column of any type.
+    Freq(FreqArgs),
+    /// Run a SQL query against the input and print the result.
+    Sql(SqlArgs),
+    /// List every column with its inferred type and an example value.
+    Schema(SchemaArgs),
+    /// Print the first rows of the input.
+    Head(PreviewArgs),
+    /// Print the last rows of the input.
+    Tail(PreviewArgs),
+    /// Calculate the correlation and covariance of two numeric columns, or the correlation
+    /// and covariance matrices of all of them.
+    Corr(CorrArgs),
+    /// List the rows whose value in a numeric column is an outlier.
+    Outliers(OutliersArgs),
+    /// Count the rows of the input, without parsing any values.
+    Count(CountArgs),
+    /// Compare the leading digits of a numeric column with Benford's law, a common
+    /// screen for fabricated amounts.
+    Benford(BenfordArgs),
+    /// Profile every column of the input: null rates, distinct counts, statistics,
+    /// histograms and top values, as a summary or a standalone HTML report.
+    Profile(ProfileArgs),
+    /// Calculate the rolling mean, standard deviation, minimum and maximum of a numeric
+    /// column over a window of rows or of time, or its running count, sum and mean.
+    Rolling(RollingArgs),
+    /// Count the rows that duplicate another, entirely or by a set of key columns.
+    Dups(DupsArgs),
+    /// Find the columns, or small combinations of columns, that uniquely identify the rows,
+    /// e.g. to pick a join key in an unfamiliar export.
+    Keys(KeysArgs),
+    /// Check every row against the data-quality rules of a rules file, reporting the
+    /// violations and failing if there are any.
+    Validate(ValidateArgs),
+    /// List the rows of CSV files with the wrong number of fields or broken quoting, with
+    /// their line numbers and byte offsets.
+    Malformed(MalformedArgs),
+    /// Write the rows of the input to a CSV or Parquet file after the same filtering,
+    /// type overrides and number parsing the statistics use.
+    Export(ExportArgs),
+    /// Serve an HTTP API for calculating statistics, so other services can request them
+    /// without spawning a process each time.
+    Serve(ServeArgs),
+    /// Explore the input interactively in the terminal: the statistics and histogram of
+    /// each column, with quick filters.
+    Tui(TuiArgs),
+    /// Load the input into memory once, then calculate statistics, histograms and more
+    /// on it interactively, with filters and groupings that can change between commands.
+    Repl(ReplArgs),
+    /// Save the statistics of the input as a JSON baseline for `compare`.
+    Snapshot(SnapshotArgs),
+    /// Recalculate the statistics of a baseline and report how far each drifted, failing
+    /// with exit code 3 when any drifted beyond its tolerance.
+    Compare(CompareArgs),
+    /// Print the statistics of columns in two files side by side, with the change between
+    /// them, e.g. to compare two monthly exports.
+    Diff(DiffArgs),
+    /// Test whether a column differs significantly between two files or two groups, with
+    /// Welch's t-test and the Mann–Whitney U test.
+    Ttest(TtestArgs),
+    /// Cross-tabulate two categorical columns and test whether they are independent, with
+    /// a chi-square test.
+    Crosstab(CrosstabArgs),
+    /// Fit a straight line through two numeric columns, y ~ x, by least squares.
+    Regress(RegressArgs),
+    /// Calculate the autocorrelation of a numeric column at several lags, e.g. to check
+    /// daily volumes for a weekly pattern.
+    Acf(AcfArgs),
+    /// Calculate simple and exponentially weighted moving averages of a numeric column,
+    /// e.g. to write a smoothed series to CSV for plotting.
+    Smooth(SmoothArgs),
+    /// Write the input to a CSV or Parquet file with numeric columns standardized to
+    /// z-scores or scaled to between 0 and 1.
+    Normalize(NormalizeArgs),
+}
+
+impl Command {
+    /// The input arguments of the command.
+    pub fn input(&self) -> &InputArgs {
+        match self {
+            Command::Stats(args) => &args.input,
+            Command::Describe(args) => &args.input,
+            Command::Hist(args) => &args.input,
+            Command::Freq(args) => &args.input,
+            Command::Sql(args) => &args.input,
+            Command::Schema(args) => &args.input,
+            Command::Head(args) | Command::Tail(args) => &args.input,
+            Command::Corr(args) => &args.input,
+            Command::Outliers(args) => &args.input,
+            Command::Count(args) => &args.input,
+            Command::Benford(args) => &args.input,
+            Command::Profile(args) => &args.input,
+            Command::Rolling(args) => &args.input,
+            Command::Dups(args) => &args.input,
+            Command::Keys(args) => &args.input,
+            Command::Validate(args) => &args.input,
+            Command::Malformed(args) => &args.input,
+            Command::Export(args) => &args.input,
+            Command::Serve(args) => &args.input,
+            Command::Tui(args) => &args.input,
+            Command::Repl(args) => &args.input,
+            Command::Snapshot(args) => &args.input,
+            Command::Compare(args) => &args.input,
+            Command::Diff(args) => &args.input,
+            Command::Ttest(args) => &args.input,
+            Command::Crosstab(args) => &args.input,
+            Command::Regress(args) => &args.input,
+            Command::Acf(args) => &args.input,
+            Command::Smooth(args) => &args.input,
+            Command::Normalize(args) => &args.input,
+        }
+    }
+
+    /// Whether the command analyzes a random sample of the rows.
+    pub fn samples(&self) -> bool {
+        let input = self.input();
+        input.sample.is_some()
+            || input.sample_frac.is_some()
+            || matches!(self, Command::Stats(args)
+                if args.sample_per_group.is_some() || args.bootstrap.is_some())
+    }
+
+    /// Whether the command takes over the terminal, so nothing else may draw on it.
+    pub fn is_interactive(&self) -> bool {
+        matches!(self, Command::Tui(_))
+    }
+}
+
+/// Arguments shared by every command that reads an input file.
+#[derive(Args, Debug)]
+pub struct InputArgs {
+    /// The path to the input file, or a glob pattern such as 'data/2024-*.csv'.
+    /// Repeat to combine several files. Use `-` or omit it to read from stdin.
+    /// Cloud storage URIs such as 's3://bucket/data.parquet', or with gs:// or az://,
+    /// are read in place, with credentials from the environment, and HTTP(S) URLs
+    /// are downloaded.
+    #[arg(short, long)]
+    pub file_path: Vec<String>,
+
+    /// The format of the input. Detected from the file extension when omitted, defaulting to CSV.
+    #[arg(long, value_enum)]
+    pub format: Option<InputFormat>,
+
+    /// The single-byte field delimiter for CSV input, e.g. ';', '|' or '\t' for tabs.
+    #[arg(short, long, value_parser = parse_delimiter)]
+    pub delimiter: Option<u8>,
+
+    /// Treat the CSV input as tab-separated. Shortcut for `--delimiter '\t'`.
+    #[arg(long, conflicts_with = "delimiter")]
+    pub tsv: bool,
+
+    /// The CSV input has no header row. Its columns are named column_1, column_2 and so on,
+    /// or can be selected by position with `--column-index`.
+    #[arg(long)]
+    pub no_header: bool,
+
+    /// Comma-separated list of strings to treat as missing values in CSV input (e.g. NA,-,NULL).
+    #[arg(long, value_delimiter = ',')]
+    pub null_values: Vec<String>,
+
+    /// Only analyze rows matching this expression,
+    /// e.g. 'col("Amount Received") > 1000 && col("Payment Currency") == "US Dollar"'.
+    #[arg(long)]
+    pub filter: Option<String>,
+
+    /// Only analyze a random sample of this many of the (filtered) rows, e.g. for quick
+    /// exploratory statistics of an enormous file.
+    #[arg(long, value_name = "ROWS")]
+    pub sample: Option<usize>,
+
+    /// Only analyze a random sample of this fraction of the (filtered) rows, e.g. 0.01.
+    #[arg(long, value_name = "FRACTION", conflicts_with = "sample")]
+    pub sample_frac: Option<f64>,
+
+    /// Seed the sampling, so every run analyzes the same rows. Without it the seed is
+    /// random, and noted on stderr to repeat the run with.
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Read a column as this type instead of inferring it, e.g. 'Amount Received=f64' or
+    /// 'Timestamp=datetime[%Y/%m/%d %H:%M]'. Repeat the flag for several columns.
+    #[arg(long, value_name = "COLUMN=TYPE")]
+    pub dtype: Vec<DtypeOverride>,
+
+    /// A JSON file mapping column names to types, e.g. '{"Amount Received": "f64"}'.
+    /// Any `--dtype` takes precedence.
+    #[arg(long, value_name = "FILE")]
+    pub schema: Option<String>,
+
+    /// When a column name isn't found, use the closest existing column instead of
+    /// failing, if exactly one is closest.
+    #[arg(long)]
+    pub fuzzy: bool,
+
+    /// Match column names regardless of case, e.g. 'amount received' for 'Amount Received'.
+    #[arg(long)]
+    pub ignore_case: bool,
+
+    /// The sheet of an Excel workbook to read, by name or position counting from 1.
+    /// Defaults to the first sheet.
+    #[arg(long)]
+    pub sheet: Option<String>,
+
+    /// A header to send when downloading HTTP(S) input, e.g. 'Authorization: Bearer TOKEN'.
+    /// Repeat the flag for several headers.
+    #[arg(long, value_name = "NAME: VALUE", value_parser = parse_http_header)]
+    pub http_header: Vec<(String, String)>,
+
+    /// The version of a Delta Lake table to read, for time travel. Defaults to the latest.
+    #[arg(long, value_name = "VERSION")]
+    pub delta_version: Option<u64>,
+}
+
+impl InputArgs {
+    /// Converts the parsed arguments into the library's input options.
+    pub fn options(&self) -> InputOptions {
+        InputOptions {
+            paths: self.file_path.clone(),
+            format: self.format,
+            delimiter: self.delimiter,
+            tsv: self.tsv,
+            no_header: self.no_header,
+            null_values: self.null_values.clone(),
+            filter: self.filter.clone(),
+            dtypes: self.dtype.clone(),
+            schema_file: self.schema.clone(),
+            sheet: self.sheet.clone(),
+            http_headers: self.http_header.clone(),
+            delta_version: self.delta_version,
+            sample: match (self.sample, self.sample_frac) {
+                (Some(rows), _) => Some(Sample::Rows(rows)),
+                (None, Some(fraction)) => Some(Sample::Fraction(fraction)),
+                (None, None) => None,
+            },
+            seed: self.seed,
+        }
+    }
+
+    /// Converts the parsed arguments into the library's column lookup options.
+    pub fn lookup(&self) -> ColumnLookup {
+        ColumnLookup {
+            fuzzy: self.fuzzy,
+            ignore_case: self.ignore_case,
+        }
+    }
+}
+
+/// Parses a percentile argument, which must be between 0 and 100.
+fn parse_percentile(value: &str) -> Result<f64, String> {
+    let percentile: f64 = value.parse().map_err(|e| format!("{}", e))?;
+    if (0.0..=100.0).contains(&percentile) {
+        Ok(percentile)
+    } else {
+        Err(format!(
+            "percentile must be between 0 and 100, got {}",
+            percentile
+        ))
+    }
+}
+
+/// Parses a tolerance of a single statistic, of the form `STAT=DRIFT`.
+fn parse_tolerance(value: &str) -> Result<(String, Tolerance), String> {
+    let (stat, drift) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected 'STAT=DRIFT', got '{}'", value))?;
+    let drift = drift.parse().map_err(|err| format!("{}", err))?;
+    Ok((stat.trim().to_string(), drift))
+}
+
+/// Parses a range of the form `LOW,HIGH`.
+fn parse_range(value: &str) -> Result<(f64, f64), String> {
+    let bounds = value
+        .split_once(',')
+        .and_then(|(low, high)| Some((low.trim().parse().ok()?, high.trim().parse().ok()?)));
+    bounds.ok_or_else(|| format!("expected 'LOW,HIGH', got '{}'", value))
+}
+
+/// Parses an HTTP header argument of the form `Name: value`.
+fn parse_http_header(value: &str) -> Result<(String, String), String> {
+    match value.split_once(':') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(format!("expected 'Name: value', got '{}'", value)),
+    }
+}
+
+/// Parses `-o` for the commands other than `stats`, which leave Prometheus metrics out
+/// of their listed formats since only statistics have that form.
+fn results_format() -> impl TypedValueParser<Value = OutputFormat> {
+    let formats = OutputFormat::value_variants()
+        .iter()
+        .filter(|format| **format != OutputFormat::Prom)
+        .filter_map(|format| format.to_possible_value());
+    PossibleValuesParser::new(formats)
+        .map(|format| OutputFormat::from_str(&format, false).expect("a listed format"))
+}
+
+/// Parses a delimiter argument, which must be a single byte.
+///
+/// The escape `\t` and the word `tab` are accepted for tabs, since a literal tab
+/// is awkward to type in most shells.
+fn parse_delimiter(value: &str) -> Result<u8, String> {
+    match value {
+        "\\t" | "tab" => Ok(b'\t'),
+        _ if value.len() == 1 => Ok(value.as_bytes()[0]),
+        _ => Err(format!(
+            "delimiter must be a single byte character, got '{}'",
+            value
+        )),
+    }
+}
+
+/// Arguments controlling how text values are cleaned before being parsed as numbers.
+#[derive(Args, Debug)]
+pub struct ParsingArgs {
+    /// Strip currency symbols (e.g. '$', '€', '£') from values before parsing them as numbers.
+    #[arg(long)]
+    pub strip_currency: bool,
+
+    /// Remove this thousands separator from values before parsing them as numbers (e.g. ',').
+    #[arg(long)]
+    pub thousands_sep: Option<String>,
+
+    /// Strip surrounding whitespace from values before parsing them as numbers.
+    #[arg(long)]
+    pub strip_whitespace: bool,
+
+    /// Fail, listing the first offending values and their rows, if any value can't be
+    /// parsed as a number, instead of treating it as missing.
+    #[arg(long)]
+    pub strict: bool,
+}
+
+impl ParsingArgs {
+    /// Converts the parsed arguments into the library's numeric parsing options.
+    pub fn options(&self) -> NumericParsing {
+        NumericParsing {
+            strip_currency: self.strip_currency,
+            thousands_sep: self.thousands_sep.clone(),
+            strip_whitespace: self.strip_whitespace,
+            strict: self.strict,
+        }
+    }
+}
+
+/// Arguments for the `stats` command.
+#[derive(Args, Debug)]
+pub struct StatsArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// The name of the column to analyze. Repeat the flag or separate names with commas to analyze several columns.
+    #[arg(short, long, value_delimiter = ',', default_value = "Amount Received")]
+    pub column_name: Vec<String>,
+
+    /// Select the columns to analyze by their position instead, counting from 1.
+    /// Repeat the flag or separate positions with commas.
+    #[arg(
+        short = 'i',
+        long,
+        value_delimiter = ',',
+        conflicts_with = "column_name"
+    )]
+    pub column_index: Vec<usize>,
+
+    /// Comma-separated list of quantiles to calculate, each between 0 and 1 (e.g. 0.25,0.5,0.95).
+    #[arg(short, long, value_delimiter = ',')]
+    pub quantiles: Vec<f64>,
+
+    /// Comma-separated list of percentiles to calculate, each between 0 and 100 (e.g. 1,5,25,50,75,95,99).
+    /// They are calculated in the same pass as, and reported after, any `--quantiles`.
+    #[arg(short, long, value_delimiter = ',', value_parser = parse_percentile)]
+    pub percentiles: Vec<f64>,
+
+    /// Delta degrees of freedom for std and var: 0 for population, 1 for sample statistics.
+    #[arg(long, default_value_t = 1)]
+    pub ddof: u8,
+
+    /// Calculate the statistics separately for each distinct value of this column.
+    #[arg(short, long)]
+    pub group_by: Option<String>,
+
+    /// With --group-by, order the groups by this statistic of the first column, largest
+    /// first, instead of by group value.
+    #[arg(long, value_enum, requires = "group_by")]
+    pub sort_by: Option<GroupSort>,
+
+    /// With --group-by, only show this many groups, e.g. the 20 with the largest mean
+    /// together with --sort-by mean.
+    #[arg(long, value_name = "N", requires = "group_by")]
+    pub top: Option<usize>,
+
+    /// Calculate the statistics separately for each interval of this date or datetime
+    /// column, as a time series. Text is parsed as datetimes; pass --dtype with a format
+    /// if it isn't recognized.
+    #[arg(long, value_name = "COLUMN", conflicts_with_all = ["group_by", "mad", "mode"])]
+    pub group_by_time: Option<String>,
+
+    /// The length of each --group-by-time interval, e.g. 1h, 1d, 1w, 1mo, 1q or 1y.
+    #[arg(long, default_value = "1d", requires = "group_by_time")]
+    pub every: String,
+
+    /// Also calculate skewness and kurtosis, to quantify how asymmetric and heavy-tailed the values are.
+    #[arg(long)]
+    pub moments: bool,
+
+    /// Also calculate the standard error of the mean and a confidence interval at this level
+    /// (0.95 when given without a value), for when the file is a sample. Boolean columns
+    /// always get an interval for their true ratio, at this level or 0.95.
+    #[arg(long, value_name = "LEVEL", num_args = 0..=1, default_missing_value = "0.95")]
+    pub ci: Option<f64>,
+
+    /// Also calculate the median absolute deviation, a dispersion measure robust to outliers.
+    #[arg(long)]
+    pub mad: bool,
+
+    /// Also calculate the geometric and harmonic means, for positive data such as rates and ratios.
+    #[arg(long)]
+    pub extended: bool,
+
+    /// Also find the most frequent value(s). Works for categorical columns too.
+    #[arg(long)]
+    pub mode: bool,
+
+    /// Estimate the distinct count (HyperLogLog), median and quantiles (t-digest) in a
+    /// single streaming pass instead of calculating them exactly. Uses bounded memory,
+    /// for high-cardinality columns and files that don't fit in memory.
+    #[arg(long)]
+    pub approx: bool,
+
+    /// With --group-by, also test whether the mean of each column differs between the
+    /// groups, with a one-way analysis of variance.
+    #[arg(long, requires = "group_by")]
+    pub anova: bool,
+
+    /// With --group-by, only analyze a random sample of up to this many rows of each
+    /// group, so the largest groups don't dominate. Taken after any --sample.
+    #[arg(
+        long,
+        value_name = "ROWS",
+        requires = "group_by",
+        conflicts_with = "per_file"
+    )]
+    pub sample_per_group: Option<usize>,
+
+    /// With --sample-per-group, sample the same fraction of every group instead, keeping
+    /// their proportions, with that many rows of the average group.
+    #[arg(long, requires = "sample_per_group")]
+    pub proportional: bool,
+
+    /// Also find confidence intervals of the mean, median and any quantiles from this
+    /// many bootstrap resamples, at the --ci level or 0.95. Loads each column into memory.
+    #[arg(long, value_name = "RESAMPLES", conflicts_with_all = ["group_by", "group_by_time"])]
+    pub bootstrap: Option<usize>,
+
+    /// Also calculate the sum and mean weighted by the values of this column.
+    #[arg(short, long)]
+    pub weight_column: Option<String>,
+
+    /// Also calculate the mean without this proportion of the values at each end, e.g. 0.05
+    /// to ignore the lowest and highest 5%.
+    #[arg(long)]
+    pub trim: Option<f64>,
+
+    /// With `--trim`, also calculate the winsorized mean, which clamps the extreme values
+    /// instead of dropping them.
+    #[arg(long, requires = "trim")]
+    pub winsorize: bool,
+
+    /// Comma-separated list of thresholds to count the values below and above, and
+    /// between, e.g. 1000,10000,100000.
+    #[arg(long, value_delimiter = ',', allow_negative_numbers = true)]
+    pub thresholds: Vec<f64>,
+
+    /// Fail with exit code 3 unless the check holds for every column, e.g. 'mean<10000'
+    /// or 'count>=1'. Repeat the flag for several checks.
+    #[arg(long = "assert", value_name = "CHECK")]
+    pub assertions: Vec<Assertion>,
+
+    /// With several input files, also calculate the statistics of each file separately,
+    /// processing the files in parallel, before those of all files combined.
+    #[arg(long)]
+    pub per_file: bool,
+
+    /// Keep running, and re-calculate the statistics whenever an input file changes.
+    #[arg(long)]
+    pub watch: bool,
+
+    /// In watch mode, also list the statistics that changed since the previous run.
+    #[arg(long, requires = "watch")]
+    pub deltas: bool,
+
+    #[command(flatten)]
+    pub parsing: ParsingArgs,
+
+    /// The format used to print the statistics.
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+    pub output: OutputFormat,
+
+    /// Print the statistics through this Handlebars-style template instead, e.g. for a
+    /// one-line summary. It sees `input` and the `stats` list as in the JSON output.
+    #[arg(long, value_name = "FILE", conflicts_with = "output")]
+    pub template: Option<String>,
+
+    /// Also append the statistics to this file, one row per file, column and group
+    /// with the time of the run, to track the dataset's health across runs.
+    #[arg(long, value_name = "FILE")]
+    pub output_file: Option<String>,
+
+    /// The format of the `--output-file`.
+    #[arg(long, value_enum, default_value_t = FileFormat::Csv, requires = "output_file")]
+    pub output_format: FileFormat,
+
+    /// Also send the statistics as metrics after each run, to 'statsd://host:8125' over
+    /// UDP or to a Prometheus Pushgateway URL such as 'http://localhost:9091'. Repeat the
+    /// flag for several targets.
+    #[arg(long, value_name = "TARGET")]
+    pub push: Vec<PushTarget>,
+}
+
+impl StatsArgs {
+    /// Converts the parsed arguments into the library's statistics options.
+    pub fn options(&self) -> StatsOptions {
+        StatsOptions {
+            columns: self.column_name.clone(),
+            quantiles: self
+                .quantiles
+                .iter()
+                .copied()
+                .chain(self.percentiles.iter().map(|p| p / 100.0))
+                .collect(),
+            ddof: self.ddof,
+            group_by: self.group_by.clone(),
+            sort_by: self.sort_by,
+            top: self.top,
+            time_buckets: self.group_by_time.as_ref().map(|column| TimeBuckets {
+                column: column.clone(),
+                every: self.every.clone(),
+            }),
+            parsing: self.parsing.options(),
+            moments: self.moments,
+            confidence_level: self.ci,
+            mad: self.mad,
+            extended: self.extended,
+            mode: self.mode,
+            approx: self.approx,
+            weight_column: self.weight_column.clone(),
+            trim: self.trim,
+            winsorize: self.winsorize,
+            thresholds: self.sorted_thresholds(),
+        }
+    }
+
+    /// Returns the thresholds in increasing order without duplicates, as the library
+    /// expects.
+    fn sorted_thresholds(&self) -> Vec<f64> {
+        let mut thresholds = self.thresholds.clone();
+        thresholds.sort_by(f64::total_cmp);
+        thresholds.dedup();
+        thresholds
+    }
+}
+
+/// Arguments for the `describe` command.
+#[derive(Args, Debug)]
+pub struct DescribeArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// The format used to print the summary.
+    #[arg(short, long, value_parser = results_format(), default_value = "text")]
+    pub output: OutputFormat,
+
+    /// Write the summary to this file instead of printing it, with a row per statistic.
+    #[arg(long, value_name = "FILE")]
+    pub output_file: Option<String>,
+
+    /// The format of the `--output-file`.
+    #[arg(long, value_enum, default_value_t = FileFormat::Csv, requires = "output_file")]
+    pub output_format: FileFormat,
+}
+
+/// Arguments for the `hist` command.
+#[derive(Args, Debug)]
+pub struct HistArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// The name of the column to bin.
+    #[arg(short, long, default_value = "Amount Received")]
+    pub column_name: String,
+
+    /// Select the column to bin by its position instead, counting from 1.
+    #[arg(short = 'i', long, conflicts_with = "column_name")]
+    pub column_index: Option<usize>,
+
+    /// The number of equal-width bins.
+    #[arg(short, long, default_value_t = 10)]
+    pub bins: usize,
+
+    /// The lower bound of the histogram. Defaults to the column's minimum.
+    #[arg(long, allow_negative_numbers = true)]
+    pub min: Option<f64>,
+
+    /// The upper bound of the histogram. Defaults to the column's maximum.
+    #[arg(long, allow_negative_numbers = true)]
+    pub max: Option<f64>,
+
+    #[command(flatten)]
+    pub parsing: ParsingArgs,
+
+    /// The format used to print the histogram.
+    #[arg(short, long, value_parser = results_format(), default_value = "text")]
+    pub output: OutputFormat,
+}
+
+impl HistArgs {
+    /// Converts the parsed arguments into the library's histogram options.
+    pub fn options(&self) -> HistogramOptions {
+        HistogramOptions {
+            bins: self.bins,
+            min: self.min,
+            max: self.max,
+            parsing: self.parsing.options(),
+        }
+    }
+}
+
+/// Arguments for the `freq` command.
+#[derive(Args, Debug)]
+pub struct FreqArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// The name of the column to count values in.
+    #[arg(short, long, default_value = "Amount Received")]
+    pub column_name: String,
+
+    /// Select the column to count values in by its position instead, counting from 1.
+    #[arg(short = 'i', long, conflicts_with = "column_name")]
+    pub column_index: Option<usize>,
+
+    /// The number of most frequent values to list.
+    #[arg(short = 'n', long, default_value_t = 10)]
+    pub top_n: usize,
+
+    /// The format used to print the frequency table.
+    #[arg(short, long, value_parser = results_format(), default_value = "text")]
+    pub output: OutputFormat,
+}
+
+/// Arguments for the `sql` command.
+#[derive(Args, Debug)]
+pub struct SqlArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// The query to run, e.g. 'SELECT "Payment Format", avg("Amount Received") FROM t GROUP BY 1'.
+    pub query: String,
+
+    /// The table name the input is registered under in the query.
+    #[arg(short, long, default_value = "t")]
+    pub table: String,
+
+    /// The format used to print the query result.
+    #[arg(short, long, value_parser = results_format(), default_value = "text")]
+    pub output: OutputFormat,
+}
+
+/// Arguments for the `schema` command.
+#[derive(Args, Debug)]
+pub struct SchemaArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// The format used to print the columns.
+    #[arg(short, long, value_parser = results_format(), default_value = "text")]
+    pub output: OutputFormat,
+}
+
+/// Arguments for the `count` command.
+#[derive(Args, Debug)]
+pub struct CountArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// The format used to print the count.
+    #[arg(short, long, value_parser = results_format(), default_value = "text")]
+    pub output: OutputFormat,
+}
+
+/// Arguments for the `head` and `tail` commands.
+#[derive(Args, Debug)]
+pub struct PreviewArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// The number of rows to print.
+    #[arg(short = 'n', long, default_value_t = 10)]
+    pub rows: usize,
+
+    /// The format used to print the rows.
+    #[arg(short, long, value_parser = results_format(), default_value = "text")]
+    pub output: OutputFormat,
+}
+
+/// Arguments for the `corr` command.
+#[derive(Args, Debug)]
+pub struct CorrArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// The names of the two columns to correlate, separated by a comma or given as two flags.
+    #[arg(short, long, value_delimiter = ',', required_unless_present = "all")]
+    pub column_name: Vec<String>,
+
+    /// Calculate the correlation and covariance matrices of every numeric column instead.
+    #[arg(long, conflicts_with = "column_name")]
+    pub all: bool,
+
+    /// Delta degrees of freedom for the covariance: 0 for population, 1 for sample covariance.
+    #[arg(long, default_value_t = 1)]
+    pub ddof: u8,
+
+    /// The correlation coefficient to calculate. Spearman's also finds monotonic but
+    /// nonlinear relationships.
+    #[arg(long, value_enum, default_value_t = CorrelationMethod::Pearson)]
+    pub method: CorrelationMethod,
+
+    #[command(flatten)]
+    pub parsing: ParsingArgs,
+
+    /// The format used to print the correlation.
+    #[arg(short, long, value_parser = results_format(), default_value = "text")]
+    pub output: OutputFormat,
+}
+
+impl CorrArgs {
+    /// Converts the parsed arguments into the library's correlation options.
+    pub fn options(&self) -> CorrelationOptions {
+        CorrelationOptions {
+            ddof: self.ddof,
+            method: self.method,
+            parsing: self.parsing.options(),
+        }
+    }
+}
+
+/// Arguments for the `outliers` command.
+#[derive(Args, Debug)]
+pub struct OutliersArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// The name of the column to check.
+    #[arg(short, long, default_value = "Amount Received")]
+    pub column_name: String,
+
+    /// Select the column to check by its position instead, counting from 1.
+    #[arg(short = 'i', long, conflicts_with = "column_name")]
+    pub column_index: Option<usize>,
+
+    /// How outliers are detected.
+    #[arg(short, long, value_enum, default_value_t = OutlierMethod::Iqr)]
+    pub method: OutlierMethod,
+
+    /// The number of IQRs (default 1.5) or standard deviations (default 3) beyond which
+    /// a value is an outlier.
+    #[arg(short, long)]
+    pub threshold: Option<f64>,
+
+    /// Also write the complete outlying rows, with their row numbers, to this CSV file.
+    #[arg(long, value_name = "FILE")]
+    pub write_csv: Option<String>,
+
+    #[command(flatten)]
+    pub parsing: ParsingArgs,
+
+    /// The format used to print the outliers.
+    #[arg(short, long, value_parser = results_format(), default_value = "text")]
+    pub output: OutputFormat,
+}
+
+impl OutliersArgs {
+    /// Converts the parsed arguments into the library's outlier options.
+    pub fn options(&self) -> OutlierOptions {
+        OutlierOptions {
+            method: self.method,
+            threshold: self.threshold,
+            parsing: self.parsing.options(),
+        }
+    }
+}
+
+/// Arguments for the `benford` command.
+#[derive(Args, Debug)]
+pub struct BenfordArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// The name of the column to analyze.
+    #[arg(short, long, default_value = "Amount Received")]
+    pub column_name: String,
+
+    /// Select the column to analyze by its position instead, counting from 1.
+    #[arg(short = 'i', long, conflicts_with = "column_name")]
+    pub column_index: Option<usize>,
+
+    /// The p-value below which the deviation from Benford's law is flagged as significant.
+    #[arg(long, default_value_t = 0.05)]
+    pub significance: f64,
+
+    #[command(flatten)]
+    pub parsing: ParsingArgs,
+
+    /// The format used to print the analysis.
+    #[arg(short, long, value_parser = results_format(), default_value = "text")]
+    pub output: OutputFormat,
+}
+
+impl BenfordArgs {
+    /// Converts the parsed arguments into the library's Benford analysis options.
+    pub fn options(&self) -> BenfordOptions {
+        BenfordOptions {
+            significance: self.significance,
+            parsing: self.parsing.options(),
+        }
+    }
+}
+
+/// Arguments for the `profile` command.
+#[derive(Args, Debug)]
+pub struct ProfileArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// The number of histogram bins for numeric columns.
+    #[arg(short, long, default_value_t = 10)]
+    pub bins: usize,
+
+    /// The number of most frequent values listed per column.
+    #[arg(short = 'n', long, default_value_t = 5)]
+    pub top_n: usize,
+
+    /// Write the report to this file instead of printing it, e.g. report.html.
+    #[arg(long, value_name = "FILE")]
+    pub report: Option<String>,
+
+    /// The format of the report. Detected from the `--report` file extension when
+    /// omitted, defaulting to a text summary.
+    #[arg(short, long, value_enum)]
+    pub output: Option<ProfileFormat>,
+}
+
+impl ProfileArgs {
+    /// Converts the parsed arguments into the library's profile options.
+    pub fn options(&self) -> ProfileOptions {
+        ProfileOptions {
+            bins: self.bins,
+            top_n: self.top_n,
+        }
+    }
+
+    /// The format of the report, from `--output` or else the report file's extension.
+    pub fn format(&self) -> ProfileFormat {
+        self.output
+            .or_else(|| self.report.as_deref().and_then(ProfileFormat::from_path))
+            .unwrap_or_default()
+    }
+}
+
+/// Arguments for the `rolling` command.
+#[derive(Args, Debug)]
+pub struct RollingArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// The name of the column to analyze.
+    #[arg(short, long, default_value = "Amount Received")]
+    pub column_name: String,
+
+    /// Select the column to analyze by its position instead, counting from 1.
+    #[arg(short = 'i', long, conflicts_with = "column_name")]
+    pub column_index: Option<usize>,
+
+    /// The extent of each window: a number of rows such as 100, or a duration such as
+    /// 30d, 12h or 1mo, which requires --order-by.
+    #[arg(short, long, required_unless_present = "cumulative")]
+    pub window: Option<RollingWindow>,
+
+    /// Calculate the running count, sum and mean over every row so far instead, e.g. for
+    /// a cumulative volume curve.
+    #[arg(long, conflicts_with = "window")]
+    pub cumulative: bool,
+
+    /// Order the rows by this column first, e.g. a timestamp, instead of keeping the file
+    /// order. Text is parsed as datetimes for duration windows.
+    #[arg(long, value_name = "COLUMN")]
+    pub order_by: Option<String>,
+
+    /// Write the statistics to this file instead of printing them.
+    #[arg(long, value_name = "FILE", alias = "write-csv")]
+    pub output_file: Option<String>,
+
+    /// The format of the `--output-file`.
+    #[arg(long, value_enum, default_value_t = FileFormat::Csv, requires = "output_file")]
+    pub output_format: FileFormat,
+
+    #[command(flatten)]
+    pub parsing: ParsingArgs,
+
+    /// The format used to print the statistics.
+    #[arg(short, long, value_parser = results_format(), default_value = "text")]
+    pub output: OutputFormat,
+}
+
+impl RollingArgs {
+    /// Converts the parsed arguments into the library's rolling statistics options.
+    pub fn options(&self) -> RollingOptions {
+        RollingOptions {
+            window: self.window.clone().unwrap_or(RollingWindow::Cumulative),
+            order_by: self.order_by.clone(),
+            parsing: self.parsing.options(),
+        }
+    }
+}
+
+/// Arguments for the `smooth` command.
+#[derive(Args, Debug)]
+pub struct SmoothArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// The name of the column to smooth.
+    #[arg(short, long, default_value = "Amount Received")]
+    pub column_name: String,
+
+    /// Select the column to smooth by its position instead, counting from 1.
+    #[arg(short = 'i', long, conflicts_with = "column_name")]
+    pub column_index: Option<usize>,
+
+    /// Comma-separated list of simple moving average windows: numbers of rows such as 7,
+    /// or durations such as 30d, which require --order-by.
+    #[arg(long, value_delimiter = ',', required_unless_present = "ewma")]
+    pub sma: Vec<RollingWindow>,
+
+    /// Comma-separated list of the spans, in rows, of exponentially weighted moving
+    /// averages, e.g. 7,30.
+    #[arg(long, value_delimiter = ',')]
+    pub ewma: Vec<usize>,
+
+    /// Order the rows by this column first, e.g. a timestamp, instead of keeping the file
+    /// order. Text is parsed as datetimes for duration windows.
+    #[arg(long, value_name = "COLUMN")]
+    pub order_by: Option<String>,
+
+    /// Write the smoothed series to this file instead of printing it.
+    #[arg(long, value_name = "FILE", alias = "write-csv")]
+    pub output_file: Option<String>,
+
+    /// The format of the `--output-file`.
+    #[arg(long, value_enum, default_value_t = FileFormat::Csv, requires = "output_file")]
+    pub output_format: FileFormat,
+
+    #[command(flatten)]
+    pub parsing: ParsingArgs,
+
+    /// The format used to print the series.
+    #[arg(short, long, value_parser = results_format(), default_value = "text")]
+    pub output: OutputFormat,
+}
+
+impl SmoothArgs {
+    /// Converts the parsed arguments into the library's smoothing options.
+    pub fn options(&self) -> SmoothingOptions {
+        SmoothingOptions {
+            moving_averages: self.sma.clone(),
+            ewma_spans: self.ewma.clone(),
+            order_by: self.order_by.clone(),
+            parsing: self.parsing.options(),
+        }
+    }
+}
+
+/// Arguments for the `dups` command.
+#[derive(Args, Debug)]
+pub struct DupsArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Comma-separated list of columns to compare rows on, e.g. a transaction ID.
+    /// Rows are compared on every column when omitted.
+    #[arg(short, long, value_delimiter = ',')]
+    pub keys: Vec<String>,
+
+    /// Also write every copy of the duplicated rows, sorted so copies are adjacent, to
+    /// this CSV file.
+    #[arg(long, value_name = "FILE")]
+    pub write_csv: Option<String>,
+
+    /// The format used to print the counts.
+    #[arg(short, long, value_parser = results_format(), default_value = "text")]
+    pub output: OutputFormat,
+}
+
+/// Arguments for the `keys` command.
+#[derive(Args, Debug)]
+pub struct KeysArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Comma-separated list of columns to consider. Every column is considered when
+    /// omitted.
+    #[arg(short, long, value_delimiter = ',')]
+    pub columns: Vec<String>,
+
+    /// The most columns to combine into a key.
+    #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u8).range(1..=4))]
+    pub max_size: u8,
+
+    /// The format used to print the keys.
+    #[arg(short, long, value_parser = results_format(), default_value = "text")]
+    pub output: OutputFormat,
+}
+
+impl KeysArgs {
+    /// Converts the parsed arguments into the library's key search options.
+    pub fn options(&self) -> KeyOptions {
+        KeyOptions {
+            columns: self.columns.clone(),
+            max_size: self.max_size as usize,
+        }
+    }
+}
+
+/// Arguments for the `validate` command.
+#[derive(Args, Debug)]
+pub struct ValidateArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// The rules file, with a line like `Amount Received: not_null, >= 0` per column.
+    /// Rules are not_null, unique, comparisons like '<= 1e9', 'in [A, B]' and
+    /// 'matches REGEX'.
+    #[arg(long, value_name = "FILE")]
+    pub rules: String,
+
+    /// The most violating rows to show for each rule.
+    #[arg(long, default_value_t = 5)]
+    pub samples: usize,
+
+    #[command(flatten)]
+    pub parsing: ParsingArgs,
+
+    /// The format used to print the violations.
+    #[arg(short, long, value_parser = results_format(), default_value = "text")]
+    pub output: OutputFormat,
+}
+
+impl ValidateArgs {
+    /// Converts the parsed arguments into the library's validation options.
+    pub fn options(&self) -> ValidationOptions {
+        ValidationOptions {
+            samples: self.samples,
+            parsing: self.parsing.options(),
+        }
+    }
+}
+
+/// Arguments for the `malformed` command.
+#[derive(Args, Debug)]
+pub struct MalformedArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// The most malformed rows to list.
+    #[arg(long, default_value_t = 20)]
+    pub limit: usize,
+
+    /// The format used to print the malformed rows.
+    #[arg(short, long, value_parser = results_format(), default_value = "text")]
+    pub output: OutputFormat,
+}
+
+/// Arguments for the `export` command.
+#[derive(Args, Debug)]
+pub struct ExportArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Comma-separated list of the columns to export, in this order. Every column is
+    /// exported when omitted.
+    #[arg(short, long, value_delimiter = ',')]
+    pub columns: Vec<String>,
+
+    /// Comma-separated list of columns to convert to numbers, cleaned like the
+    /// statistics clean them.
+    #[arg(short, long, value_delimiter = ',')]
+    pub numeric: Vec<String>,
+
+    /// Leave out the rows missing a value in any `--numeric` column, including values
+    /// that couldn't be parsed.
+    #[arg(long, requires = "numeric")]
+    pub drop_nulls: bool,
+
+    #[command(flatten)]
+    pub parsing: ParsingArgs,
+
+    /// The file to write the rows to.
+    #[arg(long, value_name = "FILE")]
+    pub output_file: String,
+
+    /// The format of the `--output-file`. Detected from its extension when omitted,
+    /// defaulting to CSV.
+    #[arg(long, value_enum)]
+    pub output_format: Option<FileFormat>,
+}
+
+impl ExportArgs {
+    /// Converts the parsed arguments into the library's export options.
+    pub fn options(&self) -> ExportOptions {
+        ExportOptions {
+            columns: self.columns.clone(),
+            numeric_columns: self.numeric.clone(),
+            parsing: self.parsing.options(),
+            drop_nulls: self.drop_nulls,
+        }
+    }
+}
+
+/// Arguments for the `normalize` command.
+#[derive(Args, Debug)]
+pub struct NormalizeArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Comma-separated list of the columns to normalize. The other columns are written
+    /// unchanged.
+    #[arg(short, long, value_delimiter = ',', required = true)]
+    pub column_name: Vec<String>,
+
+    /// How to rescale the values: to z-scores, or between the minimum and maximum.
+    #[arg(short, long, value_enum, default_value_t = NormalizeMethod::Zscore)]
+    pub method: NormalizeMethod,
+
+    /// With `--method minmax`, scale between these bounds instead of the minimum and
+    /// maximum, e.g. `0,1000`.
+    #[arg(long, value_name = "LOW,HIGH", value_parser = parse_range, allow_hyphen_values = true)]
+    pub range: Option<(f64, f64)>,
+
+    /// Clamp the values outside the `--range` to its bounds, so every result lies between
+    /// 0 and 1.
+    #[arg(long, requires = "range")]
+    pub clip: bool,
+
+    #[command(flatten)]
+    pub parsing: ParsingArgs,
+
+    /// The file to write the rows to.
+    #[arg(long, value_name = "FILE")]
+    pub output_file: String,
+
+    /// The format of the `--output-file`. Detected from its extension when omitted,
+    /// defaulting to CSV.
+    #[arg(long, value_enum)]
+    pub output_format: Option<FileFormat>,
+}
+
+impl NormalizeArgs {
+    /// Converts the parsed arguments into the library's normalization options.
+    pub fn options(&self) -> NormalizeOptions {
+        NormalizeOptions {
+            method: self.method,
+            range: self.range,
+            clip: self.clip,
+            parsing: self.parsing.options(),
+        }
+    }
+}
+
+/// Arguments for the `serve` command.
+#[derive(Args, Debug)]
+pub struct ServeArgs {
+    /// How to read the input of each request. The files given here are analyzed when a
+    /// request names no file and uploads no data.
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// The address to listen on. Requests can name any file the server can read, so
+    /// only listen beyond localhost on a trusted network.
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    pub bind: String,
+
+    #[command(flatten)]
+    pub parsing: ParsingArgs,
+}
+
+impl ServeArgs {
+    /// Converts the parsed arguments into the library's server options.
+    pub fn options(&self) -> ServerOptions {
+        ServerOptions {
+            input: self.input.options(),
+            parsing: self.parsing.options(),
+        }
+    }
+}
+
+/// Arguments for the `tui` command.
+#[derive(Args, Debug)]
+pub struct TuiArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// The column to select at the start. Defaults to the first column.
+    #[arg(short, long)]
+    pub column_name: Option<String>,
+
+    #[command(flatten)]
+    pub parsing: ParsingArgs,
+}
+
+impl TuiArgs {
+    /// Converts the parsed arguments into the library's explorer options.
+    pub fn options(&self) -> TuiOptions {
+        TuiOptions {
+            input: self.input.options(),
+            parsing: self.parsing.options(),
+            column: self.column_name.clone(),
+        }
+    }
+}
+
+/// Arguments for the `repl` command.
+#[derive(Args, Debug)]
+pub struct ReplArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    #[command(flatten)]
+    pub parsing: ParsingArgs,
+
+    /// The format used to print results, until changed with the `output` command.
+    #[arg(short, long, value_parser = results_format(), default_value = "text")]
+    pub output: OutputFormat,
+}
+
+/// Arguments for the `snapshot` command.
+#[derive(Args, Debug)]
+pub struct SnapshotArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Comma-separated list of the columns to include. Every numeric column is included
+    /// when omitted.
+    #[arg(short, long, value_delimiter = ',')]
+    pub column_name: Vec<String>,
+
+    /// Calculate the statistics for each distinct value of this column.
+    #[arg(short, long)]
+    pub group_by: Option<String>,
+
+    /// Comma-separated list of quantiles to include, each between 0 and 1.
+    #[arg(short, long, value_delimiter = ',')]
+    pub quantiles: Vec<f64>,
+
+    #[command(flatten)]
+    pub parsing: ParsingArgs,
+
+    /// The JSON file to write the baseline to.
+    #[arg(long, value_name = "FILE")]
+    pub baseline: String,
+}
+
+/// Arguments for the `compare` command.
+#[derive(Args, Debug)]
+pub struct CompareArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// The baseline to compare to, as written by `snapshot` or `stats -o json`.
+    #[arg(long, value_name = "FILE")]
+    pub baseline: String,
+
+    /// How far any statistic may drift: a percentage of its baseline value such as '10%',
+    /// or an absolute amount such as '0.5'.
+    #[arg(long, value_name = "DRIFT", default_value = "10%")]
+    pub max_drift: Tolerance,
+
+    /// How far a single statistic may drift, overriding `--max-drift`, e.g.
+    /// 'null_percent=1' or 'q0.95=20%'. Repeat the flag for several statistics.
+    #[arg(long, value_name = "STAT=DRIFT", value_parser = parse_tolerance)]
+    pub tolerance: Vec<(String, Tolerance)>,
+
+    #[command(flatten)]
+    pub parsing: ParsingArgs,
+
+    /// The format used to print the report.
+    #[arg(short, long, value_parser = results_format(), default_value = "text")]
+    pub output: OutputFormat,
+}
+
+impl CompareArgs {
+    /// Converts the parsed arguments into the library's drift options.
+    pub fn options(&self) -> DriftOptions {
+        DriftOptions {
+            max_drift: self.max_drift,
+            tolerances: self.tolerance.clone(),
+        }
+    }
+}
+
+/// Arguments for the `diff` command.
+#[derive(Args, Debug)]
+pub struct DiffArgs {
+    /// How to read both files. They are given as the two positional arguments instead of
+    /// with `--file-path`.
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// The first, or earlier, file.
+    #[arg(value_name = "FILE_A")]
+    pub left: String,
+
+    /// The second, or later, file.
+    #[arg(value_name = "FILE_B")]
+    pub right: String,
+
+    /// Comma-separated list of the columns to compare.
+    #[arg(short, long, value_delimiter = ',', required = true)]
+    pub column_name: Vec<String>,
+
+    /// Comma-separated list of quantiles to also compare, each between 0 and 1.
+    #[arg(short, long, value_delimiter = ',')]
+    pub quantiles: Vec<f64>,
+
+    #[command(flatten)]
+    pub parsing: ParsingArgs,
+
+    /// The format used to print the comparison.
+    #[arg(short, long, value_parser = results_format(), default_value = "text")]
+    pub output: OutputFormat,
+}
+
+/// Arguments for the `ttest` command.
+#[derive(Args, Debug)]
+pub struct TtestArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// The name of the column to compare.
+    #[arg(short, long, default_value = "Amount Received")]
+    pub column_name: String,
+
+    /// Compare the input with this file, read the same way.
+    #[arg(long, value_name = "FILE", required_unless_present = "group_by")]
+    pub against: Option<String>,
+
+    /// Compare two groups of the input, by their values in this column.
+    #[arg(short, long, conflicts_with = "against")]
+    pub group_by: Option<String>,
+
+    /// The two groups to compare, e.g. `EUR,USD`. Needed when the column has more than
+    /// two distinct values.
+    #[arg(long, value_delimiter = ',', num_args = 1, requires = "group_by")]
+    pub groups: Vec<String>,
+
+    /// The p-value below which a difference is flagged as significant.
+    #[arg(long, default_value_t = 0.05)]
+    pub significance: f64,
+
+    #[command(flatten)]
+    pub parsing: ParsingArgs,
+
+    /// The format used to print the comparison.
+    #[arg(short, long, value_parser = results_format(), default_value = "text")]
+    pub output: OutputFormat,
+}
+
+impl TtestArgs {
+    /// Converts the parsed arguments into the library's significance test options.
+    pub fn options(&self) -> SignificanceOptions {
+        SignificanceOptions {
+            significance: self.significance,
+            parsing: self.parsing.options(),
+        }
+    }
+}
+
+/// Arguments for the `crosstab` command.
+#[derive(Args, Debug)]
+pub struct CrosstabArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// The names of the two columns to cross-tabulate, separated by a comma or given as
+    /// two flags. The first one labels the rows of the table.
+    #[arg(short, long, value_delimiter = ',', required = true)]
+    pub column_name: Vec<String>,
+
+    /// The p-value below which the association is flagged as significant.
+    #[arg(long, default_value_t = 0.05)]
+    pub significance: f64,
+
+    /// The format used to print the table and test.
+    #[arg(short, long, value_parser = results_format(), default_value = "text")]
+    pub output: OutputFormat,
+}
+
+impl CrosstabArgs {
+    /// Converts the parsed arguments into the library's significance test options.
+    pub fn options(&self) -> SignificanceOptions {
+        SignificanceOptions {
+            significance: self.significance,
+            ..Default::default()
+        }
+    }
+}
+
+/// Arguments for the `regress` command.
+#[derive(Args, Debug)]
+pub struct RegressArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// The name of the explanatory column.
+    #[arg(short, long = "x-column", value_name = "COLUMN")]
+    pub x: String,
+
+    /// The name of the response column, predicted from the explanatory one.
+    #[arg(short, long = "y-column", value_name = "COLUMN")]
+    pub y: String,
+
+    #[command(flatten)]
+    pub parsing: ParsingArgs,
+
+    /// The format used to print the fit.
+    #[arg(short, long, value_parser = results_format(), default_value = "text")]
+    pub output: OutputFormat,
+}
+
+/// Arguments for the `acf` command.
+#[derive(Args, Debug)]
+pub struct AcfArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// The name of the column to analyze.
+    #[arg(short, long, default_value = "Amount Received")]
+    pub column_name: String,
+
+    /// Select the column to analyze by its position instead, counting from 1.
+    #[arg(short = 'i', long, conflicts_with = "column_name")]
+    pub column_index: Option<usize>,
+
+    /// Order the rows by this column first, e.g. a date, instead of keeping the file order.
+    #[arg(long, value_name = "COLUMN")]
+    pub order_by: Option<String>,
+
+    /// Comma-separated list of the lags, in rows, to calculate the autocorrelation at.
+    /// Defaults to 1 through 14.
+    #[arg(short, long, value_delimiter = ',')]
+    pub lags: Vec<usize>,
+
+    #[command(flatten)]
+    pub parsing: ParsingArgs,
+
+    /// The format used to print the autocorrelation.
+    #[arg(short, long, value_parser = results_format(), default_value = "text")]
+    pub output: OutputFormat,
+}
+
+impl AcfArgs {
+    /// Converts the parsed arguments into the library's autocorrelation options.
+    pub fn options(&self) -> AutocorrelationOptions {
+        AutocorrelationOptions {
+            lags: self.lags.clone(),
+            order_by: self.order_by.clone(),
+            parsing: self.parsing.options(),
+        }
+    }
+}
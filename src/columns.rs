@@ -0,0 +1,113 @@
+//! Resolving the columns named on the command line against the input's header.
+//!
+//! Header names often differ from what users type by a typo, case or a trailing space.
+//! Surrounding whitespace is ignored when matching, and a column that isn't found is
+//! reported with the closest existing names.
+
+use anyhow::{Result, bail};
+use polars::prelude::*;
+
+/// The most suggestions listed when a column isn't found.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// How a requested column name is matched against the input's header.
+#[derive(Debug, Clone, Default)]
+pub struct ColumnLookup {
+    /// Use the closest column instead of failing when no column has the exact name,
+    /// as long as exactly one column is closest.
+    pub fuzzy: bool,
+    /// Match column names regardless of case.
+    pub ignore_case: bool,
+}
+
+/// Returns the name of the input column matching `name`.
+///
+/// An exact match is preferred; otherwise names are compared without surrounding
+/// whitespace (and case, with `lookup.ignore_case`). Fails with a "did you mean" list of the closest column names if there is no exact
+/// match, unless `lookup.fuzzy` allows picking an unambiguous closest one.
+pub fn resolve_column(lf: &mut LazyFrame, name: &str, lookup: &ColumnLookup) -> Result<String> {
+    let schema = lf.collect_schema()?;
+    if schema.contains(name) {
+        return Ok(name.to_string());
+    }
+
+    let wanted = normalize(name, lookup);
+    let matches: Vec<_> = schema
+        .iter_names()
+        .filter(|candidate| normalize(candidate, lookup) == wanted)
+        .collect();
+    match matches.as_slice() {
+        [] => {}
+        [column] => return Ok(column.to_string()),
+        _ => bail!(
+            "Column name '{}' is ambiguous, it matches {}",
+            name,
+            quoted(matches.iter().map(|n| n.as_str()))
+        ),
+    }
+
+    let suggestions = closest_names(schema.iter_names().map(|n| n.as_str()), name);
+    match suggestions.as_slice() {
+        [] => bail!(
+            "No column named '{}', the columns are: {}",
+            name,
+            quoted(schema.iter_names().map(|n| n.as_str()))
+        ),
+        [(closest, distance), rest @ ..]
+            if lookup.fuzzy && rest.first().is_none_or(|(_, d)| d > distance) =>
+        {
+            Ok(closest.to_string())
+        }
+        _ => bail!(
+            "No column named '{}'. Did you mean {}?",
+            name,
+            quoted(suggestions.iter().map(|(n, _)| *n))
+        ),
+    }
+}
+
+/// Returns the name of the column at a one-based position, for selecting columns by
+/// position, e.g. in files without a header row.
+pub fn column_at(lf: &mut LazyFrame, position: usize) -> Result<String> {
+    let schema = lf.collect_schema()?;
+    match position.checked_sub(1).and_then(|i| schema.get_at_index(i)) {
+        Some((name, _)) => Ok(name.to_string()),
+        None => bail!(
+            "Column position {} is out of range, the input has {} columns (counting from 1)",
+            position,
+            schema.len()
+        ),
+    }
+}
+
+/// Normalizes a column name for comparison according to the lookup options.
+fn normalize(name: &str, lookup: &ColumnLookup) -> String {
+    if lookup.ignore_case {
+        name.trim().to_lowercase()
+    } else {
+        name.trim().to_string()
+    }
+}
+
+/// Finds the names within a small edit distance of `name`, closest first.
+///
+/// The allowed distance grows with the length of the name, so that short names don't
+/// match everything.
+fn closest_names<'a>(names: impl Iterator<Item = &'a str>, name: &str) -> Vec<(&'a str, usize)> {
+    let max_distance = (name.chars().count() / 3).max(2);
+    let mut close: Vec<_> = names
+        .map(|candidate| (candidate, strsim::levenshtein(candidate, name)))
+        .filter(|(_, distance)| *distance <= max_distance)
+        .collect();
+    close.sort_by_key(|(_, distance)| *distance);
+    close.truncate(MAX_SUGGESTIONS);
+    close
+}
+
+/// Joins names as a quoted, comma-separated list.
+fn quoted<'a>(names: impl Iterator<Item = &'a str>) -> String {
+    names
+        .map(|name| format!("'{}'", name))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
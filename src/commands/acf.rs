@@ -0,0 +1,21 @@
+use anyhow::Result;
+use csv_stats::autocorrelation::compute_autocorrelation;
+use csv_stats::input::scan;
+use csv_stats::output::print_autocorrelation;
+
+use super::{resolved_column, selected_column};
+use crate::cli::AcfArgs;
+
+/// Calculates the autocorrelation of a column at the requested lags and prints it.
+pub fn run(args: &AcfArgs) -> Result<()> {
+    let mut lf = scan(&args.input.options())?;
+    let lookup = args.input.lookup();
+    let column = selected_column(&mut lf, &args.column_name, args.column_index, &lookup)?;
+    let mut options = args.options();
+    if let Some(name) = &options.order_by {
+        options.order_by = Some(resolved_column(&mut lf, name, &lookup)?);
+    }
+    let acf = compute_autocorrelation(lf, &column, &options)?;
+
+    print_autocorrelation(args.output, &acf)
+}
@@ -0,0 +1,21 @@
+use anyhow::Result;
+use csv_stats::benford::compute_benford;
+use csv_stats::input::scan;
+use csv_stats::output::print_benford;
+
+use super::selected_column;
+use crate::cli::BenfordArgs;
+
+/// Tests the leading digits of a column against Benford's law and prints the result.
+pub fn run(args: &BenfordArgs) -> Result<()> {
+    let mut lf = scan(&args.input.options())?;
+    let column = selected_column(
+        &mut lf,
+        &args.column_name,
+        args.column_index,
+        &args.input.lookup(),
+    )?;
+    let report = compute_benford(lf, &column, &args.options())?;
+
+    print_benford(args.output, &report)
+}
@@ -0,0 +1,21 @@
+use anyhow::Result;
+use csv_stats::compute_stats;
+use csv_stats::drift::{baseline_options, compare_stats, read_baseline};
+use csv_stats::input::scan;
+use csv_stats::output::print_drift;
+
+use crate::cli::CompareArgs;
+
+/// Recalculates the statistics of a baseline and prints how far they drifted, failing if
+/// any drifted beyond its tolerance.
+pub fn run(args: &CompareArgs) -> Result<()> {
+    let baseline = read_baseline(&args.baseline)?;
+    let mut options = baseline_options(&baseline);
+    options.parsing = args.parsing.options();
+    let lf = scan(&args.input.options())?;
+    let current = compute_stats(lf, &options)?;
+
+    let report = compare_stats(&baseline, &current, &args.options())?;
+    print_drift(args.output, &report)?;
+    report.check()
+}
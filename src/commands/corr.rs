@@ -0,0 +1,42 @@
+use anyhow::{Result, bail};
+use csv_stats::correlation::{compute_correlation_matrix, compute_correlations};
+use csv_stats::input::{input_name, numeric_columns, scan};
+use csv_stats::output::{print_correlation, print_correlation_matrix};
+
+use super::resolved_column;
+use crate::cli::CorrArgs;
+
+/// Calculates the correlation between the two requested columns, or between every pair
+/// of numeric columns with `--all`.
+pub fn run(args: &CorrArgs) -> Result<()> {
+    let input = args.input.options();
+    let mut lf = scan(&input)?;
+
+    if args.all {
+        let columns = numeric_columns(&mut lf)?;
+        if columns.len() < 2 {
+            bail!(
+                "Need at least two numeric columns to correlate, found {} in '{}'",
+                columns.len(),
+                input_name(&input)
+            );
+        }
+        let matrix = compute_correlation_matrix(lf, &columns, &args.options())?;
+        return print_correlation_matrix(args.output, &matrix);
+    }
+
+    let [x, y] = args.column_name.as_slice() else {
+        bail!(
+            "Expected exactly two columns to correlate, got {}",
+            args.column_name.len()
+        );
+    };
+    let lookup = args.input.lookup();
+    let pair = (
+        resolved_column(&mut lf, x, &lookup)?,
+        resolved_column(&mut lf, y, &lookup)?,
+    );
+    let correlations = compute_correlations(lf, &[pair], &args.options())?;
+
+    print_correlation(args.output, &correlations[0])
+}
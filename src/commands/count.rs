@@ -0,0 +1,14 @@
+use anyhow::Result;
+use csv_stats::input::scan;
+use csv_stats::output::print_count;
+use csv_stats::preview::count_rows;
+
+use crate::cli::CountArgs;
+
+/// Prints the number of rows of the input.
+pub fn run(args: &CountArgs) -> Result<()> {
+    let lf = scan(&args.input.options())?;
+    let count = count_rows(lf)?;
+
+    print_count(args.output, count)
+}
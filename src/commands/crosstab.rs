@@ -0,0 +1,25 @@
+use anyhow::{Result, bail};
+use csv_stats::input::scan;
+use csv_stats::output::print_chi_square;
+use csv_stats::significance::chi_square_independence;
+
+use super::resolved_column;
+use crate::cli::CrosstabArgs;
+
+/// Cross-tabulates the two requested columns and prints the table with the chi-square
+/// test of their independence.
+pub fn run(args: &CrosstabArgs) -> Result<()> {
+    let mut lf = scan(&args.input.options())?;
+    let [rows, columns] = args.column_name.as_slice() else {
+        bail!(
+            "Expected exactly two columns to cross-tabulate, got {}",
+            args.column_name.len()
+        );
+    };
+    let lookup = args.input.lookup();
+    let rows = resolved_column(&mut lf, rows, &lookup)?;
+    let columns = resolved_column(&mut lf, columns, &lookup)?;
+    let report = chi_square_independence(lf, &rows, &columns, &args.options())?;
+
+    print_chi_square(args.output, &report)
+}
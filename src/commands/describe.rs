@@ -0,0 +1,36 @@
+use anyhow::{Result, bail};
+
+use csv_stats::input::{input_name, numeric_columns, scan};
+use csv_stats::output::{describe_dataframe, print_describe, write_dataframe};
+use csv_stats::{StatsOptions, compute_stats};
+
+use crate::cli::DescribeArgs;
+
+/// The quartiles reported for every column, matching pandas `describe()`.
+const QUARTILES: [f64; 3] = [0.25, 0.5, 0.75];
+
+/// Summarizes every numeric column of the file in a single pass, and prints the summary
+/// or saves it to a file.
+pub fn run(args: &DescribeArgs) -> Result<()> {
+    let input = args.input.options();
+    let mut lf = scan(&input)?;
+
+    // Inspect the inferred schema to find the columns worth describing.
+    let columns = numeric_columns(&mut lf)?;
+    if columns.is_empty() {
+        bail!("No numeric columns found in '{}'", input_name(&input));
+    }
+
+    // The default options use the sample standard deviation, as pandas reports it.
+    let options = StatsOptions {
+        columns,
+        quantiles: QUARTILES.to_vec(),
+        ..Default::default()
+    };
+    let stats = compute_stats(lf, &options)?;
+
+    match &args.output_file {
+        Some(path) => write_dataframe(path, &mut describe_dataframe(&stats)?, args.output_format),
+        None => print_describe(args.output, &stats),
+    }
+}
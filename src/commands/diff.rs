@@ -0,0 +1,38 @@
+use anyhow::{Result, bail};
+use csv_stats::compute_stats;
+use csv_stats::input::scan;
+use csv_stats::output::print_diff;
+use csv_stats::stats::{SelectedStats, StatsOptions};
+
+use super::resolved_column;
+use crate::cli::DiffArgs;
+
+/// Calculates the statistics of the columns in both files and prints them side by side.
+pub fn run(args: &DiffArgs) -> Result<()> {
+    if !args.input.file_path.is_empty() {
+        bail!("Give the two files to compare as arguments, without --file-path");
+    }
+    let left = analyze(args, &args.left)?;
+    let right = analyze(args, &args.right)?;
+
+    print_diff(args.output, [&args.left, &args.right], &left, &right)
+}
+
+/// Calculates the statistics of the columns in one of the files.
+fn analyze(args: &DiffArgs, path: &str) -> Result<Vec<SelectedStats>> {
+    let mut input = args.input.options();
+    input.paths = vec![path.to_string()];
+    let mut lf = scan(&input)?;
+    let lookup = args.input.lookup();
+    let options = StatsOptions {
+        columns: args
+            .column_name
+            .iter()
+            .map(|name| resolved_column(&mut lf, name, &lookup))
+            .collect::<Result<_>>()?,
+        quantiles: args.quantiles.clone(),
+        parsing: args.parsing.options(),
+        ..Default::default()
+    };
+    compute_stats(lf, &options)
+}
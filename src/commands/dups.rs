@@ -0,0 +1,24 @@
+use anyhow::Result;
+use csv_stats::duplicates::compute_duplicates;
+use csv_stats::input::scan;
+use csv_stats::output::{print_duplicates, write_csv};
+
+use super::resolved_column;
+use crate::cli::DupsArgs;
+
+/// Counts the duplicated rows, prints the counts, and optionally saves the rows.
+pub fn run(args: &DupsArgs) -> Result<()> {
+    let mut lf = scan(&args.input.options())?;
+    let lookup = args.input.lookup();
+    let keys = args
+        .keys
+        .iter()
+        .map(|name| resolved_column(&mut lf, name, &lookup))
+        .collect::<Result<Vec<_>>>()?;
+    let mut duplicates = compute_duplicates(lf, &keys, args.write_csv.is_some())?;
+
+    if let Some(path) = &args.write_csv {
+        write_csv(path, &mut duplicates.rows)?;
+    }
+    print_duplicates(args.output, &duplicates.report)
+}
@@ -0,0 +1,29 @@
+use anyhow::Result;
+use csv_stats::engine::collect;
+use csv_stats::export::prepare_export;
+use csv_stats::input::scan;
+use csv_stats::output::{FileFormat, write_dataframe};
+
+use super::resolved_column;
+use crate::cli::ExportArgs;
+
+/// Writes the filtered and cleaned rows of the input to a CSV or Parquet file.
+pub fn run(args: &ExportArgs) -> Result<()> {
+    let mut lf = scan(&args.input.options())?;
+    let lookup = args.input.lookup();
+    let mut options = args.options();
+    for columns in [&mut options.columns, &mut options.numeric_columns] {
+        *columns = columns
+            .iter()
+            .map(|name| resolved_column(&mut lf, name, &lookup))
+            .collect::<Result<_>>()?;
+    }
+    let mut df = collect(prepare_export(lf, &options)?)?;
+
+    let format = args
+        .output_format
+        .unwrap_or_else(|| FileFormat::from_path(&args.output_file));
+    write_dataframe(&args.output_file, &mut df, format)?;
+    eprintln!("Wrote {} row(s) to '{}'", df.height(), args.output_file);
+    Ok(())
+}
@@ -0,0 +1,21 @@
+use anyhow::Result;
+use csv_stats::frequency::compute_frequencies;
+use csv_stats::input::scan;
+use csv_stats::output::print_frequencies;
+
+use super::selected_column;
+use crate::cli::FreqArgs;
+
+/// Counts the values of a single column and prints the most frequent ones.
+pub fn run(args: &FreqArgs) -> Result<()> {
+    let mut lf = scan(&args.input.options())?;
+    let column = selected_column(
+        &mut lf,
+        &args.column_name,
+        args.column_index,
+        &args.input.lookup(),
+    )?;
+    let table = compute_frequencies(lf, &column, args.top_n)?;
+
+    print_frequencies(args.output, &table)
+}
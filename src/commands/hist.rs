@@ -0,0 +1,21 @@
+use anyhow::Result;
+use csv_stats::histogram::compute_histogram;
+use csv_stats::input::scan;
+use csv_stats::output::print_histogram;
+
+use super::selected_column;
+use crate::cli::HistArgs;
+
+/// Bins a single column and prints the histogram in the requested format.
+pub fn run(args: &HistArgs) -> Result<()> {
+    let mut lf = scan(&args.input.options())?;
+    let column = selected_column(
+        &mut lf,
+        &args.column_name,
+        args.column_index,
+        &args.input.lookup(),
+    )?;
+    let histogram = compute_histogram(lf, &column, &args.options())?;
+
+    print_histogram(args.output, &histogram)
+}
@@ -0,0 +1,22 @@
+use anyhow::Result;
+use csv_stats::input::scan;
+use csv_stats::keys::find_keys;
+use csv_stats::output::print_keys;
+
+use super::resolved_column;
+use crate::cli::KeysArgs;
+
+/// Finds the unique keys of the input and prints them.
+pub fn run(args: &KeysArgs) -> Result<()> {
+    let mut lf = scan(&args.input.options())?;
+    let lookup = args.input.lookup();
+    let mut options = args.options();
+    options.columns = options
+        .columns
+        .iter()
+        .map(|name| resolved_column(&mut lf, name, &lookup))
+        .collect::<Result<_>>()?;
+    let report = find_keys(lf, &options)?;
+
+    print_keys(args.output, &report)
+}
@@ -0,0 +1,11 @@
+use anyhow::Result;
+use csv_stats::malformed::find_malformed_rows;
+use csv_stats::output::print_malformed;
+
+use crate::cli::MalformedArgs;
+
+/// Scans the input files for malformed rows and prints them.
+pub fn run(args: &MalformedArgs) -> Result<()> {
+    let report = find_malformed_rows(&args.input.options(), args.limit)?;
+    print_malformed(args.output, &report)
+}
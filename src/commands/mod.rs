@@ -0,0 +1,123 @@
+//! Command dispatch: each subcommand has its own handler module.
+
+mod acf;
+mod benford;
+mod compare;
+mod corr;
+mod count;
+mod crosstab;
+mod describe;
+mod diff;
+mod dups;
+mod export;
+mod freq;
+mod head;
+mod hist;
+mod keys;
+mod malformed;
+mod normalize;
+mod outliers;
+mod profile;
+mod regress;
+mod repl;
+mod rolling;
+mod schema;
+mod serve;
+mod smooth;
+mod snapshot;
+mod sql;
+mod stats;
+mod tail;
+mod ttest;
+mod tui;
+mod validate;
+
+use anyhow::Result;
+use csv_stats::columns::{ColumnLookup, column_at, resolve_column};
+use csv_stats::input::InputOptions;
+use csv_stats::malformed::find_malformed_rows;
+use polars::prelude::{LazyFrame, PolarsError};
+
+use crate::cli::Command;
+
+/// The most malformed rows listed when reading the input fails.
+const MALFORMED_EXAMPLES: usize = 10;
+
+/// Runs the handler for the given subcommand.
+pub fn run(command: &Command) -> Result<()> {
+    let result = match command {
+        Command::Stats(args) => stats::run(args),
+        Command::Describe(args) => describe::run(args),
+        Command::Hist(args) => hist::run(args),
+        Command::Freq(args) => freq::run(args),
+        Command::Sql(args) => sql::run(args),
+        Command::Schema(args) => schema::run(args),
+        Command::Head(args) => head::run(args),
+        Command::Tail(args) => tail::run(args),
+        Command::Corr(args) => corr::run(args),
+        Command::Outliers(args) => outliers::run(args),
+        Command::Count(args) => count::run(args),
+        Command::Benford(args) => benford::run(args),
+        Command::Profile(args) => profile::run(args),
+        Command::Rolling(args) => rolling::run(args),
+        Command::Dups(args) => dups::run(args),
+        Command::Keys(args) => keys::run(args),
+        Command::Validate(args) => validate::run(args),
+        Command::Malformed(args) => malformed::run(args),
+        Command::Export(args) => export::run(args),
+        Command::Serve(args) => serve::run(args),
+        Command::Tui(args) => tui::run(args),
+        Command::Repl(args) => repl::run(args),
+        Command::Snapshot(args) => snapshot::run(args),
+        Command::Compare(args) => compare::run(args),
+        Command::Diff(args) => diff::run(args),
+        Command::Ttest(args) => ttest::run(args),
+        Command::Crosstab(args) => crosstab::run(args),
+        Command::Regress(args) => regress::run(args),
+        Command::Acf(args) => acf::run(args),
+        Command::Smooth(args) => smooth::run(args),
+        Command::Normalize(args) => normalize::run(args),
+    };
+    result.map_err(|err| with_malformed_rows(err, &command.input().options()))
+}
+
+/// Adds the malformed rows of the input, if any, to an error from reading it, since the
+/// CSV reader's own errors don't say which line is at fault.
+fn with_malformed_rows(err: anyhow::Error, input: &InputOptions) -> anyhow::Error {
+    if !err.chain().any(|cause| cause.is::<PolarsError>()) {
+        return err;
+    }
+    match find_malformed_rows(input, MALFORMED_EXAMPLES) {
+        Ok(report) if report.malformed > 0 => {
+            let mut message = format!("The input has {} malformed row(s)", report.malformed);
+            for row in &report.examples {
+                message.push_str(&format!("\n  {}", row));
+            }
+            err.context(message)
+        }
+        _ => err,
+    }
+}
+
+/// Returns the column selected by `--column-index` if given, or else by `--column-name`.
+fn selected_column(
+    lf: &mut LazyFrame,
+    name: &str,
+    position: Option<usize>,
+    lookup: &ColumnLookup,
+) -> Result<String> {
+    match position {
+        Some(position) => column_at(lf, position),
+        None => resolved_column(lf, name, lookup),
+    }
+}
+
+/// Looks up a column named on the command line, noting on stderr when `--fuzzy` picked
+/// a column with a different name.
+fn resolved_column(lf: &mut LazyFrame, name: &str, lookup: &ColumnLookup) -> Result<String> {
+    let column = resolve_column(lf, name, lookup)?;
+    if column != name {
+        eprintln!("Using column '{}' for '{}'", column, name);
+    }
+    Ok(column)
+}
@@ -0,0 +1,42 @@
+use anyhow::Result;
+use csv_stats::engine::collect;
+use csv_stats::input::scan;
+use csv_stats::normalize::{NormalizeMethod, normalize};
+use csv_stats::output::{FileFormat, write_dataframe};
+
+use super::resolved_column;
+use crate::cli::NormalizeArgs;
+
+/// Writes the input to a file with the requested columns normalized.
+pub fn run(args: &NormalizeArgs) -> Result<()> {
+    let mut lf = scan(&args.input.options())?;
+    let lookup = args.input.lookup();
+    let columns: Vec<String> = args
+        .column_name
+        .iter()
+        .map(|name| resolved_column(&mut lf, name, &lookup))
+        .collect::<Result<_>>()?;
+    let (lf, scalings) = normalize(lf, &columns, &args.options())?;
+    for s in &scalings {
+        match args.method {
+            NormalizeMethod::Zscore => eprintln!(
+                "Standardized '{}' with mean {:.4} and std {:.4}",
+                s.column, s.shift, s.scale
+            ),
+            NormalizeMethod::Minmax => eprintln!(
+                "Scaled '{}' from {:.4} to {:.4}",
+                s.column,
+                s.shift,
+                s.shift + s.scale
+            ),
+        }
+    }
+    let mut df = collect(lf)?;
+
+    let format = args
+        .output_format
+        .unwrap_or_else(|| FileFormat::from_path(&args.output_file));
+    write_dataframe(&args.output_file, &mut df, format)?;
+    eprintln!("Wrote {} row(s) to '{}'", df.height(), args.output_file);
+    Ok(())
+}
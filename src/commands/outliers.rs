@@ -0,0 +1,24 @@
+use anyhow::Result;
+use csv_stats::input::scan;
+use csv_stats::outliers::compute_outliers;
+use csv_stats::output::{print_outliers, write_csv};
+
+use super::selected_column;
+use crate::cli::OutliersArgs;
+
+/// Finds the outliers of a column, prints them, and optionally saves the full rows.
+pub fn run(args: &OutliersArgs) -> Result<()> {
+    let mut lf = scan(&args.input.options())?;
+    let column = selected_column(
+        &mut lf,
+        &args.column_name,
+        args.column_index,
+        &args.input.lookup(),
+    )?;
+    let mut outliers = compute_outliers(lf, &column, &args.options())?;
+
+    if let Some(path) = &args.write_csv {
+        write_csv(path, &mut outliers.rows)?;
+    }
+    print_outliers(args.output, &outliers.report)
+}
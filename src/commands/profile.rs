@@ -0,0 +1,23 @@
+use anyhow::{Context, Result};
+use csv_stats::input::{input_name, scan};
+use csv_stats::profile::compute_profile;
+use csv_stats::report::render_profile;
+
+use crate::cli::ProfileArgs;
+
+/// Profiles every column of the input and prints the report or writes it to a file.
+pub fn run(args: &ProfileArgs) -> Result<()> {
+    let input = args.input.options();
+    let lf = scan(&input)?;
+    let profile = compute_profile(lf, &input_name(&input), &args.options())?;
+    let report = render_profile(args.format(), &profile)?;
+
+    match &args.report {
+        Some(path) => std::fs::write(path, report)
+            .with_context(|| format!("Failed to write the report to '{}'", path)),
+        None => {
+            print!("{}", report);
+            Ok(())
+        }
+    }
+}
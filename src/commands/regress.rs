@@ -0,0 +1,18 @@
+use anyhow::Result;
+use csv_stats::input::scan;
+use csv_stats::output::print_regression;
+use csv_stats::regression::compute_regression;
+
+use super::resolved_column;
+use crate::cli::RegressArgs;
+
+/// Fits the response column on the explanatory one and prints the line.
+pub fn run(args: &RegressArgs) -> Result<()> {
+    let mut lf = scan(&args.input.options())?;
+    let lookup = args.input.lookup();
+    let x = resolved_column(&mut lf, &args.x, &lookup)?;
+    let y = resolved_column(&mut lf, &args.y, &lookup)?;
+    let regression = compute_regression(lf, &x, &y, &args.parsing.options())?;
+
+    print_regression(args.output, &regression)
+}
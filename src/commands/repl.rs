@@ -0,0 +1,196 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use anyhow::{Result, anyhow, bail};
+use clap::ValueEnum;
+use csv_stats::columns::ColumnLookup;
+use csv_stats::compute_stats;
+use csv_stats::engine::collect;
+use csv_stats::filter::parse_filter;
+use csv_stats::histogram::{HistogramOptions, compute_histogram};
+use csv_stats::input::{input_name, numeric_columns, scan};
+use csv_stats::output::{
+    OutputFormat, print_count, print_dataframe, print_histogram, print_schema, print_stats,
+};
+use csv_stats::preview::{count_rows, head};
+use csv_stats::schema::inspect_schema;
+use csv_stats::stats::{NumericParsing, StatsOptions};
+use polars::prelude::{Expr, IntoLazy, LazyFrame};
+
+use super::resolved_column;
+use crate::cli::ReplArgs;
+
+/// The commands of the REPL, as listed by `help`.
+const HELP: &str = "\
+Commands:
+  stats [COLUMN, ...]   Calculate the statistics of these columns, or of every numeric one
+  hist COLUMN           Print a histogram of the column
+  count                 Count the rows
+  head [N]              Print the first N rows (default 10)
+  columns               List the columns with their types
+  filter [EXPRESSION]   Only analyze the matching rows, e.g. col(\"Amount\") > 100;
+                        without an expression, show the filter. 'filter off' removes it
+  group [COLUMN]        Calculate the statistics per group of this column; 'group off' stops
+  output FORMAT         Print results as text, json, markdown or yaml
+  help                  Show this list
+  quit                  Leave (or press Ctrl-D)";
+
+/// Loads the input into memory once, then runs the commands typed at the prompt against
+/// it until `quit` or the end of the input.
+pub fn run(args: &ReplArgs) -> Result<()> {
+    let input = args.input.options();
+    let name = input_name(&input);
+    eprintln!("Loading {}...", name);
+    let data = collect(scan(&input)?)?;
+    eprintln!(
+        "Loaded {} row(s) and {} column(s). Type 'help' for the commands.",
+        data.height(),
+        data.width()
+    );
+
+    let mut session = Session {
+        data: data.lazy(),
+        input: name,
+        lookup: args.input.lookup(),
+        parsing: args.parsing.options(),
+        output: args.output,
+        filter: None,
+        group_by: None,
+    };
+    let interactive = io::stdin().is_terminal();
+    let mut lines = io::stdin().lock().lines();
+    loop {
+        if interactive {
+            print!("> ");
+            io::stdout().flush()?;
+        }
+        let Some(line) = lines.next() else {
+            if interactive {
+                println!();
+            }
+            return Ok(());
+        };
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        match session.execute(line) {
+            Ok(true) => {}
+            Ok(false) => return Ok(()),
+            // A mistyped command shouldn't end the session.
+            Err(err) => eprintln!("Error: {:#}", err),
+        }
+    }
+}
+
+/// The loaded data and the settings in effect for the following commands.
+struct Session {
+    data: LazyFrame,
+    input: String,
+    lookup: ColumnLookup,
+    parsing: NumericParsing,
+    output: OutputFormat,
+    /// The filter expression as typed, and parsed.
+    filter: Option<(String, Expr)>,
+    group_by: Option<String>,
+}
+
+impl Session {
+    /// Runs a command line, returning false when the user quits.
+    fn execute(&mut self, line: &str) -> Result<bool> {
+        let (command, rest) = line
+            .split_once(char::is_whitespace)
+            .map_or((line, ""), |(command, rest)| (command, rest.trim()));
+        match command {
+            "quit" | "exit" => return Ok(false),
+            "help" => println!("{}", HELP),
+            "stats" => self.stats(rest)?,
+            "hist" => {
+                let mut lf = self.frame();
+                let column = self.column(&mut lf, rest)?;
+                let options = HistogramOptions {
+                    parsing: self.parsing.clone(),
+                    ..Default::default()
+                };
+                print_histogram(self.output, &compute_histogram(lf, &column, &options)?)?;
+            }
+            "count" => print_count(self.output, count_rows(self.frame())?)?,
+            "head" => {
+                let rows = if rest.is_empty() {
+                    10
+                } else {
+                    rest.parse()
+                        .map_err(|_| anyhow!("Expected a number of rows, got '{}'", rest))?
+                };
+                print_dataframe(self.output, &head(self.frame(), rows)?)?;
+            }
+            "columns" | "schema" => print_schema(self.output, &inspect_schema(self.frame())?)?,
+            "filter" => match rest {
+                "" => match &self.filter {
+                    Some((filter, _)) => println!("Filter: {}", filter),
+                    None => println!("No filter"),
+                },
+                "off" => self.filter = None,
+                _ => self.filter = Some((rest.to_string(), parse_filter(rest)?)),
+            },
+            "group" => match rest {
+                "" => match &self.group_by {
+                    Some(column) => println!("Grouped by '{}'", column),
+                    None => println!("Not grouped"),
+                },
+                "off" => self.group_by = None,
+                _ => {
+                    let mut lf = self.frame();
+                    self.group_by = Some(self.column(&mut lf, rest)?);
+                }
+            },
+            "output" => {
+                // Only `stats` has a Prometheus form, so the session can't switch to it.
+                self.output = OutputFormat::from_str(rest, true)
+                    .ok()
+                    .filter(|format| *format != OutputFormat::Prom)
+                    .ok_or_else(|| anyhow!("Unknown output format '{}'", rest))?;
+            }
+            _ => bail!(
+                "Unknown command '{}'; type 'help' for the commands",
+                command
+            ),
+        }
+        Ok(true)
+    }
+
+    /// Calculates and prints the statistics of a comma-separated list of columns.
+    fn stats(&self, columns: &str) -> Result<()> {
+        let mut lf = self.frame();
+        let columns = if columns.is_empty() {
+            numeric_columns(&mut lf)?
+        } else {
+            columns
+                .split(',')
+                .map(|name| self.column(&mut lf, name.trim()))
+                .collect::<Result<_>>()?
+        };
+        let options = StatsOptions {
+            columns,
+            group_by: self.group_by.clone(),
+            parsing: self.parsing.clone(),
+            ..Default::default()
+        };
+        print_stats(self.output, &self.input, &compute_stats(lf, &options)?, &[])
+    }
+
+    /// The loaded data, filtered by the current filter.
+    fn frame(&self) -> LazyFrame {
+        match &self.filter {
+            Some((_, predicate)) => self.data.clone().filter(predicate.clone()),
+            None => self.data.clone(),
+        }
+    }
+
+    fn column(&self, lf: &mut LazyFrame, name: &str) -> Result<String> {
+        if name.is_empty() {
+            bail!("Name a column");
+        }
+        resolved_column(lf, name, &self.lookup)
+    }
+}
@@ -0,0 +1,14 @@
+use anyhow::Result;
+use csv_stats::input::scan;
+use csv_stats::output::print_schema;
+use csv_stats::schema::inspect_schema;
+
+use crate::cli::SchemaArgs;
+
+/// Lists the columns of the input so their exact names and types can be looked up.
+pub fn run(args: &SchemaArgs) -> Result<()> {
+    let lf = scan(&args.input.options())?;
+    let columns = inspect_schema(lf)?;
+
+    print_schema(args.output, &columns)
+}
@@ -0,0 +1,9 @@
+use anyhow::Result;
+use csv_stats::server::serve;
+
+use crate::cli::ServeArgs;
+
+/// Answers HTTP requests for statistics until interrupted.
+pub fn run(args: &ServeArgs) -> Result<()> {
+    serve(&args.bind, args.options())
+}
@@ -0,0 +1,24 @@
+use anyhow::Result;
+use csv_stats::input::scan;
+use csv_stats::output::{print_dataframe, write_dataframe};
+use csv_stats::rolling::compute_smoothed;
+
+use super::{resolved_column, selected_column};
+use crate::cli::SmoothArgs;
+
+/// Calculates the moving averages of a column and prints them or saves them to a file.
+pub fn run(args: &SmoothArgs) -> Result<()> {
+    let mut lf = scan(&args.input.options())?;
+    let lookup = args.input.lookup();
+    let column = selected_column(&mut lf, &args.column_name, args.column_index, &lookup)?;
+    let mut options = args.options();
+    if let Some(name) = &options.order_by {
+        options.order_by = Some(resolved_column(&mut lf, name, &lookup)?);
+    }
+    let mut smoothed = compute_smoothed(lf, &column, &options)?;
+
+    match &args.output_file {
+        Some(path) => write_dataframe(path, &mut smoothed, args.output_format),
+        None => print_dataframe(args.output, &smoothed),
+    }
+}
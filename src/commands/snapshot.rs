@@ -0,0 +1,42 @@
+use anyhow::Result;
+use csv_stats::compute_stats;
+use csv_stats::drift::write_baseline;
+use csv_stats::input::{numeric_columns, scan};
+use csv_stats::stats::StatsOptions;
+
+use super::resolved_column;
+use crate::cli::SnapshotArgs;
+
+/// Calculates the statistics of the input and saves them as a baseline.
+pub fn run(args: &SnapshotArgs) -> Result<()> {
+    let mut lf = scan(&args.input.options())?;
+    let lookup = args.input.lookup();
+    let columns = if args.column_name.is_empty() {
+        numeric_columns(&mut lf)?
+    } else {
+        args.column_name
+            .iter()
+            .map(|name| resolved_column(&mut lf, name, &lookup))
+            .collect::<Result<_>>()?
+    };
+    let group_by = match &args.group_by {
+        Some(name) => Some(resolved_column(&mut lf, name, &lookup)?),
+        None => None,
+    };
+    let options = StatsOptions {
+        columns,
+        group_by,
+        quantiles: args.quantiles.clone(),
+        parsing: args.parsing.options(),
+        ..Default::default()
+    };
+    let stats = compute_stats(lf, &options)?;
+
+    write_baseline(&args.baseline, &stats)?;
+    eprintln!(
+        "Wrote the statistics of {} column(s) to '{}'",
+        options.columns.len(),
+        args.baseline
+    );
+    Ok(())
+}
@@ -0,0 +1,14 @@
+use anyhow::Result;
+use csv_stats::input::scan;
+use csv_stats::output::print_dataframe;
+use csv_stats::sql::run_sql;
+
+use crate::cli::SqlArgs;
+
+/// Runs the query against the input and prints the resulting table.
+pub fn run(args: &SqlArgs) -> Result<()> {
+    let lf = scan(&args.input.options())?;
+    let df = run_sql(lf, &args.table, &args.query)?;
+
+    print_dataframe(args.output, &df)
+}
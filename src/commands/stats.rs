@@ -0,0 +1,197 @@
+use anyhow::Result;
+use chrono::{SecondsFormat, Utc};
+
+use csv_stats::assertions::check_all;
+use csv_stats::bootstrap::{BootstrapOptions, compute_bootstrap};
+use csv_stats::columns::column_at;
+use csv_stats::input::{GroupSample, Sampling, input_name, sample_groups, scan};
+use csv_stats::metrics::push;
+use csv_stats::output::{append_stats, print_deltas, print_stats, print_template};
+use csv_stats::significance::{SignificanceOptions, one_way_anova};
+use csv_stats::stats::{DEFAULT_CONFIDENCE_LEVEL, NumericParsing};
+use csv_stats::watch::InputWatcher;
+use csv_stats::{SelectedStats, compute_per_file, compute_stats};
+use polars::prelude::LazyFrame;
+
+use super::resolved_column;
+use crate::cli::StatsArgs;
+
+/// The most distinct unparseable values noted for each column.
+const UNPARSEABLE_EXAMPLES: usize = 5;
+
+/// Calculates statistics for each requested column and prints them in the requested format.
+///
+/// In watch mode this repeats whenever an input file changes, until interrupted.
+pub fn run(args: &StatsArgs) -> Result<()> {
+    if !args.watch {
+        let stats = analyze(args)?;
+        // Check after printing, so the stats are still shown when a check fails.
+        return check_all(&args.assertions, &stats);
+    }
+
+    let input = args.input.options();
+    let watcher = InputWatcher::new(&input)?;
+    let mut previous: Option<Vec<SelectedStats>> = None;
+    loop {
+        // A file caught half-written or a failed check shouldn't end the watch, so
+        // report the problem and wait for the next change.
+        match analyze(args) {
+            Ok(stats) => {
+                if let (true, Some(previous)) = (args.deltas, &previous) {
+                    print_deltas(args.output, previous, &stats)?;
+                }
+                if let Err(err) = check_all(&args.assertions, &stats) {
+                    eprintln!("{}", err);
+                }
+                previous = Some(stats);
+            }
+            Err(err) => eprintln!("Error: {:?}", err),
+        }
+
+        eprintln!("Watching {} for changes...", input_name(&input));
+        watcher.wait_for_change()?;
+    }
+}
+
+/// Calculates and prints the statistics once.
+fn analyze(args: &StatsArgs) -> Result<Vec<SelectedStats>> {
+    let input = args.input.options();
+    let mut lf = scan(&input)?;
+    let mut options = args.options();
+    let lookup = args.input.lookup();
+    options.columns = if args.column_index.is_empty() {
+        options
+            .columns
+            .iter()
+            .map(|name| resolved_column(&mut lf, name, &lookup))
+            .collect::<Result<_>>()?
+    } else {
+        args.column_index
+            .iter()
+            .map(|&position| column_at(&mut lf, position))
+            .collect::<Result<_>>()?
+    };
+    if let Some(name) = &options.group_by {
+        options.group_by = Some(resolved_column(&mut lf, name, &lookup)?);
+    }
+    if let Some(buckets) = &mut options.time_buckets {
+        buckets.column = resolved_column(&mut lf, &buckets.column, &lookup)?;
+    }
+    if let Some(name) = &options.weight_column {
+        options.weight_column = Some(resolved_column(&mut lf, name, &lookup)?);
+    }
+    let mut sampling = input.sampling();
+    if let (Some(rows), Some(column)) = (args.sample_per_group, &options.group_by) {
+        let per_group = GroupSample {
+            column: column.clone(),
+            rows,
+            proportional: args.proportional,
+        };
+        lf = sample_groups(lf, &per_group, input.seed());
+        sampling = Some(Sampling {
+            size: input.sample,
+            per_group: Some(per_group),
+            seed: input.seed(),
+        });
+    }
+
+    // Execute the data processing function.
+    let mut stats = if args.per_file {
+        compute_per_file(&input, &options)?
+    } else {
+        Vec::new()
+    };
+    stats.extend(compute_stats(lf.clone(), &options)?);
+    if let Some(sampling) = &sampling {
+        stats
+            .iter_mut()
+            .for_each(|s| s.sample = Some(sampling.clone()));
+    }
+    let mut anova = Vec::new();
+    if let (true, Some(group_by)) = (args.anova, &options.group_by) {
+        let significance = SignificanceOptions {
+            parsing: options.parsing.clone(),
+            ..Default::default()
+        };
+        for column in options.columns.iter().filter(|c| *c != group_by) {
+            let numeric = stats
+                .iter()
+                .any(|s| &s.column == column && s.file.is_none() && s.is_numeric());
+            if numeric {
+                anova.push(one_way_anova(lf.clone(), column, group_by, &significance)?);
+            }
+        }
+    }
+    if let Some(resamples) = args.bootstrap {
+        let bootstrap = BootstrapOptions {
+            resamples,
+            level: options.confidence_level.unwrap_or(DEFAULT_CONFIDENCE_LEVEL),
+            quantiles: options.quantiles.clone(),
+            seed: input.seed(),
+            parsing: options.parsing.clone(),
+        };
+        for s in stats
+            .iter_mut()
+            .filter(|s| s.file.is_none() && s.is_numeric())
+        {
+            s.bootstrap = Some(compute_bootstrap(lf.clone(), &s.column, &bootstrap)?);
+        }
+    }
+
+    match &args.template {
+        Some(template) => print_template(template, &input_name(&input), &stats, &anova)?,
+        None => print_stats(args.output, &input_name(&input), &stats, &anova)?,
+    }
+    if let Some(path) = &args.output_file {
+        let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+        append_stats(
+            path,
+            args.output_format,
+            &input_name(&input),
+            &stats,
+            &timestamp,
+        )?;
+    }
+    for target in &args.push {
+        push(target, &input_name(&input), &stats, &anova)?;
+    }
+    report_unparseable(&lf, &stats, &options.parsing)?;
+    Ok(stats)
+}
+
+/// Notes on stderr how many values of each column were left out of the numeric
+/// statistics because they couldn't be parsed, with the most common of them, so a
+/// count that exceeds the values behind the mean doesn't go unexplained.
+fn report_unparseable(
+    lf: &LazyFrame,
+    stats: &[SelectedStats],
+    parsing: &NumericParsing,
+) -> Result<()> {
+    let mut reported: Vec<&str> = Vec::new();
+    for s in stats {
+        if !s.is_numeric()
+            || s.numeric_null_count == s.null_count
+            || reported.contains(&s.column.as_str())
+        {
+            continue;
+        }
+        reported.push(&s.column);
+        let unparseable = parsing.unparseable_values(lf, &s.column, UNPARSEABLE_EXAMPLES)?;
+        if unparseable.count == 0 {
+            continue;
+        }
+        let examples: Vec<String> = unparseable
+            .values
+            .iter()
+            .map(|v| format!("'{}' ({}x)", v.value, v.count))
+            .collect();
+        eprintln!(
+            "Note: {} value(s) of '{}' couldn't be parsed as numbers and were treated as \
+             missing, e.g. {}",
+            unparseable.count,
+            s.column,
+            examples.join(", ")
+        );
+    }
+    Ok(())
+}
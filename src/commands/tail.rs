@@ -0,0 +1,14 @@
+use anyhow::Result;
+use csv_stats::input::scan;
+use csv_stats::output::print_dataframe;
+use csv_stats::preview::tail;
+
+use crate::cli::PreviewArgs;
+
+/// Prints the last rows of the input as a table.
+pub fn run(args: &PreviewArgs) -> Result<()> {
+    let lf = scan(&args.input.options())?;
+    let df = tail(lf, args.rows)?;
+
+    print_dataframe(args.output, &df)
+}
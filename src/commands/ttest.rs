@@ -0,0 +1,42 @@
+use anyhow::{Result, bail};
+use csv_stats::input::{input_name, scan};
+use csv_stats::output::print_significance;
+use csv_stats::significance::{compare_groups, compare_inputs};
+
+use super::resolved_column;
+use crate::cli::TtestArgs;
+
+/// Tests whether the column differs between the input and another file, or between two
+/// groups of the input, and prints the result.
+pub fn run(args: &TtestArgs) -> Result<()> {
+    let input = args.input.options();
+    let mut lf = scan(&input)?;
+    let lookup = args.input.lookup();
+    let column = resolved_column(&mut lf, &args.column_name, &lookup)?;
+
+    let report = match (&args.against, &args.group_by) {
+        (Some(path), _) => {
+            let mut other_input = input.clone();
+            other_input.paths = vec![path.clone()];
+            let other = scan(&other_input)?;
+            compare_inputs(
+                (&input_name(&input), lf),
+                (path, other),
+                &column,
+                &args.options(),
+            )?
+        }
+        (None, Some(group_by)) => {
+            let group_by = resolved_column(&mut lf, group_by, &lookup)?;
+            let groups = match args.groups.as_slice() {
+                [] => None,
+                [a, b] => Some([a.clone(), b.clone()]),
+                groups => bail!("Expected two groups to compare, got {}", groups.len()),
+            };
+            compare_groups(lf, &column, &group_by, groups, &args.options())?
+        }
+        (None, None) => unreachable!("clap requires --against or --group-by"),
+    };
+
+    print_significance(args.output, &report)
+}
@@ -0,0 +1,9 @@
+use anyhow::Result;
+use csv_stats::tui::explore;
+
+use crate::cli::TuiArgs;
+
+/// Opens the interactive explorer until the user quits.
+pub fn run(args: &TuiArgs) -> Result<()> {
+    explore(args.options())
+}
@@ -0,0 +1,37 @@
+use anyhow::Result;
+use csv_stats::assertions::AssertionsFailed;
+use csv_stats::input::scan;
+use csv_stats::output::print_validation;
+use csv_stats::validation::{read_rules, validate};
+
+use super::resolved_column;
+use crate::cli::ValidateArgs;
+
+/// Checks the input against the rules file, prints the violations, and fails if there
+/// are any.
+pub fn run(args: &ValidateArgs) -> Result<()> {
+    let mut lf = scan(&args.input.options())?;
+    let lookup = args.input.lookup();
+    let mut rules = read_rules(&args.rules)?;
+    for column in &mut rules {
+        column.column = resolved_column(&mut lf, &column.column, &lookup)?;
+    }
+    let validation = validate(lf, &rules, &args.options())?;
+    print_validation(args.output, &validation)?;
+
+    let violations: Vec<String> = validation
+        .report
+        .failures()
+        .map(|r| {
+            format!(
+                "'{}' {} failed for {} of {} rows",
+                r.column, r.rule, r.violations, validation.report.rows
+            )
+        })
+        .collect();
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(AssertionsFailed(violations).into())
+    }
+}
@@ -0,0 +1,198 @@
+//! Defaults for command-line arguments, read from `csv-stats.toml` files.
+//!
+//! The user's file in `~/.config/csv-stats/` is read first, then the one in the
+//! current directory, whose settings take precedence. Each setting is named after a
+//! command-line flag, e.g.
+//!
+//! ```toml
+//! column_name = "Amount Received"
+//! delimiter = ";"
+//! null_values = ["NA", "-"]
+//! output = "json"
+//! ```
+//!
+//! and becomes that flag's default for every command that has it, so flags given on
+//! the command line still win.
+//!
+//! Named profiles group the settings of a recurring analysis. `--profile aml` applies
+//! the settings of the `[profile.aml]` table over the others:
+//!
+//! ```toml
+//! [profile.aml]
+//! column_name = "Amount Paid"
+//! filter = 'col("Is Laundering") == 1'
+//! thresholds = [10000]
+//! ```
+
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, bail};
+use clap::Command;
+use toml::{Table, Value};
+
+/// The name of the config file.
+pub const CONFIG_FILE: &str = "csv-stats.toml";
+
+/// The key of the table holding the named profiles.
+const PROFILES_KEY: &str = "profile";
+
+/// The settings read from the config files.
+#[derive(Debug, Default)]
+pub struct Config {
+    /// The default values keyed by argument name, with later files already merged in.
+    settings: Table,
+    /// The named profiles, each a table of settings.
+    profiles: Table,
+}
+
+impl Config {
+    /// Reads and merges the config files that exist.
+    pub fn load() -> Result<Self> {
+        let mut config = Config::default();
+        let paths = user_config_dir()
+            .map(|dir| dir.join(CONFIG_FILE))
+            .into_iter()
+            .chain([PathBuf::from(CONFIG_FILE)]);
+        for path in paths {
+            if !path.is_file() {
+                continue;
+            }
+            let text = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read '{}'", path.display()))?;
+            let mut settings: Table = text
+                .parse()
+                .with_context(|| format!("Invalid config file '{}'", path.display()))?;
+            match settings.remove(PROFILES_KEY) {
+                Some(Value::Table(profiles)) => config.profiles.extend(profiles),
+                Some(_) => bail!(
+                    "'{}' in '{}' must be a table of profiles, e.g. [profile.NAME]",
+                    PROFILES_KEY,
+                    path.display()
+                ),
+                None => {}
+            }
+            config.settings.extend(settings);
+        }
+        Ok(config)
+    }
+
+    /// Applies the settings of a named profile over the other settings.
+    pub fn select_profile(&mut self, name: &str) -> Result<()> {
+        match self.profiles.get(name) {
+            Some(Value::Table(settings)) => {
+                self.settings.extend(settings.clone());
+                Ok(())
+            }
+            Some(_) => bail!("Profile '{}' in {} must be a table", name, CONFIG_FILE),
+            None if self.profiles.is_empty() => {
+                bail!(
+                    "Unknown profile '{}', no profiles are defined in {}",
+                    name,
+                    CONFIG_FILE
+                )
+            }
+            None => bail!(
+                "Unknown profile '{}', expected one of: {}",
+                name,
+                self.profiles
+                    .keys()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+
+    /// Sets the configured defaults on the arguments of the CLI and its subcommands.
+    pub fn apply(&self, mut command: Command) -> Result<Command> {
+        for (key, value) in &self.settings {
+            let id = key.replace('-', "_");
+            let values = setting_values(key, value)?;
+            let mut known = false;
+
+            if has_arg(&command, &id) {
+                command = command.mut_arg(&id, |arg| arg.default_values(values.clone()));
+                known = true;
+            }
+            let subcommands: Vec<String> = command
+                .get_subcommands()
+                .filter(|sub| has_arg(sub, &id))
+                .map(|sub| sub.get_name().to_string())
+                .collect();
+            for name in subcommands {
+                command = command.mut_subcommand(name, |sub| {
+                    sub.mut_arg(&id, |arg| arg.default_values(values.clone()))
+                });
+                known = true;
+            }
+
+            if !known {
+                bail!("Unknown setting '{}' in {}", key, CONFIG_FILE);
+            }
+        }
+        Ok(command)
+    }
+}
+
+/// Finds the value of the `--profile` flag in the raw command-line arguments.
+///
+/// The profile has to be known before the arguments are parsed, since its settings
+/// become the defaults they are parsed with.
+pub fn profile_arg(args: impl IntoIterator<Item = OsString>) -> Option<String> {
+    let mut args = args
+        .into_iter()
+        .map(|arg| arg.to_string_lossy().into_owned());
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        if arg == "--profile" {
+            return args.next();
+        }
+        if let Some(name) = arg.strip_prefix("--profile=") {
+            return Some(name.to_string());
+        }
+    }
+    None
+}
+
+/// The directory of the user's config file: `$XDG_CONFIG_HOME/csv-stats`, or
+/// `~/.config/csv-stats`.
+fn user_config_dir() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("csv-stats"))
+}
+
+/// Returns whether a command has an argument with the given id.
+fn has_arg(command: &Command, id: &str) -> bool {
+    command.get_arguments().any(|arg| arg.get_id() == id)
+}
+
+/// Converts a setting to the strings it would be given as on the command line.
+fn setting_values(key: &str, value: &Value) -> Result<Vec<String>> {
+    match value {
+        Value::Array(items) => items.iter().map(|item| scalar_value(key, item)).collect(),
+        _ => Ok(vec![scalar_value(key, value)?]),
+    }
+}
+
+/// Converts a single setting value to a string.
+fn scalar_value(key: &str, value: &Value) -> Result<String> {
+    Ok(match value {
+        Value::String(s) => s.clone(),
+        Value::Integer(i) => i.to_string(),
+        Value::Float(f) => f.to_string(),
+        Value::Boolean(b) => b.to_string(),
+        _ => bail!(
+            "Setting '{}' in {} must be a string, number, boolean or a list of them",
+            key,
+            CONFIG_FILE
+        ),
+    })
+}
@@ -0,0 +1,200 @@
+//! Correlation and covariance between pairs of numeric columns.
+
+use anyhow::Result;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::collect;
+use crate::stats::NumericParsing;
+
+/// The correlation coefficient calculated.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CorrelationMethod {
+    /// How well a straight line relates the values.
+    #[default]
+    Pearson,
+    /// How well any monotonic function relates the values: the Pearson correlation of
+    /// their ranks. Robust to outliers and nonlinear relationships.
+    Spearman,
+}
+
+/// The relationship between two numeric columns.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Correlation {
+    /// The name of the first column.
+    pub x: String,
+    /// The name of the second column.
+    pub y: String,
+    /// The number of rows where both columns have a value; only these rows are used.
+    pub count: usize,
+    /// The Pearson correlation coefficient, between -1 and 1.
+    pub pearson: Option<f64>,
+    /// The Spearman rank correlation coefficient, between -1 and 1, with
+    /// [`CorrelationMethod::Spearman`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spearman: Option<f64>,
+    /// The covariance of the two columns.
+    pub covariance: Option<f64>,
+}
+
+/// The pairwise correlations between several columns.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorrelationMatrix {
+    /// The names of the columns, in the order of the matrix rows and columns.
+    pub columns: Vec<String>,
+    /// The Pearson correlation coefficient of every pair of columns, indexed by their
+    /// positions in `columns`. The matrix is symmetric with ones on the diagonal.
+    pub pearson: Vec<Vec<Option<f64>>>,
+    /// The Spearman rank correlation coefficients, like `pearson`, with
+    /// [`CorrelationMethod::Spearman`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spearman: Option<Vec<Vec<Option<f64>>>>,
+    /// The covariance of every pair of columns, like `pearson`, with the variances of the
+    /// columns on the diagonal.
+    #[serde(default)]
+    pub covariance: Vec<Vec<Option<f64>>>,
+    /// The full results for each distinct pair of columns.
+    pub pairs: Vec<Correlation>,
+}
+
+/// Settings for calculating correlations.
+#[derive(Debug, Clone)]
+pub struct CorrelationOptions {
+    /// Delta degrees of freedom for the covariance: 0 for population, 1 for sample.
+    pub ddof: u8,
+    /// The coefficient calculated besides Pearson's.
+    pub method: CorrelationMethod,
+    /// How text values are cleaned up before being parsed as numbers.
+    pub parsing: NumericParsing,
+}
+
+impl Default for CorrelationOptions {
+    fn default() -> Self {
+        Self {
+            ddof: 1,
+            method: CorrelationMethod::default(),
+            parsing: NumericParsing::default(),
+        }
+    }
+}
+
+/// Calculates the correlation of each pair of columns, in a single pass over the data.
+///
+/// Each pair only uses the rows where both of its columns have a value (pairwise-complete
+/// observations), so missing values in one column don't affect the other pairs.
+pub fn compute_correlations(
+    lf: LazyFrame,
+    pairs: &[(String, String)],
+    options: &CorrelationOptions,
+) -> Result<Vec<Correlation>> {
+    let mut checked: Vec<&str> = Vec::new();
+    for column_name in pairs.iter().flat_map(|(x, y)| [x, y]) {
+        if !checked.contains(&column_name.as_str()) {
+            options.parsing.check_strict(&lf, column_name)?;
+            checked.push(column_name);
+        }
+    }
+
+    let mut aggregations = Vec::with_capacity(pairs.len() * 3);
+    for (i, (x, y)) in pairs.iter().enumerate() {
+        let x = options.parsing.to_float(x);
+        let y = options.parsing.to_float(y);
+        let complete = x.clone().is_not_null().and(y.clone().is_not_null());
+        // Masking rather than filtering keeps the columns the same length, which the
+        // streaming engine needs.
+        let x = when(complete.clone()).then(x).otherwise(lit(NULL));
+        let y = when(complete.clone()).then(y).otherwise(lit(NULL));
+        if options.method == CorrelationMethod::Spearman {
+            // Nulls keep a null rank, so only the complete rows are ranked. Ties share
+            // the average of their ranks, as Spearman's coefficient is defined.
+            let options = RankOptions {
+                method: RankMethod::Average,
+                descending: false,
+            };
+            let rank = |e: Expr| e.rank(options, None).cast(DataType::Float64);
+            aggregations.push(
+                pearson_corr(rank(x.clone()), rank(y.clone())).alias(format!("{}_spearman", i)),
+            );
+        }
+        aggregations.extend([
+            complete.sum().alias(format!("{}_count", i)),
+            pearson_corr(x.clone(), y.clone()).alias(format!("{}_pearson", i)),
+            cov(x, y, options.ddof).alias(format!("{}_covariance", i)),
+        ]);
+    }
+    let df = collect(lf.select(aggregations))?;
+
+    let get_optional_f64 = |name: String| -> Result<Option<f64>> {
+        match df.column(&name)?.get(0)? {
+            AnyValue::Null => Ok(None),
+            value => Ok(Some(value.try_extract()?).filter(|v: &f64| !v.is_nan())),
+        }
+    };
+    pairs
+        .iter()
+        .enumerate()
+        .map(|(i, (x, y))| {
+            Ok(Correlation {
+                x: x.clone(),
+                y: y.clone(),
+                count: df
+                    .column(&format!("{}_count", i))?
+                    .get(0)?
+                    .try_extract::<u32>()? as usize,
+                pearson: get_optional_f64(format!("{}_pearson", i))?,
+                spearman: if options.method == CorrelationMethod::Spearman {
+                    get_optional_f64(format!("{}_spearman", i))?
+                } else {
+                    None
+                },
+                covariance: get_optional_f64(format!("{}_covariance", i))?,
+            })
+        })
+        .collect()
+}
+
+/// Calculates the correlation matrix of the given columns, in a single pass over the data.
+pub fn compute_correlation_matrix(
+    lf: LazyFrame,
+    columns: &[String],
+    options: &CorrelationOptions,
+) -> Result<CorrelationMatrix> {
+    // The matrices are symmetric, so only the pairs on and above the diagonal are
+    // calculated. The diagonal pairs give the variances of the covariance matrix.
+    let mut positions = Vec::new();
+    let mut pairs = Vec::new();
+    for (i, x) in columns.iter().enumerate() {
+        for (j, y) in columns.iter().enumerate().skip(i) {
+            positions.push((i, j));
+            pairs.push((x.clone(), y.clone()));
+        }
+    }
+    let pairs = compute_correlations(lf, &pairs, options)?;
+
+    let n = columns.len();
+    let matrix = |coefficient: fn(&Correlation) -> Option<f64>, diagonal: bool| {
+        let mut matrix = vec![vec![Some(1.0); n]; n];
+        for ((i, j), correlation) in positions.iter().zip(&pairs) {
+            if i != j || diagonal {
+                matrix[*i][*j] = coefficient(correlation);
+                matrix[*j][*i] = coefficient(correlation);
+            }
+        }
+        matrix
+    };
+
+    Ok(CorrelationMatrix {
+        columns: columns.to_vec(),
+        pearson: matrix(|c| c.pearson, false),
+        spearman: (options.method == CorrelationMethod::Spearman)
+            .then(|| matrix(|c| c.spearman, false)),
+        covariance: matrix(|c| c.covariance, true),
+        pairs: positions
+            .iter()
+            .zip(pairs.iter())
+            .filter(|((i, j), _)| i != j)
+            .map(|(_, correlation)| correlation.clone())
+            .collect(),
+    })
+}
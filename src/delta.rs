@@ -0,0 +1,589 @@
+//! Reading of Delta Lake tables from a local directory.
+//!
+//! A table is a directory of Parquet files with a transaction log in `_delta_log`: JSON
+//! commits, numbered from 0, that add and remove files, and Parquet checkpoints that
+//! summarize the log up to a version. The files of the requested version are found by
+//! replaying the log from the latest checkpoint at or before it, then scanned lazily like
+//! any Parquet input. V2 checkpoints, named by a UUID and possibly keeping their file
+//! actions in sidecar files, are read too. Partition columns aren't stored in the files, so their values are
+//! added from the log.
+//!
+//! Tables using deletion vectors or column mapping aren't supported.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow, bail};
+use polars::io::json::JsonWriter;
+use polars::prelude::*;
+use serde_json::Value;
+
+/// The directory of a table holding its transaction log.
+const LOG_DIRECTORY: &str = "_delta_log";
+
+/// Reader features that don't change how the data files are read.
+const SUPPORTED_FEATURES: &[&str] = &["timestampNtz", "v2Checkpoint", "vacuumProtocolCheck"];
+
+/// Whether the path is a directory holding a Delta Lake table.
+pub fn is_delta_table(path: &str) -> bool {
+    Path::new(path).join(LOG_DIRECTORY).is_dir()
+}
+
+/// Scans a version of the Delta Lake table at `path`, or its latest version when `None`.
+pub fn scan_delta(path: &str, version: Option<u64>) -> Result<LazyFrame> {
+    let table = Path::new(path);
+    let log = Log::list(&table.join(LOG_DIRECTORY))
+        .with_context(|| format!("Failed to read the Delta log of '{}'", path))?;
+    let latest = log
+        .latest()
+        .ok_or_else(|| anyhow!("The Delta log of '{}' is empty", path))?;
+    let version = version.unwrap_or(latest);
+    if version > latest {
+        bail!(
+            "The Delta table '{}' has no version {}, the latest is {}",
+            path,
+            version,
+            latest
+        );
+    }
+
+    let mut snapshot = Snapshot::default();
+    let start = match log.checkpoints.range(..=version).next_back() {
+        Some((&checkpoint, parts)) => {
+            for part in parts {
+                snapshot.apply_checkpoint(part)?;
+            }
+            checkpoint + 1
+        }
+        None => 0,
+    };
+    for v in start..=version {
+        let commit = log.commits.get(&v).ok_or_else(|| {
+            anyhow!(
+                "Version {} of the Delta table '{}' can't be read: the log is missing commit {}",
+                version,
+                path,
+                v
+            )
+        })?;
+        snapshot.apply_commit(commit)?;
+    }
+    snapshot.scan(table)
+}
+
+/// The commit and checkpoint files of a transaction log, by version.
+#[derive(Default)]
+struct Log {
+    commits: BTreeMap<u64, PathBuf>,
+    /// The complete checkpoints, each of one or more parts.
+    checkpoints: BTreeMap<u64, Vec<PathBuf>>,
+}
+
+impl Log {
+    /// Lists the files of the log directory, named like `00000000000000000010.json`,
+    /// `00000000000000000010.checkpoint.parquet`, for a checkpoint in parts
+    /// `00000000000000000010.checkpoint.0000000001.0000000002.parquet` or, for a v2
+    /// checkpoint, `00000000000000000010.checkpoint.<uuid>.parquet` or `.json`.
+    fn list(directory: &Path) -> Result<Log> {
+        let mut log = Log::default();
+        let mut parts: BTreeMap<(u64, u64), Vec<PathBuf>> = BTreeMap::new();
+        for entry in fs::read_dir(directory)? {
+            let path = entry?.path();
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let Some((version, rest)) = name.split_once('.') else {
+                continue;
+            };
+            let Ok(version) = version.parse::<u64>() else {
+                continue;
+            };
+            match rest.split('.').collect::<Vec<_>>().as_slice() {
+                ["json"] => {
+                    log.commits.insert(version, path);
+                }
+                ["checkpoint", "parquet"] => {
+                    parts.entry((version, 1)).or_default().push(path);
+                }
+                ["checkpoint", _, total, "parquet"] => {
+                    if let Ok(total) = total.parse() {
+                        parts.entry((version, total)).or_default().push(path);
+                    }
+                }
+                ["checkpoint", _, "parquet" | "json"] => {
+                    log.checkpoints.insert(version, vec![path]);
+                }
+                _ => {}
+            }
+        }
+        // A checkpoint in parts can only be used once every part has been written.
+        for ((version, total), mut files) in parts {
+            if files.len() as u64 == total {
+                files.sort();
+                log.checkpoints.insert(version, files);
+            }
+        }
+        Ok(log)
+    }
+
+    /// The latest version of the table.
+    fn latest(&self) -> Option<u64> {
+        let commit = self.commits.keys().next_back();
+        let checkpoint = self.checkpoints.keys().next_back();
+        commit.max(checkpoint).copied()
+    }
+}
+
+/// The state of a table at a version.
+#[derive(Default)]
+struct Snapshot {
+    /// The table's schema, as Delta JSON.
+    schema: Option<Value>,
+    partition_columns: Vec<String>,
+    /// The data files, relative to the table directory, with their partition values.
+    files: BTreeMap<String, HashMap<String, Option<String>>>,
+    /// The sidecar files of the v2 checkpoint being applied, relative to
+    /// `_delta_log/_sidecars`.
+    sidecars: Vec<String>,
+}
+
+impl Snapshot {
+    /// Applies the actions of a JSON commit file, one per line.
+    fn apply_commit(&mut self, path: &Path) -> Result<()> {
+        let reader = BufReader::new(
+            File::open(path).with_context(|| format!("Failed to open '{}'", path.display()))?,
+        );
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let action: Value = serde_json::from_str(&line)
+                .with_context(|| format!("Invalid Delta commit '{}'", path.display()))?;
+            self.apply(&action)?;
+        }
+        Ok(())
+    }
+
+    /// Applies the actions of a checkpoint file, then those of its sidecar files.
+    fn apply_checkpoint(&mut self, path: &Path) -> Result<()> {
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            self.apply_commit(path)?;
+        } else {
+            self.apply_parquet(path)?;
+        }
+        let directory = path.with_file_name("_sidecars");
+        for sidecar in std::mem::take(&mut self.sidecars) {
+            self.apply_parquet(&directory.join(sidecar))?;
+        }
+        Ok(())
+    }
+
+    /// Applies the actions of a Parquet checkpoint or sidecar file, which has a column per
+    /// kind of action and a row per action.
+    fn apply_parquet(&mut self, path: &Path) -> Result<()> {
+        let file =
+            File::open(path).with_context(|| format!("Failed to open '{}'", path.display()))?;
+        let mut df = ParquetReader::new(file)
+            .finish()
+            .with_context(|| format!("Invalid Delta checkpoint '{}'", path.display()))?;
+        // Written out as JSON lines, each row has the same shape as a commit's action.
+        let mut buf = Vec::new();
+        JsonWriter::new(&mut buf).finish(&mut df)?;
+        for line in buf.split(|&b| b == b'\n').filter(|line| !line.is_empty()) {
+            self.apply(&serde_json::from_slice(line)?)?;
+        }
+        Ok(())
+    }
+
+    fn apply(&mut self, action: &Value) -> Result<()> {
+        if let Some(add) = present(action, "add") {
+            if add.get("deletionVector").is_some_and(|d| !d.is_null()) {
+                bail!("Delta tables with deletion vectors aren't supported");
+            }
+            self.files
+                .insert(file_path(add)?, partition_values(&add["partitionValues"]));
+        }
+        if let Some(remove) = present(action, "remove") {
+            self.files.remove(&file_path(remove)?);
+        }
+        if let Some(sidecar) = present(action, "sidecar") {
+            self.sidecars.push(file_path(sidecar)?);
+        }
+        if let Some(metadata) = present(action, "metaData") {
+            let mapping = metadata["configuration"]
+                .get("delta.columnMapping.mode")
+                .and_then(Value::as_str)
+                .or_else(|| pair_value(&metadata["configuration"], "delta.columnMapping.mode"));
+            if mapping.is_some_and(|mode| mode != "none") {
+                bail!("Delta tables with column mapping aren't supported");
+            }
+            let schema = metadata["schemaString"]
+                .as_str()
+                .ok_or_else(|| anyhow!("The Delta table's metadata has no schema"))?;
+            self.schema = Some(serde_json::from_str(schema).context("Invalid Delta schema")?);
+            self.partition_columns = metadata["partitionColumns"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|c| c.as_str().map(str::to_string))
+                .collect();
+        }
+        if let Some(protocol) = present(action, "protocol") {
+            let unsupported = protocol["readerFeatures"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .find(|feature| !SUPPORTED_FEATURES.contains(feature));
+            if let Some(feature) = unsupported {
+                bail!(
+                    "Delta tables with the '{}' feature aren't supported",
+                    feature
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Scans the data files of the snapshot into a single LazyFrame with the table's
+    /// columns.
+    fn scan(self, table: &Path) -> Result<LazyFrame> {
+        let fields: Vec<(String, DataType)> = self
+            .schema
+            .as_ref()
+            .and_then(|schema| schema["fields"].as_array())
+            .ok_or_else(|| anyhow!("The Delta log has no table schema"))?
+            .iter()
+            .map(|field| {
+                let name = field["name"].as_str().unwrap_or_default().to_string();
+                (name, delta_dtype(&field["type"]))
+            })
+            .collect();
+
+        let mut frames = Vec::with_capacity(self.files.len());
+        for (file, values) in &self.files {
+            let path = table.join(file);
+            let mut lf = LazyFrame::scan_parquet(
+                PlPath::new(&path.to_string_lossy()),
+                ScanArgsParquet::default(),
+            )?;
+            let partitions: Vec<Expr> = self
+                .partition_columns
+                .iter()
+                .map(|column| {
+                    let value = match values.get(column).cloned().flatten() {
+                        Some(value) => lit(value),
+                        None => lit(NULL),
+                    };
+                    let dtype = fields
+                        .iter()
+                        .find(|(name, _)| name == column)
+                        .map_or(DataType::String, |(_, dtype)| dtype.clone());
+                    value.cast(dtype).alias(column.as_str())
+                })
+                .collect();
+            if !partitions.is_empty() {
+                lf = lf.with_columns(partitions);
+            }
+            frames.push(lf);
+        }
+
+        if frames.is_empty() {
+            let schema = Schema::from_iter(
+                fields
+                    .into_iter()
+                    .map(|(name, dtype)| Field::new(name.into(), dtype)),
+            );
+            return Ok(DataFrame::empty_with_schema(&schema).lazy());
+        }
+        // Columns added to the table later are missing from older files.
+        let args = UnionArgs {
+            diagonal: true,
+            to_supertypes: true,
+            ..Default::default()
+        };
+        let columns: Vec<Expr> = fields.iter().map(|(name, _)| col(name.as_str())).collect();
+        Ok(concat(frames, args)?.select(columns))
+    }
+}
+
+/// Returns the action of the given kind, if there is one. Checkpoint rows hold every
+/// kind, the absent ones as null or with all fields null.
+fn present<'a>(action: &'a Value, kind: &str) -> Option<&'a Value> {
+    action.get(kind).filter(|fields| {
+        fields
+            .as_object()
+            .is_some_and(|f| f.values().any(|v| !v.is_null()))
+    })
+}
+
+/// The path of an added or removed file, relative to the table directory.
+fn file_path(action: &Value) -> Result<String> {
+    let path = action["path"]
+        .as_str()
+        .ok_or_else(|| anyhow!("A Delta log action has no file path"))?;
+    Ok(percent_decode(path.strip_prefix("file://").unwrap_or(path)))
+}
+
+/// Reads the partition values of an added file: a JSON object in commits, or a list of
+/// key/value pairs in checkpoints.
+fn partition_values(values: &Value) -> HashMap<String, Option<String>> {
+    match values {
+        Value::Object(map) => map
+            .iter()
+            .map(|(key, value)| (key.clone(), value.as_str().map(str::to_string)))
+            .collect(),
+        Value::Array(pairs) => pairs
+            .iter()
+            .filter_map(|pair| {
+                let key = pair["key"].as_str()?.to_string();
+                Some((key, pair["value"].as_str().map(str::to_string)))
+            })
+            .collect(),
+        _ => HashMap::new(),
+    }
+}
+
+/// Looks up a key in a map read from a checkpoint, as a list of key/value pairs.
+fn pair_value<'a>(pairs: &'a Value, key: &str) -> Option<&'a str> {
+    pairs.as_array()?.iter().find(|pair| pair["key"] == key)?["value"].as_str()
+}
+
+/// Decodes the `%XX` escapes of a file path in the log.
+fn percent_decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Maps a Delta column type onto the Polars type it is read as. Nested types are only
+/// used for empty tables, whose files don't give their actual type.
+fn delta_dtype(dtype: &Value) -> DataType {
+    match dtype.as_str() {
+        Some("boolean") => DataType::Boolean,
+        Some("byte" | "short" | "integer") => DataType::Int32,
+        Some("long") => DataType::Int64,
+        Some("float") => DataType::Float32,
+        Some("double") => DataType::Float64,
+        Some(decimal) if decimal.starts_with("decimal") => DataType::Float64,
+        Some("binary") => DataType::Binary,
+        Some("date") => DataType::Date,
+        Some("timestamp") => DataType::Datetime(TimeUnit::Microseconds, Some(TimeZone::UTC)),
+        Some("timestamp_ntz") => DataType::Datetime(TimeUnit::Microseconds, None),
+        _ => DataType::String,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use serde_json::json;
+
+    use super::*;
+
+    /// A table partitioned by `region`, whose files hold an `amount` column.
+    struct Table {
+        directory: tempfile::TempDir,
+    }
+
+    impl Table {
+        fn new() -> Table {
+            let table = Table {
+                directory: tempfile::tempdir().unwrap(),
+            };
+            fs::create_dir_all(table.log().join("_sidecars")).unwrap();
+            for (file, amounts) in [
+                ("a", [1.0, 2.0]),
+                ("b", [10.0, 20.0]),
+                ("c", [100.0, 200.0]),
+            ] {
+                let mut df = df!("amount" => amounts).unwrap();
+                let file = File::create(table.path().join(format!("{}.parquet", file))).unwrap();
+                ParquetWriter::new(file).finish(&mut df).unwrap();
+            }
+            table
+        }
+
+        fn path(&self) -> &Path {
+            self.directory.path()
+        }
+
+        fn log(&self) -> PathBuf {
+            self.path().join(LOG_DIRECTORY)
+        }
+
+        fn write_json(&self, name: &str, actions: &[Value]) {
+            let mut file = File::create(self.log().join(name)).unwrap();
+            for action in actions {
+                writeln!(file, "{}", action).unwrap();
+            }
+        }
+
+        /// Writes actions as a Parquet checkpoint, with a row per action.
+        fn write_parquet(&self, name: &str, actions: &[Value]) {
+            let lines: Vec<String> = actions.iter().map(Value::to_string).collect();
+            let mut df = JsonLineReader::new(std::io::Cursor::new(lines.join("\n")))
+                .finish()
+                .unwrap();
+            let file = File::create(self.log().join(name)).unwrap();
+            ParquetWriter::new(file).finish(&mut df).unwrap();
+        }
+
+        fn scan(&self, version: Option<u64>) -> Result<Vec<(f64, Option<String>)>> {
+            let df = scan_delta(&self.path().to_string_lossy(), version)?
+                .sort(["amount"], Default::default())
+                .collect()?;
+            let amounts = df.column("amount")?.f64()?;
+            let regions = df.column("region")?.str()?;
+            Ok(amounts
+                .into_no_null_iter()
+                .zip(regions.into_iter().map(|r| r.map(str::to_string)))
+                .collect())
+        }
+    }
+
+    fn metadata() -> Value {
+        let schema = json!({"type": "struct", "fields": [
+            {"name": "amount", "type": "double", "nullable": true, "metadata": {}},
+            {"name": "region", "type": "string", "nullable": true, "metadata": {}}
+        ]});
+        json!({"metaData": {
+            "id": "table",
+            "schemaString": schema.to_string(),
+            "partitionColumns": ["region"]
+        }})
+    }
+
+    fn add(file: &str, region: Option<&str>) -> Value {
+        json!({"add": {
+            "path": format!("{}.parquet", file),
+            "partitionValues": {"region": region},
+            "dataChange": true
+        }})
+    }
+
+    fn remove(file: &str) -> Value {
+        json!({"remove": {"path": format!("{}.parquet", file), "dataChange": true}})
+    }
+
+    /// Commits versions 0 to 2: two files are added, one replaced, then one more added.
+    fn commit_history(table: &Table) {
+        let protocol = json!({"protocol": {"minReaderVersion": 1, "minWriterVersion": 2}});
+        table.write_json(
+            "00000000000000000000.json",
+            &[protocol, metadata(), add("a", Some("east")), add("b", None)],
+        );
+        table.write_json(
+            "00000000000000000001.json",
+            &[remove("a"), add("c", Some("west"))],
+        );
+        table.write_json("00000000000000000002.json", &[remove("b")]);
+    }
+
+    fn row(amount: f64, region: Option<&str>) -> (f64, Option<String>) {
+        (amount, region.map(str::to_string))
+    }
+
+    #[test]
+    fn replays_added_and_removed_files_with_their_partition_values() {
+        let table = Table::new();
+        commit_history(&table);
+        let rows = table.scan(None).unwrap();
+        assert_eq!(rows, [row(100.0, Some("west")), row(200.0, Some("west"))]);
+    }
+
+    #[test]
+    fn travels_to_an_earlier_version() {
+        let table = Table::new();
+        commit_history(&table);
+        let rows = table.scan(Some(0)).unwrap();
+        let expected = [
+            row(1.0, Some("east")),
+            row(2.0, Some("east")),
+            row(10.0, None),
+            row(20.0, None),
+        ];
+        assert_eq!(rows, expected);
+        assert_eq!(table.scan(Some(1)).unwrap().len(), 4);
+        let err = table.scan(Some(3)).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("has no version 3, the latest is 2")
+        );
+    }
+
+    #[test]
+    fn uses_a_checkpoint_in_parts_only_once_complete() {
+        let table = Table::new();
+        commit_history(&table);
+        // Part 2 of the checkpoint of version 1, which alone would leave out file c.
+        let part = "00000000000000000001.checkpoint.0000000002.0000000002.parquet";
+        table.write_parquet(part, &[metadata(), add("b", None)]);
+        let log = Log::list(&table.log()).unwrap();
+        assert!(log.checkpoints.is_empty());
+        assert_eq!(table.scan(Some(1)).unwrap().len(), 4);
+
+        table.write_parquet(
+            "00000000000000000001.checkpoint.0000000001.0000000002.parquet",
+            &[add("c", Some("west"))],
+        );
+        // With the checkpoint complete, the commits before it are no longer needed.
+        fs::remove_file(table.log().join("00000000000000000000.json")).unwrap();
+        fs::remove_file(table.log().join("00000000000000000001.json")).unwrap();
+        let log = Log::list(&table.log()).unwrap();
+        assert_eq!(log.checkpoints[&1].len(), 2);
+        assert_eq!(table.scan(Some(1)).unwrap().len(), 4);
+        assert_eq!(table.scan(None).unwrap().len(), 2);
+        assert!(table.scan(Some(0)).is_err());
+    }
+
+    #[test]
+    fn reads_a_v2_checkpoint_with_sidecars() {
+        let table = Table::new();
+        commit_history(&table);
+        table.write_parquet(
+            "_sidecars/00000000000000000001.sidecar.parquet",
+            &[add("b", None), add("c", Some("west"))],
+        );
+        let sidecar = json!({"sidecar": {
+            "path": "00000000000000000001.sidecar.parquet",
+            "sizeInBytes": 1,
+            "modificationTime": 0
+        }});
+        table.write_json(
+            "00000000000000000001.checkpoint.3a0d65cd-4056-49b8-937b-95f9e3ee90e5.json",
+            &[
+                json!({"checkpointMetadata": {"version": 1}}),
+                metadata(),
+                sidecar,
+            ],
+        );
+        fs::remove_file(table.log().join("00000000000000000001.json")).unwrap();
+        let rows = table.scan(None).unwrap();
+        assert_eq!(rows, [row(100.0, Some("west")), row(200.0, Some("west"))]);
+    }
+}
@@ -0,0 +1,276 @@
+//! Comparison of statistics against a stored baseline, to catch a dataset drifting
+//! between runs.
+//!
+//! A baseline is the JSON output of the statistics, as written by the `snapshot` command
+//! or `stats -o json`. Comparing recalculates the same statistics and reports the change
+//! of every numeric one, failing when a change exceeds its tolerance.
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{Context, Result, anyhow};
+use serde::Serialize;
+
+use crate::metrics::{Sample, samples};
+use crate::stats::{SelectedStats, StatsOptions};
+
+/// How far a statistic may move from its baseline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tolerance {
+    /// At most this much in either direction.
+    Absolute(f64),
+    /// At most this percentage of the baseline value in either direction.
+    Relative(f64),
+}
+
+impl FromStr for Tolerance {
+    type Err = anyhow::Error;
+
+    /// Parses `5%` as a relative tolerance and `0.5` as an absolute one.
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let (number, relative) = match s.strip_suffix('%') {
+            Some(number) => (number, true),
+            None => (s, false),
+        };
+        let value: f64 = number
+            .trim()
+            .parse()
+            .map_err(|_| anyhow!("Expected a tolerance such as '5%' or '0.5', got '{}'", s))?;
+        if value < 0.0 {
+            return Err(anyhow!("Tolerances can't be negative, got '{}'", s));
+        }
+        Ok(if relative {
+            Tolerance::Relative(value)
+        } else {
+            Tolerance::Absolute(value)
+        })
+    }
+}
+
+impl fmt::Display for Tolerance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Tolerance::Absolute(value) => write!(f, "{}", value),
+            Tolerance::Relative(percent) => write!(f, "{}%", percent),
+        }
+    }
+}
+
+impl Tolerance {
+    /// Whether moving from `baseline` to `current` stays within the tolerance. Any change
+    /// from a baseline of zero exceeds a relative tolerance.
+    fn allows(self, baseline: f64, current: f64) -> bool {
+        let change = (current - baseline).abs();
+        match self {
+            Tolerance::Absolute(max) => change <= max,
+            Tolerance::Relative(_) if baseline == 0.0 => change == 0.0,
+            Tolerance::Relative(percent) => change / baseline.abs() * 100.0 <= percent,
+        }
+    }
+}
+
+/// Options controlling when a change counts as drift.
+#[derive(Debug, Clone)]
+pub struct DriftOptions {
+    /// The tolerance of every statistic without its own.
+    pub max_drift: Tolerance,
+    /// Tolerances of individual statistics, by name as in the report, e.g. `mean` or
+    /// `q0.95`.
+    pub tolerances: Vec<(String, Tolerance)>,
+}
+
+impl Default for DriftOptions {
+    fn default() -> Self {
+        Self {
+            max_drift: Tolerance::Relative(10.0),
+            tolerances: Vec::new(),
+        }
+    }
+}
+
+impl DriftOptions {
+    fn tolerance(&self, statistic: &str) -> Tolerance {
+        self.tolerances
+            .iter()
+            .rev()
+            .find(|(name, _)| name == statistic)
+            .map_or(self.max_drift, |(_, tolerance)| *tolerance)
+    }
+}
+
+/// The change of a single statistic since the baseline.
+#[derive(Debug, Clone, Serialize)]
+pub struct Drift {
+    /// The column, with its group when the data was grouped.
+    pub subject: String,
+    pub statistic: String,
+    pub baseline: f64,
+    pub current: f64,
+    /// The current value minus the baseline value.
+    pub change: f64,
+    /// The change as a percentage of the baseline value, or `None` for a baseline of zero.
+    pub percent_change: Option<f64>,
+    /// The tolerance applied, e.g. `10%`.
+    pub tolerance: String,
+    /// Whether the change exceeds the tolerance.
+    pub exceeded: bool,
+}
+
+/// The changes of every statistic since the baseline.
+#[derive(Debug, Clone, Serialize)]
+pub struct DriftReport {
+    pub drifts: Vec<Drift>,
+    /// The statistics of the baseline that couldn't be calculated anymore, e.g. because
+    /// a column or group disappeared, as `subject: statistic`.
+    pub missing: Vec<String>,
+}
+
+impl DriftReport {
+    /// Fails with [`DriftExceeded`] if any statistic drifted beyond its tolerance or is
+    /// missing.
+    pub fn check(&self) -> Result<()> {
+        let mut problems: Vec<String> = self
+            .drifts
+            .iter()
+            .filter(|d| d.exceeded)
+            .map(|d| {
+                format!(
+                    "{}: {} changed from {} to {}, beyond {}",
+                    d.subject, d.statistic, d.baseline, d.current, d.tolerance
+                )
+            })
+            .collect();
+        problems.extend(self.missing.iter().map(|m| format!("{} is missing", m)));
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(DriftExceeded(problems).into())
+        }
+    }
+}
+
+/// The error returned when statistics drifted beyond their tolerances, listing each.
+#[derive(Debug)]
+pub struct DriftExceeded(pub Vec<String>);
+
+impl fmt::Display for DriftExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} statistic(s) drifted from the baseline", self.0.len())?;
+        for problem in &self.0 {
+            write!(f, "\n  {}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for DriftExceeded {}
+
+/// Writes statistics as a baseline JSON file.
+pub fn write_baseline(path: &str, stats: &[SelectedStats]) -> Result<()> {
+    let json = serde_json::to_string_pretty(stats)?;
+    std::fs::write(path, json + "\n").with_context(|| format!("Failed to write '{}'", path))
+}
+
+/// Reads a baseline JSON file.
+pub fn read_baseline(path: &str) -> Result<Vec<SelectedStats>> {
+    let json =
+        std::fs::read_to_string(path).with_context(|| format!("Failed to read '{}'", path))?;
+    serde_json::from_str(&json).with_context(|| format!("Invalid baseline '{}'", path))
+}
+
+/// The options that calculate the baseline's statistics again: the same columns, group
+/// column, quantiles and optional statistics.
+pub fn baseline_options(baseline: &[SelectedStats]) -> StatsOptions {
+    let mut options = StatsOptions::default();
+    for s in baseline {
+        if !options.columns.contains(&s.column) {
+            options.columns.push(s.column.clone());
+        }
+        if let Some(group) = &s.group {
+            options.group_by = Some(group.column.clone());
+        }
+        for q in &s.quantiles {
+            if !options.quantiles.contains(&q.quantile) {
+                options.quantiles.push(q.quantile);
+            }
+        }
+        options.moments |= s.moments.is_some();
+        options.mad |= s.mad.is_some();
+        options.extended |= s.extended.is_some();
+    }
+    options
+}
+
+/// Compares the current statistics to the baseline, statistic by statistic.
+pub fn compare_stats(
+    baseline: &[SelectedStats],
+    current: &[SelectedStats],
+    options: &DriftOptions,
+) -> Result<DriftReport> {
+    // The input names don't matter; only the columns and groups are matched.
+    let current = samples("", current, &[])?;
+    let mut report = DriftReport {
+        drifts: Vec::new(),
+        missing: Vec::new(),
+    };
+    for before in samples("", baseline, &[])? {
+        let subject = subject(&before);
+        let statistic = statistic_of(&before);
+        let now = current
+            .iter()
+            .find(|s| subject_of(s) == subject_of(&before) && statistic_of(s) == statistic);
+        let Some(now) = now else {
+            report.missing.push(format!("{}: {}", subject, statistic));
+            continue;
+        };
+        let baseline_value: f64 = before.value.parse()?;
+        let current_value: f64 = now.value.parse()?;
+        let tolerance = options.tolerance(&statistic);
+        let change = current_value - baseline_value;
+        report.drifts.push(Drift {
+            subject,
+            statistic,
+            baseline: baseline_value,
+            current: current_value,
+            change,
+            percent_change: (baseline_value != 0.0).then(|| change / baseline_value.abs() * 100.0),
+            tolerance: tolerance.to_string(),
+            exceeded: !tolerance.allows(baseline_value, current_value),
+        });
+    }
+    Ok(report)
+}
+
+/// The labels that identify what a sample describes, other than the file.
+fn subject_of(sample: &Sample) -> Vec<&(String, String)> {
+    sample
+        .labels
+        .iter()
+        .filter(|(label, _)| label != "file" && label != "quantile")
+        .collect()
+}
+
+/// The column, with its group, e.g. `Amount (Currency=EUR)`.
+fn subject(sample: &Sample) -> String {
+    let label = |name: &str| {
+        sample
+            .labels
+            .iter()
+            .find(|(l, _)| l == name)
+            .map(|(_, value)| value.as_str())
+    };
+    let column = label("column").unwrap_or_default();
+    match (label("group_by"), label("group")) {
+        (Some(group_by), Some(group)) => format!("{} ({}={})", column, group_by, group),
+        _ => column.to_string(),
+    }
+}
+
+/// The name of the statistic, with quantiles named like `q0.95`.
+fn statistic_of(sample: &Sample) -> String {
+    match sample.labels.iter().find(|(label, _)| label == "quantile") {
+        Some((_, quantile)) => format!("q{}", quantile),
+        None => sample.name.clone(),
+    }
+}
@@ -0,0 +1,94 @@
+//! Detection of duplicated rows, either entirely or by a set of key columns.
+
+use anyhow::Result;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::collect;
+
+/// How many times each distinct key occurs, in the temporary counts.
+const COPIES: &str = "__copies";
+
+/// The duplicates found in the input.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateReport {
+    /// The columns rows were compared on, or every column when empty.
+    pub keys: Vec<String>,
+    /// The number of rows.
+    pub rows: usize,
+    /// The number of distinct rows, or distinct keys.
+    pub distinct: usize,
+    /// The number of rows repeating an earlier one: the rows that would be left out when
+    /// keeping a single copy of each.
+    pub duplicates: usize,
+    /// The number of rows, or keys, that occur more than once.
+    pub duplicated: usize,
+}
+
+/// The duplicates of the input, with the rows involved.
+pub struct Duplicates {
+    /// The summary of the duplicates.
+    pub report: DuplicateReport,
+    /// Every copy of each duplicated row, sorted so that copies are adjacent.
+    pub rows: DataFrame,
+}
+
+/// Counts the rows that are duplicates of another, comparing only the `keys` columns if
+/// any are given. Nulls compare equal to each other.
+///
+/// The counts take a pass over the data; the duplicated rows take another, only when
+/// `with_rows` is set.
+pub fn compute_duplicates(
+    mut lf: LazyFrame,
+    keys: &[String],
+    with_rows: bool,
+) -> Result<Duplicates> {
+    let key_names: Vec<String> = if keys.is_empty() {
+        lf.collect_schema()?
+            .iter_names()
+            .map(|name| name.to_string())
+            .collect()
+    } else {
+        keys.to_vec()
+    };
+    let key_exprs: Vec<Expr> = key_names.iter().map(|k| col(k.as_str())).collect();
+
+    let counts_df = collect(
+        lf.clone()
+            .group_by(key_exprs.clone())
+            .agg([len().alias(COPIES)])
+            .select([
+                len().alias("distinct"),
+                (col(COPIES) - lit(1)).sum().alias("duplicates"),
+                col(COPIES).gt(lit(1)).sum().alias("duplicated"),
+            ]),
+    )?;
+    let get_count = |name: &str| -> Result<usize> {
+        Ok(match counts_df.column(name)?.get(0)? {
+            AnyValue::Null => 0,
+            av => av.try_extract::<u64>()? as usize,
+        })
+    };
+    let distinct = get_count("distinct")?;
+    let duplicates = get_count("duplicates")?;
+
+    let rows = if with_rows {
+        collect(
+            lf.filter(len().over(key_exprs).gt(lit(1)))
+                .sort(key_names.clone(), SortMultipleOptions::default()),
+        )?
+    } else {
+        DataFrame::empty()
+    };
+
+    Ok(Duplicates {
+        report: DuplicateReport {
+            keys: keys.to_vec(),
+            rows: distinct + duplicates,
+            distinct,
+            duplicates,
+            duplicated: get_count("duplicated")?,
+        },
+        rows,
+    })
+}
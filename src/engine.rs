@@ -0,0 +1,123 @@
+//! Execution of query plans.
+//!
+//! By default every query in this crate is executed with Polars' streaming engine,
+//! which processes the input in batches instead of loading it all into memory. Plans
+//! that reduce to aggregates (counts, sums, min/max, group-by results, ...) therefore
+//! run in roughly constant memory, so CSV files much larger than RAM can be analyzed.
+//! Operations that cannot stream, such as exact medians and quantiles, fall back to
+//! in-memory execution for just that part of the plan.
+
+use std::io::{self, Read};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::Result;
+use polars::prelude::*;
+
+/// The engine used to execute query plans.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExecutionEngine {
+    /// Process the data in batches, in roughly constant memory.
+    #[default]
+    Streaming,
+    /// Load the data into memory before processing it. Can be faster for small files.
+    InMemory,
+}
+
+/// Whether plans run on the in-memory engine instead of the default streaming one.
+static IN_MEMORY: AtomicBool = AtomicBool::new(false);
+
+/// Receives a notification around the execution of every query, and while input that
+/// has to be copied before it can be scanned is read, e.g. to show progress.
+pub trait QueryObserver: Send + Sync {
+    /// Called before a query starts executing.
+    fn query_started(&self);
+    /// Called after a query has finished executing, successfully or not.
+    fn query_finished(&self);
+    /// Called before stdin, a download or a compressed file is copied into a temporary
+    /// file, with its size if known.
+    fn read_started(&self, _size: Option<u64>) {}
+    /// Called as the input is copied, with the number of bytes read since the last call.
+    fn bytes_read(&self, _bytes: u64) {}
+    /// Called once the input has been copied, or has failed to be.
+    fn read_finished(&self) {}
+}
+
+/// The observer notified around every query, if any.
+static OBSERVER: OnceLock<Box<dyn QueryObserver>> = OnceLock::new();
+
+/// Sets the observer notified around every subsequent query. Only the first observer
+/// set is kept.
+pub fn set_observer(observer: Box<dyn QueryObserver>) {
+    let _ = OBSERVER.set(observer);
+}
+
+/// A reader telling the observer how much of the input has been read through it, from
+/// its creation until it is dropped.
+pub(crate) struct ObservedReader<R> {
+    inner: R,
+}
+
+impl<R> ObservedReader<R> {
+    /// Wraps a reader of an input of the given size, if known.
+    pub(crate) fn new(inner: R, size: Option<u64>) -> Self {
+        if let Some(observer) = OBSERVER.get() {
+            observer.read_started(size);
+        }
+        ObservedReader { inner }
+    }
+}
+
+impl<R: Read> Read for ObservedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if let Some(observer) = OBSERVER.get() {
+            observer.bytes_read(read as u64);
+        }
+        Ok(read)
+    }
+}
+
+impl<R> Drop for ObservedReader<R> {
+    fn drop(&mut self) {
+        if let Some(observer) = OBSERVER.get() {
+            observer.read_finished();
+        }
+    }
+}
+
+/// Runs a query, notifying the observer before and after.
+fn observed(lf: LazyFrame, engine: Engine) -> Result<DataFrame> {
+    let observer = OBSERVER.get();
+    if let Some(observer) = observer {
+        observer.query_started();
+    }
+    let result = lf.collect_with_engine(engine);
+    if let Some(observer) = observer {
+        observer.query_finished();
+    }
+    Ok(result?)
+}
+
+/// Selects the engine used by every subsequent query.
+pub fn set_engine(engine: ExecutionEngine) {
+    IN_MEMORY.store(engine == ExecutionEngine::InMemory, Ordering::Relaxed);
+}
+
+/// Executes a query plan with the selected engine and materializes the result.
+pub fn collect(lf: LazyFrame) -> Result<DataFrame> {
+    let engine = if IN_MEMORY.load(Ordering::Relaxed) {
+        Engine::InMemory
+    } else {
+        Engine::Streaming
+    };
+    observed(lf, engine)
+}
+
+/// Executes a query plan with the in-memory engine, whatever engine is selected.
+///
+/// For plans the streaming engine gets wrong, such as slices of a sorted column combined
+/// with other aggregations. Such plans need all of a column's values in memory anyway.
+pub fn collect_in_memory(lf: LazyFrame) -> Result<DataFrame> {
+    observed(lf, Engine::InMemory)
+}
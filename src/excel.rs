@@ -0,0 +1,537 @@
+//! Reading of Excel workbooks (`.xlsx`), which are zip archives of XML parts.
+//!
+//! Only the cell values of the selected sheet are read: shared and inline strings,
+//! numbers, booleans and the cached results of formulas. Error cells are read as
+//! missing. Styles aren't interpreted, so dates are read as the numbers Excel stores
+//! them as, counting days since 1899-12-30.
+//!
+//! Each column becomes a Boolean, Int64 or Float64 column if all its values are of that
+//! kind, and a String column otherwise.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::Read;
+
+use anyhow::{Context, Result, anyhow, bail};
+use flate2::read::DeflateDecoder;
+use polars::prelude::*;
+use quick_xml::Reader;
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::{BytesRef, BytesStart, Event};
+
+/// Reads a sheet of a workbook into a DataFrame.
+///
+/// The sheet is picked by name, or else by its position counting from 1, and defaults
+/// to the first one. With `has_header`, the first row holds the column names; otherwise
+/// the columns are named `column_1`, `column_2` and so on, like the CSV reader's.
+pub fn read_xlsx(data: &[u8], sheet: Option<&str>, has_header: bool) -> Result<DataFrame> {
+    let archive = ZipArchive::new(data)?;
+    let sheets = read_sheets(&archive)?;
+    let (_, path) = match sheet {
+        None => sheets.first(),
+        Some(sheet) => sheets.iter().find(|(name, _)| name == sheet).or_else(|| {
+            sheet
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| n.checked_sub(1))
+                .and_then(|i| sheets.get(i))
+        }),
+    }
+    .ok_or_else(|| {
+        let names: Vec<&str> = sheets.iter().map(|(name, _)| name.as_str()).collect();
+        anyhow!(
+            "No sheet '{}' in the workbook, expected one of: {}",
+            sheet.unwrap_or_default(),
+            names.join(", ")
+        )
+    })?;
+
+    let shared_strings = match archive.read("xl/sharedStrings.xml") {
+        Ok(xml) => read_shared_strings(&xml)?,
+        Err(_) => Vec::new(),
+    };
+    let rows = read_cells(&archive.read(path)?, &shared_strings)
+        .with_context(|| format!("Invalid sheet '{}'", path))?;
+    to_dataframe(rows, has_header)
+}
+
+/// The value of a cell.
+#[derive(Debug, Clone, PartialEq)]
+enum Cell {
+    Empty,
+    Number(f64),
+    Bool(bool),
+    Text(String),
+}
+
+impl Cell {
+    fn to_text(&self) -> Option<String> {
+        match self {
+            Cell::Empty => None,
+            Cell::Number(n) => Some(n.to_string()),
+            Cell::Bool(b) => Some(b.to_string()),
+            Cell::Text(s) => Some(s.clone()),
+        }
+    }
+}
+
+/// Lists the sheets of the workbook by name, with the paths of their parts, in workbook
+/// order.
+fn read_sheets(archive: &ZipArchive) -> Result<Vec<(String, String)>> {
+    // The workbook names each sheet and refers to its part by a relationship id.
+    let mut relationships = HashMap::new();
+    for_each_element(&archive.read("xl/_rels/workbook.xml.rels")?, |e| {
+        if e.local_name().as_ref() == b"Relationship"
+            && let (Some(id), Some(target)) = (attribute(e, b"Id")?, attribute(e, b"Target")?)
+        {
+            let path = match target.strip_prefix('/') {
+                Some(absolute) => absolute.to_string(),
+                None => format!("xl/{}", target),
+            };
+            relationships.insert(id, path);
+        }
+        Ok(())
+    })?;
+
+    let mut sheets = Vec::new();
+    for_each_element(&archive.read("xl/workbook.xml")?, |e| {
+        if e.local_name().as_ref() == b"sheet"
+            && let (Some(name), Some(id)) = (attribute(e, b"name")?, attribute(e, b"r:id")?)
+            && let Some(path) = relationships.get(&id)
+        {
+            sheets.push((name, path.clone()));
+        }
+        Ok(())
+    })?;
+    if sheets.is_empty() {
+        bail!("The workbook has no sheets");
+    }
+    Ok(sheets)
+}
+
+/// Reads the table of strings that cells refer to by index.
+fn read_shared_strings(xml: &[u8]) -> Result<Vec<String>> {
+    let mut reader = Reader::from_reader(xml);
+    let mut buf = Vec::new();
+    let mut strings = Vec::new();
+    let mut current = String::new();
+    // Text counts within `<t>`, except in the phonetic hints of `<rPh>`.
+    let (mut in_text, mut in_phonetic) = (false, false);
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"si" => current.clear(),
+                b"t" => in_text = true,
+                b"rPh" => in_phonetic = true,
+                _ => {}
+            },
+            Event::End(e) => match e.local_name().as_ref() {
+                b"si" => strings.push(current.clone()),
+                b"t" => in_text = false,
+                b"rPh" => in_phonetic = false,
+                _ => {}
+            },
+            Event::Empty(e) if e.local_name().as_ref() == b"si" => strings.push(String::new()),
+            Event::Text(e) if in_text && !in_phonetic => current.push_str(&e.decode()?),
+            Event::GeneralRef(e) if in_text && !in_phonetic => current.push_str(&resolve(&e)?),
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    Ok(strings)
+}
+
+/// Reads the cells of a sheet, row by row. Rows without any cells are left out.
+fn read_cells(xml: &[u8], shared_strings: &[String]) -> Result<Vec<Vec<Cell>>> {
+    let mut reader = Reader::from_reader(xml);
+    let mut buf = Vec::new();
+    let mut rows: Vec<Vec<Cell>> = Vec::new();
+    let mut row: Vec<Cell> = Vec::new();
+    // The type and position of the current cell, and its raw value.
+    let mut cell_type = String::new();
+    let mut column = 0;
+    let mut value = String::new();
+    let mut in_value = false;
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"row" => row.clear(),
+                b"c" => {
+                    cell_type = attribute(&e, b"t")?.unwrap_or_default();
+                    column = match attribute(&e, b"r")? {
+                        Some(reference) => column_of(&reference)?,
+                        None => row.len(),
+                    };
+                    value.clear();
+                }
+                b"v" | b"t" => in_value = true,
+                _ => {}
+            },
+            Event::End(e) => match e.local_name().as_ref() {
+                b"row" if !row.is_empty() => rows.push(std::mem::take(&mut row)),
+                b"c" => {
+                    let cell = parse_cell(&cell_type, &value, shared_strings)?;
+                    if row.len() <= column {
+                        row.resize(column + 1, Cell::Empty);
+                    }
+                    row[column] = cell;
+                }
+                b"v" | b"t" => in_value = false,
+                _ => {}
+            },
+            Event::Text(e) if in_value => value.push_str(&e.decode()?),
+            Event::GeneralRef(e) if in_value => value.push_str(&resolve(&e)?),
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    Ok(rows)
+}
+
+/// Interprets the raw value of a cell according to its type attribute.
+fn parse_cell(cell_type: &str, value: &str, shared_strings: &[String]) -> Result<Cell> {
+    if value.is_empty() && cell_type != "inlineStr" && cell_type != "str" {
+        return Ok(Cell::Empty);
+    }
+    Ok(match cell_type {
+        "s" => {
+            let index: usize = value
+                .parse()
+                .map_err(|_| anyhow!("Invalid shared string index '{}'", value))?;
+            let text = shared_strings
+                .get(index)
+                .ok_or_else(|| anyhow!("Shared string {} doesn't exist", index))?;
+            Cell::Text(text.clone())
+        }
+        "b" => Cell::Bool(value == "1"),
+        "e" => Cell::Empty,
+        "inlineStr" | "str" | "d" => Cell::Text(value.to_string()),
+        _ => value
+            .parse()
+            .map_or_else(|_| Cell::Text(value.to_string()), Cell::Number),
+    })
+}
+
+/// Builds the DataFrame from the cells, picking each column's type from its values.
+fn to_dataframe(mut rows: Vec<Vec<Cell>>, has_header: bool) -> Result<DataFrame> {
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let header = if has_header && !rows.is_empty() {
+        rows.remove(0)
+    } else {
+        Vec::new()
+    };
+    let names: Vec<String> = (0..width)
+        .map(|i| {
+            header
+                .get(i)
+                .and_then(Cell::to_text)
+                .unwrap_or_else(|| format!("column_{}", i + 1))
+        })
+        .collect();
+
+    let columns = names
+        .into_iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let cells: Vec<&Cell> = rows
+                .iter()
+                .map(|row| row.get(i).unwrap_or(&Cell::Empty))
+                .collect();
+            column_of_cells(name, &cells)
+        })
+        .collect();
+    Ok(DataFrame::new(columns)?)
+}
+
+/// Builds a column of the narrowest type that holds all the cells.
+fn column_of_cells(name: String, cells: &[&Cell]) -> Column {
+    let present = || cells.iter().filter(|c| **c != &Cell::Empty);
+    let name = PlSmallStr::from(name);
+    if present().all(|c| matches!(c, Cell::Bool(_))) {
+        let values: Vec<Option<bool>> = cells
+            .iter()
+            .map(|c| match c {
+                Cell::Bool(b) => Some(*b),
+                _ => None,
+            })
+            .collect();
+        return Column::new(name, values);
+    }
+    if present().all(|c| matches!(c, Cell::Number(_))) {
+        let values: Vec<Option<f64>> = cells
+            .iter()
+            .map(|c| match c {
+                Cell::Number(n) => Some(*n),
+                _ => None,
+            })
+            .collect();
+        let integral = values
+            .iter()
+            .flatten()
+            .all(|n| n.fract() == 0.0 && n.abs() < i64::MAX as f64);
+        if integral {
+            let values: Vec<Option<i64>> = values.iter().map(|n| n.map(|n| n as i64)).collect();
+            return Column::new(name, values);
+        }
+        return Column::new(name, values);
+    }
+    let values: Vec<Option<String>> = cells.iter().map(|c| c.to_text()).collect();
+    Column::new(name, values)
+}
+
+/// The number of columns of a worksheet, up to column XFD.
+const MAX_COLUMNS: usize = 16_384;
+
+/// Converts the letters of a cell reference such as `AB12` to a column index from 0.
+fn column_of(reference: &str) -> Result<usize> {
+    let invalid = || anyhow!("Invalid cell reference '{}'", reference);
+    let mut column: usize = 0;
+    for letter in reference.bytes().take_while(u8::is_ascii_alphabetic) {
+        let letter = (letter.to_ascii_uppercase() - b'A') as usize + 1;
+        // Checked at every letter, so a long run of letters can't overflow.
+        column = column
+            .checked_mul(26)
+            .and_then(|column| column.checked_add(letter))
+            .filter(|&column| column <= MAX_COLUMNS)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Invalid cell reference '{}': worksheets end at column XFD",
+                    reference
+                )
+            })?;
+    }
+    column.checked_sub(1).ok_or_else(invalid)
+}
+
+/// Calls `f` with every start or empty element of an XML part.
+fn for_each_element(xml: &[u8], mut f: impl FnMut(&BytesStart) -> Result<()>) -> Result<()> {
+    let mut reader = Reader::from_reader(xml);
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(e) | Event::Empty(e) => f(&e)?,
+            Event::Eof => return Ok(()),
+            _ => {}
+        }
+        buf.clear();
+    }
+}
+
+/// Returns the unescaped value of an element's attribute, if it has one.
+fn attribute(element: &BytesStart, name: &[u8]) -> Result<Option<String>> {
+    Ok(match element.try_get_attribute(name)? {
+        Some(attribute) => Some(attribute.unescape_value()?.into_owned()),
+        None => None,
+    })
+}
+
+/// Resolves a character or predefined entity reference such as `&#10;` or `&amp;`.
+fn resolve<'a>(reference: &'a BytesRef) -> Result<Cow<'a, str>> {
+    if let Some(c) = reference.resolve_char_ref()? {
+        return Ok(Cow::Owned(c.to_string()));
+    }
+    let name = reference.decode()?;
+    resolve_predefined_entity(&name)
+        .map(Cow::Borrowed)
+        .ok_or_else(|| anyhow!("Unknown entity '&{};'", name))
+}
+
+/// The signatures of the zip records that are read.
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x0605_4b50;
+const CENTRAL_DIRECTORY_ENTRY: u32 = 0x0201_4b50;
+const LOCAL_FILE_HEADER: u32 = 0x0403_4b50;
+
+/// A zip archive held in memory, reading only what workbooks use: stored or deflated
+/// entries, without zip64 extensions or encryption.
+struct ZipArchive<'a> {
+    data: &'a [u8],
+    /// The compression method and compressed size of each entry and the offset of its
+    /// local header, keyed by name.
+    entries: HashMap<String, (u16, usize, usize)>,
+}
+
+impl<'a> ZipArchive<'a> {
+    fn new(data: &'a [u8]) -> Result<Self> {
+        let not_zip = || anyhow!("Not an xlsx workbook: the data isn't a zip archive");
+        // The end record is last, followed only by a comment of up to 64 KiB.
+        let search_from = data.len().saturating_sub(22 + u16::MAX as usize);
+        let end = (search_from..data.len().saturating_sub(21))
+            .rev()
+            .find(|&i| read_u32(data, i) == Some(END_OF_CENTRAL_DIRECTORY))
+            .ok_or_else(not_zip)?;
+        let count = read_u16(data, end + 10).ok_or_else(not_zip)? as usize;
+        let mut offset = read_u32(data, end + 16).ok_or_else(not_zip)? as usize;
+
+        let mut entries = HashMap::with_capacity(count);
+        for _ in 0..count {
+            if read_u32(data, offset) != Some(CENTRAL_DIRECTORY_ENTRY) {
+                bail!("Corrupt zip archive: bad central directory entry");
+            }
+            let field = |at: usize| read_u16(data, offset + at).ok_or_else(not_zip);
+            let method = field(10)?;
+            let compressed = read_u32(data, offset + 20).ok_or_else(not_zip)? as usize;
+            let name_length = field(28)? as usize;
+            let extra_length = field(30)? as usize;
+            let comment_length = field(32)? as usize;
+            let header = read_u32(data, offset + 42).ok_or_else(not_zip)? as usize;
+            let name = data
+                .get(offset + 46..offset + 46 + name_length)
+                .ok_or_else(not_zip)?;
+            entries.insert(
+                String::from_utf8_lossy(name).into_owned(),
+                (method, compressed, header),
+            );
+            offset += 46 + name_length + extra_length + comment_length;
+        }
+        Ok(ZipArchive { data, entries })
+    }
+
+    /// Reads and decompresses an entry.
+    fn read(&self, name: &str) -> Result<Vec<u8>> {
+        let &(method, compressed, header) = self
+            .entries
+            .get(name)
+            .ok_or_else(|| anyhow!("The workbook has no '{}'", name))?;
+        let corrupt = || anyhow!("Corrupt zip entry '{}'", name);
+        if read_u32(self.data, header) != Some(LOCAL_FILE_HEADER) {
+            return Err(corrupt());
+        }
+        let name_length = read_u16(self.data, header + 26).ok_or_else(corrupt)? as usize;
+        let extra_length = read_u16(self.data, header + 28).ok_or_else(corrupt)? as usize;
+        let start = header + 30 + name_length + extra_length;
+        let contents = self
+            .data
+            .get(start..start + compressed)
+            .ok_or_else(corrupt)?;
+        match method {
+            0 => Ok(contents.to_vec()),
+            8 => {
+                let mut buf = Vec::new();
+                DeflateDecoder::new(contents)
+                    .read_to_end(&mut buf)
+                    .with_context(corrupt)?;
+                Ok(buf)
+            }
+            _ => bail!(
+                "Zip entry '{}' uses unsupported compression method {}",
+                name,
+                method
+            ),
+        }
+    }
+}
+
+fn read_u16(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn read_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A workbook with an `Accounts` sheet of mixed cells and a `Counts` sheet of numbers.
+    /// The first sheet and the shared strings are deflated, the second sheet is stored.
+    const WORKBOOK: &[u8] = include_bytes!("../test_data/workbook.xlsx");
+
+    fn strings<'a>(df: &'a DataFrame, column: &str) -> Vec<Option<&'a str>> {
+        df.column(column)
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn reads_the_first_sheet_with_its_header() {
+        let df = read_xlsx(WORKBOOK, None, true).unwrap();
+        assert_eq!(df.get_column_names(), ["Name", "Amount", "Active", "Note"]);
+        // The empty row is left out, and so are the rows without any cells.
+        assert_eq!(df.height(), 4);
+
+        // Shared strings, one of them in runs with a phonetic hint, and an inline string.
+        assert_eq!(
+            strings(&df, "Name"),
+            [Some("Alice"), Some("Bob"), Some("Carol"), None]
+        );
+
+        let amounts: Vec<Option<f64>> = df
+            .column("Amount")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(amounts, [Some(10.5), Some(3.0), None, Some(-2.0)]);
+
+        let active: Vec<Option<bool>> = df
+            .column("Active")
+            .unwrap()
+            .bool()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(active, [Some(true), Some(false), Some(true), None]);
+    }
+
+    #[test]
+    fn reads_inline_strings_formula_results_and_errors() {
+        let df = read_xlsx(WORKBOOK, Some("Accounts"), true).unwrap();
+        // Entities are resolved, a formula gives its cached result and an error is missing.
+        assert_eq!(
+            strings(&df, "Note"),
+            [Some("Q&A été"), None, Some("Carol!"), None]
+        );
+    }
+
+    #[test]
+    fn selects_a_sheet_by_name_or_position() {
+        for sheet in ["Counts", "2"] {
+            let df = read_xlsx(WORKBOOK, Some(sheet), true).unwrap();
+            let counts: Vec<Option<i64>> =
+                df.column("n").unwrap().i64().unwrap().into_iter().collect();
+            assert_eq!(counts, [Some(1), Some(2), Some(3)]);
+        }
+        let err = read_xlsx(WORKBOOK, Some("Missing"), true).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "No sheet 'Missing' in the workbook, expected one of: Accounts, Counts"
+        );
+    }
+
+    #[test]
+    fn names_the_columns_without_a_header() {
+        let df = read_xlsx(WORKBOOK, Some("Counts"), false).unwrap();
+        assert_eq!(df.get_column_names(), ["column_1"]);
+        assert_eq!(strings(&df, "column_1")[0], Some("n"));
+    }
+
+    #[test]
+    fn reads_stored_and_deflated_entries() {
+        let archive = ZipArchive::new(WORKBOOK).unwrap();
+        assert_eq!(archive.entries["xl/worksheets/sheet1.xml"].0, 8);
+        assert_eq!(archive.entries["xl/worksheets/sheet2.xml"].0, 0);
+        for name in ["xl/worksheets/sheet1.xml", "xl/worksheets/sheet2.xml"] {
+            let xml = archive.read(name).unwrap();
+            assert!(xml.starts_with(b"<?xml"), "{}", name);
+        }
+        assert!(ZipArchive::new(b"not a zip").is_err());
+    }
+
+    #[test]
+    fn converts_cell_references_to_columns() {
+        assert_eq!(column_of("A1").unwrap(), 0);
+        assert_eq!(column_of("z9").unwrap(), 25);
+        assert_eq!(column_of("AA1").unwrap(), 26);
+        assert_eq!(column_of("XFD1048576").unwrap(), MAX_COLUMNS - 1);
+        for reference in ["XFE1", "ZZZZZZZZZZZZZ1", "1"] {
+            assert!(column_of(reference).is_err(), "{}", reference);
+        }
+    }
+}
@@ -0,0 +1,57 @@
+//! Export of the rows the other commands analyze, with the same filtering and cleaning,
+//! so the cleaning logic doesn't have to be repeated in another tool.
+
+use anyhow::Result;
+use polars::prelude::*;
+
+use crate::stats::NumericParsing;
+
+/// Options controlling how the rows are cleaned before they are exported.
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    /// The columns to export, in this order, or every column when empty.
+    pub columns: Vec<String>,
+    /// The columns to convert to numbers, as the statistics do.
+    pub numeric_columns: Vec<String>,
+    /// How the values of the numeric columns are parsed.
+    pub parsing: NumericParsing,
+    /// Leave out the rows missing a value in any numeric column, including values that
+    /// couldn't be parsed.
+    pub drop_nulls: bool,
+}
+
+/// Cleans the input for export: converts the numeric columns like the statistics do,
+/// drops the rows they're missing values in if requested, and selects the columns.
+///
+/// The input's own filter and type overrides have already been applied by
+/// [`crate::input::scan`].
+pub fn prepare_export(mut lf: LazyFrame, options: &ExportOptions) -> Result<LazyFrame> {
+    for name in &options.numeric_columns {
+        options.parsing.check_strict(&lf, name)?;
+    }
+    if !options.numeric_columns.is_empty() {
+        let conversions: Vec<Expr> = options
+            .numeric_columns
+            .iter()
+            .map(|name| options.parsing.to_float(name).alias(name.as_str()))
+            .collect();
+        lf = lf.with_columns(conversions);
+    }
+    let present = options
+        .numeric_columns
+        .iter()
+        .map(|name| col(name.as_str()).is_not_null())
+        .reduce(|all, present| all.and(present));
+    if let (true, Some(present)) = (options.drop_nulls, present) {
+        lf = lf.filter(present);
+    }
+    if !options.columns.is_empty() {
+        let columns: Vec<Expr> = options
+            .columns
+            .iter()
+            .map(|name| col(name.as_str()))
+            .collect();
+        lf = lf.select(columns);
+    }
+    Ok(lf)
+}
@@ -0,0 +1,294 @@
+//! Parsing of `--filter` expressions into Polars predicates.
+//!
+//! The grammar is a small subset of Rust/Polars expression syntax:
+//!
+//! ```text
+//! expr       := and ( "||" and )*
+//! and        := unary ( "&&" unary )*
+//! unary      := "!" unary | comparison
+//! comparison := operand ( ( "==" | "!=" | "<" | "<=" | ">" | ">=" ) operand )?
+//! operand    := 'col("name")' | number | '"string"' | true | false | null | "(" expr ")"
+//! ```
+//!
+//! For example: `col("Amount Received") > 1000 && col("Payment Currency") == "US Dollar"`.
+//! Comparing with `null` using `==` or `!=` checks whether a value is missing.
+
+use anyhow::{Result, bail};
+use polars::prelude::*;
+
+/// Parses a filter expression into a predicate that can be passed to `LazyFrame::filter`.
+pub fn parse_filter(source: &str) -> Result<Expr> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let operand = parser.parse_or()?;
+    if let Some(token) = parser.peek() {
+        bail!("Unexpected {} in filter expression", token.describe());
+    }
+    match operand {
+        Operand::Expr(expr) => Ok(expr),
+        Operand::Literal(Value::Bool(b)) => Ok(lit(b)),
+        _ => bail!("Filter expression must evaluate to true or false"),
+    }
+}
+
+/// A lexical token of the filter language.
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Num(f64),
+    Op(&'static str),
+    LParen,
+    RParen,
+}
+
+impl Token {
+    /// Describes the token for error messages.
+    fn describe(&self) -> String {
+        match self {
+            Token::Ident(name) => format!("'{}'", name),
+            Token::Str(s) => format!("string \"{}\"", s),
+            Token::Num(n) => format!("number {}", n),
+            Token::Op(op) => format!("'{}'", op),
+            Token::LParen => "'('".to_string(),
+            Token::RParen => "')'".to_string(),
+        }
+    }
+}
+
+/// Splits the source into tokens.
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    // Longer operators first, so "<=" isn't read as "<" followed by "=".
+    const OPERATORS: [&str; 9] = ["&&", "||", "==", "!=", "<=", ">=", "<", ">", "!"];
+
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '(' {
+            chars.next();
+            tokens.push(Token::LParen);
+        } else if c == ')' {
+            chars.next();
+            tokens.push(Token::RParen);
+        } else if c == '"' {
+            chars.next();
+            let mut value = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '"')) => break,
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, escaped)) => value.push(escaped),
+                        None => bail!("Unterminated string in filter expression"),
+                    },
+                    Some((_, ch)) => value.push(ch),
+                    None => bail!("Unterminated string in filter expression"),
+                }
+            }
+            tokens.push(Token::Str(value));
+        } else if c.is_ascii_digit() || c == '-' || c == '.' {
+            let mut end = start;
+            while let Some(&(i, ch)) = chars.peek() {
+                let is_sign =
+                    (ch == '-' || ch == '+') && (i == start || source[..i].ends_with(['e', 'E']));
+                if ch.is_ascii_digit() || ch == '.' || ch == 'e' || ch == 'E' || is_sign {
+                    end = i + ch.len_utf8();
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            let text = &source[start..end];
+            match text.parse() {
+                Ok(n) => tokens.push(Token::Num(n)),
+                Err(_) => bail!("Invalid number '{}' in filter expression", text),
+            }
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = start;
+            while let Some(&(i, ch)) = chars.peek() {
+                if ch.is_alphanumeric() || ch == '_' {
+                    end = i + ch.len_utf8();
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            tokens.push(Token::Ident(source[start..end].to_string()));
+        } else if let Some(op) = OPERATORS
+            .iter()
+            .find(|op| source[start..].starts_with(**op))
+        {
+            for _ in 0..op.len() {
+                chars.next();
+            }
+            tokens.push(Token::Op(op));
+        } else {
+            bail!("Unexpected character '{}' in filter expression", c);
+        }
+    }
+    Ok(tokens)
+}
+
+/// A literal value in the filter language.
+#[derive(Debug, Clone)]
+enum Value {
+    Str(String),
+    Num(f64),
+    Bool(bool),
+    Null,
+}
+
+/// The result of parsing part of a filter: either a column expression or a bare literal.
+///
+/// Literals are kept separate so that comparisons with `null` can become null checks.
+#[derive(Debug, Clone)]
+enum Operand {
+    Expr(Expr),
+    Literal(Value),
+}
+
+impl Operand {
+    /// Converts the operand into a Polars expression.
+    fn into_expr(self) -> Expr {
+        match self {
+            Operand::Expr(expr) => expr,
+            Operand::Literal(Value::Str(s)) => lit(s),
+            Operand::Literal(Value::Num(n)) => lit(n),
+            Operand::Literal(Value::Bool(b)) => lit(b),
+            Operand::Literal(Value::Null) => lit(NULL),
+        }
+    }
+}
+
+/// A recursive-descent parser over the token stream.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// Consumes the next token if it is the given operator.
+    fn eat_op(&mut self, op: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Op(o)) if *o == op) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consumes the next token, failing unless it is the expected one.
+    fn expect(&mut self, expected: Token) -> Result<()> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => bail!(
+                "Expected {} but found {} in filter expression",
+                expected.describe(),
+                token.describe()
+            ),
+            None => bail!(
+                "Expected {} but the filter expression ended",
+                expected.describe()
+            ),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Operand> {
+        let mut left = self.parse_and()?;
+        while self.eat_op("||") {
+            let right = self.parse_and()?;
+            left = Operand::Expr(left.into_expr().or(right.into_expr()));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Operand> {
+        let mut left = self.parse_unary()?;
+        while self.eat_op("&&") {
+            let right = self.parse_unary()?;
+            left = Operand::Expr(left.into_expr().and(right.into_expr()));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Operand> {
+        if self.eat_op("!") {
+            let operand = self.parse_unary()?;
+            return Ok(Operand::Expr(operand.into_expr().not()));
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<Operand> {
+        let left = self.parse_operand()?;
+        let op = match self.peek() {
+            Some(Token::Op(op)) if ["==", "!=", "<", "<=", ">", ">="].contains(op) => *op,
+            _ => return Ok(left),
+        };
+        self.pos += 1;
+        let right = self.parse_operand()?;
+
+        // `== null` and `!= null` test for missing values, as SQL's `IS NULL` would.
+        let expr = match (left, right, op) {
+            (Operand::Literal(Value::Null), other, "==")
+            | (other, Operand::Literal(Value::Null), "==") => other.into_expr().is_null(),
+            (Operand::Literal(Value::Null), other, "!=")
+            | (other, Operand::Literal(Value::Null), "!=") => other.into_expr().is_not_null(),
+            (left, right, op) => {
+                let (left, right) = (left.into_expr(), right.into_expr());
+                match op {
+                    "==" => left.eq(right),
+                    "!=" => left.neq(right),
+                    "<" => left.lt(right),
+                    "<=" => left.lt_eq(right),
+                    ">" => left.gt(right),
+                    _ => left.gt_eq(right),
+                }
+            }
+        };
+        Ok(Operand::Expr(expr))
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand> {
+        match self.next() {
+            Some(Token::Num(n)) => Ok(Operand::Literal(Value::Num(n))),
+            Some(Token::Str(s)) => Ok(Operand::Literal(Value::Str(s))),
+            Some(Token::LParen) => {
+                let inner = self.parse_or()?;
+                self.expect(Token::RParen)?;
+                Ok(inner)
+            }
+            Some(Token::Ident(name)) => match name.as_str() {
+                "true" => Ok(Operand::Literal(Value::Bool(true))),
+                "false" => Ok(Operand::Literal(Value::Bool(false))),
+                "null" => Ok(Operand::Literal(Value::Null)),
+                "col" => {
+                    self.expect(Token::LParen)?;
+                    let column = match self.next() {
+                        Some(Token::Str(column)) => column,
+                        _ => bail!("col() expects a quoted column name, e.g. col(\"Amount\")"),
+                    };
+                    self.expect(Token::RParen)?;
+                    Ok(Operand::Expr(col(column.as_str())))
+                }
+                _ => bail!(
+                    "Unknown identifier '{}' in filter expression; use col(\"name\") to refer to columns",
+                    name
+                ),
+            },
+            Some(token) => bail!("Unexpected {} in filter expression", token.describe()),
+            None => bail!("The filter expression ended unexpectedly"),
+        }
+    }
+}
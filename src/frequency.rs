@@ -0,0 +1,82 @@
+//! Frequency tables of the most common values in a column.
+
+use anyhow::Result;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::collect;
+use crate::stats::any_value_to_string;
+
+/// The most frequent values of a column with their counts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrequencyTable {
+    /// The name of the column that was counted.
+    pub column: String,
+    /// The total number of rows, including nulls.
+    pub total: usize,
+    /// The number of distinct values, counting null as a value.
+    pub distinct: usize,
+    /// The most frequent values, in descending order of count.
+    pub values: Vec<ValueCount>,
+}
+
+/// A single value and how often it occurs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValueCount {
+    /// The value, or `None` for nulls.
+    pub value: Option<String>,
+    /// The number of rows holding the value.
+    pub count: usize,
+    /// The share of all rows holding the value, as a percentage.
+    pub percent: f64,
+}
+
+/// Counts the values of a column and returns the `top_n` most frequent ones.
+///
+/// Works on columns of any type, so categorical columns can be summarized too.
+/// Ties are broken by value so the output is stable between runs.
+pub fn compute_frequencies(
+    lf: LazyFrame,
+    column_name: &str,
+    top_n: usize,
+) -> Result<FrequencyTable> {
+    let counts = lf.group_by([col(column_name)]).agg([len().alias("count")]);
+
+    // The totals need every group, the table only the top ones.
+    let totals_df = collect(
+        counts
+            .clone()
+            .select([col("count").sum().alias("total"), len().alias("distinct")]),
+    )?;
+    let total = totals_df.column("total")?.get(0)?.try_extract::<u64>()? as usize;
+    let distinct = totals_df.column("distinct")?.get(0)?.try_extract::<u32>()? as usize;
+
+    let top_df = collect(
+        counts
+            .sort(
+                ["count", column_name],
+                SortMultipleOptions::default().with_order_descending_multi([true, false]),
+            )
+            .limit(top_n as IdxSize),
+    )?;
+
+    let value_column = top_df.column(column_name)?;
+    let count_column = top_df.column("count")?;
+    let values = (0..top_df.height())
+        .map(|row| {
+            let count = count_column.get(row)?.try_extract::<u32>()? as usize;
+            Ok(ValueCount {
+                value: any_value_to_string(value_column.get(row)?),
+                count,
+                percent: count as f64 * 100.0 / total as f64,
+            })
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(FrequencyTable {
+        column: column_name.to_string(),
+        total,
+        distinct,
+        values,
+    })
+}
@@ -0,0 +1,142 @@
+//! Binning of a numeric column into a histogram.
+
+use anyhow::{Result, bail};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::collect;
+use crate::stats::NumericParsing;
+
+/// A histogram of a column's values split into equal-width bins.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Histogram {
+    /// The name of the column that was binned.
+    pub column: String,
+    /// The bins in ascending order. Each bin includes its lower bound; the last bin
+    /// also includes its upper bound.
+    pub bins: Vec<Bin>,
+    /// The number of non-null values that fell outside the histogram's range.
+    pub out_of_range: usize,
+}
+
+/// A single histogram bin.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Bin {
+    /// The inclusive lower bound of the bin.
+    pub lower: f64,
+    /// The upper bound of the bin, exclusive except for the last bin.
+    pub upper: f64,
+    /// The number of values in the bin.
+    pub count: usize,
+}
+
+/// Options controlling how a column is binned.
+#[derive(Debug, Clone)]
+pub struct HistogramOptions {
+    /// The number of equal-width bins.
+    pub bins: usize,
+    /// The lower bound of the histogram, defaulting to the column's minimum.
+    pub min: Option<f64>,
+    /// The upper bound of the histogram, defaulting to the column's maximum.
+    pub max: Option<f64>,
+    /// How text values are cleaned before being parsed as numbers.
+    pub parsing: NumericParsing,
+}
+
+impl Default for HistogramOptions {
+    fn default() -> Self {
+        Self {
+            bins: 10,
+            min: None,
+            max: None,
+            parsing: NumericParsing::default(),
+        }
+    }
+}
+
+/// Bins the values of a column into a histogram.
+///
+/// This takes two passes over the data: one to find the range (and the number of
+/// values, so out-of-range values can be reported) and one to count the values per bin.
+pub fn compute_histogram(
+    lf: LazyFrame,
+    column_name: &str,
+    options: &HistogramOptions,
+) -> Result<Histogram> {
+    if options.bins == 0 {
+        bail!("The number of bins must be at least 1");
+    }
+    options.parsing.check_strict(&lf, column_name)?;
+    let values = options.parsing.to_float(column_name);
+
+    // First pass: the column's range and its number of non-null values.
+    let range_df = collect(lf.clone().select([
+        values.clone().min().alias("min"),
+        values.clone().max().alias("max"),
+        values.clone().count().alias("count"),
+    ]))?;
+    let get_f64 = |name: &str| -> Result<Option<f64>> {
+        match range_df.column(name)?.get(0)? {
+            AnyValue::Null => Ok(None),
+            av => Ok(Some(av.try_extract()?)),
+        }
+    };
+    let total = range_df.column("count")?.get(0)?.try_extract::<u32>()? as usize;
+
+    let (Some(min), Some(max)) = (
+        options.min.or(get_f64("min")?),
+        options.max.or(get_f64("max")?),
+    ) else {
+        bail!("Column '{}' has no numeric values to bin", column_name);
+    };
+    if min > max {
+        bail!("Histogram minimum {} is greater than maximum {}", min, max);
+    }
+
+    // A column with a single distinct value gets one bin of width 1 holding everything.
+    let width = if max > min {
+        (max - min) / options.bins as f64
+    } else {
+        1.0
+    };
+    let last_bin = options.bins as i64 - 1;
+
+    // Second pass: count the in-range values per bin. Values are never below `min`, so
+    // truncating the cast acts as `floor`; the maximum itself belongs in the last bin.
+    let bin_index = ((col("value") - lit(min)) / lit(width)).cast(DataType::Int64);
+    let counts_df = collect(
+        lf.select([values.alias("value")])
+            .filter(
+                col("value")
+                    .gt_eq(lit(min))
+                    .and(col("value").lt_eq(lit(max))),
+            )
+            .select([when(bin_index.clone().gt(lit(last_bin)))
+                .then(lit(last_bin))
+                .otherwise(bin_index)
+                .alias("bin")])
+            .group_by([col("bin")])
+            .agg([len().alias("count")]),
+    )?;
+
+    let mut bins: Vec<Bin> = (0..options.bins)
+        .map(|i| Bin {
+            lower: min + width * i as f64,
+            upper: min + width * (i + 1) as f64,
+            count: 0,
+        })
+        .collect();
+    let bin_column = counts_df.column("bin")?;
+    let count_column = counts_df.column("count")?;
+    for row in 0..counts_df.height() {
+        let bin = bin_column.get(row)?.try_extract::<i64>()? as usize;
+        bins[bin].count = count_column.get(row)?.try_extract::<u32>()? as usize;
+    }
+
+    let in_range: usize = bins.iter().map(|b| b.count).sum();
+    Ok(Histogram {
+        column: column_name.to_string(),
+        bins,
+        out_of_range: total - in_range,
+    })
+}
@@ -0,0 +1,885 @@
+//! Construction of the LazyFrame that every command reads its data from.
+
+use std::fs::File;
+use std::hash::{BuildHasher, RandomState};
+use std::io::{self, BufReader, Cursor, Read, Seek};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result, bail};
+use bzip2::read::MultiBzDecoder;
+use flate2::read::MultiGzDecoder;
+use polars::io::HiveOptions;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::avro::read_avro;
+use crate::delta::{is_delta_table, scan_delta};
+use crate::engine::ObservedReader;
+use crate::excel::read_xlsx;
+use crate::filter::parse_filter;
+use crate::schema::{DtypeOverride, read_schema_file};
+
+/// The supported input file formats.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputFormat {
+    /// Comma-separated values with a header row.
+    Csv,
+    /// Apache Parquet.
+    Parquet,
+    /// An Excel workbook (.xlsx), of which one sheet is read.
+    Xlsx,
+    /// Newline-delimited JSON (.ndjson or .jsonl), with an object per line.
+    Ndjson,
+    /// Arrow IPC, also known as Feather (.arrow, .ipc or .feather).
+    Ipc,
+    /// An Avro object container file (.avro), such as a Kafka topic dump.
+    Avro,
+    /// A Delta Lake table: a directory of Parquet files with a `_delta_log`.
+    Delta,
+}
+
+/// Describes where and how to read the input data.
+#[derive(Debug, Clone, Default)]
+pub struct InputOptions {
+    /// Paths or glob patterns of the files to read. Empty or `-` reads from stdin.
+    /// Cloud storage URIs (s3://, gs://, az://, ...) are scanned in place, and HTTP(S)
+    /// URLs are downloaded.
+    pub paths: Vec<String>,
+    /// The format of the input, detected from the file extension when `None`.
+    pub format: Option<InputFormat>,
+    /// The CSV field delimiter, a comma unless tab-separated input is detected.
+    pub delimiter: Option<u8>,
+    /// Whether CSV input is tab-separated.
+    pub tsv: bool,
+    /// Whether CSV input lacks a header row. Its columns are then named `column_1`,
+    /// `column_2` and so on.
+    pub no_header: bool,
+    /// Strings that the CSV reader treats as missing values, e.g. "NA" or "NULL".
+    pub null_values: Vec<String>,
+    /// A filter expression (see [`crate::filter`]) selecting the rows to analyze.
+    pub filter: Option<String>,
+    /// Column types to use instead of the inferred ones.
+    pub dtypes: Vec<DtypeOverride>,
+    /// A JSON file of column types (see [`crate::schema::read_schema_file`]).
+    /// Types in `dtypes` take precedence over the file's.
+    pub schema_file: Option<String>,
+    /// The sheet of an Excel workbook to read, by name or position counting from 1.
+    /// Defaults to the first sheet.
+    pub sheet: Option<String>,
+    /// Headers to send when downloading HTTP(S) input, such as `Authorization`.
+    pub http_headers: Vec<(String, String)>,
+    /// The version of a Delta Lake table to read, or the latest when `None`.
+    pub delta_version: Option<u64>,
+    /// Analyze a random sample of the (filtered) rows instead of all of them.
+    pub sample: Option<Sample>,
+    /// The seed of the sampling, so every run samples the same rows. When `None`, a
+    /// random seed is used, the same one for the whole run.
+    pub seed: Option<u64>,
+}
+
+/// The size of a random sample of the rows.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Sample {
+    /// At most this many rows; every row when there are fewer.
+    Rows(usize),
+    /// This fraction of the rows, between 0 and 1.
+    Fraction(f64),
+}
+
+/// A random sample of the rows of each group, so that large groups don't dominate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupSample {
+    /// The column whose distinct values form the groups.
+    pub column: String,
+    /// At most this many rows of every group, or with `proportional` this many rows of
+    /// the average group.
+    pub rows: usize,
+    /// Take the same fraction of every group instead of the same number of rows, keeping
+    /// the groups' proportions.
+    pub proportional: bool,
+}
+
+/// How the analyzed rows were sampled, recorded so the sample can be drawn again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sampling {
+    /// The sample of all the rows, if any.
+    #[serde(flatten, default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<Sample>,
+    /// The sample of each group, taken after the sample of all the rows, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub per_group: Option<GroupSample>,
+    /// The seed to pass to `--seed` to sample the same rows.
+    pub seed: u64,
+}
+
+impl std::fmt::Display for Sampling {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+        match self.size {
+            Some(Sample::Rows(rows)) => parts.push(format!("up to {} random rows", rows)),
+            Some(Sample::Fraction(fraction)) => {
+                parts.push(format!("{}% of the rows", fraction * 100.0))
+            }
+            None => {}
+        }
+        match &self.per_group {
+            Some(group) if group.proportional => parts.push(format!(
+                "{} random rows per '{}' group on average, in proportion to their sizes",
+                group.rows, group.column
+            )),
+            Some(group) => parts.push(format!(
+                "up to {} random rows per '{}' group",
+                group.rows, group.column
+            )),
+            None => {}
+        }
+        write!(f, "{}, seed {}", parts.join(", then "), self.seed)
+    }
+}
+
+impl InputOptions {
+    /// Creates options for reading a single file with its format detected from the extension.
+    pub fn from_path(path: impl Into<String>) -> Self {
+        Self {
+            paths: vec![path.into()],
+            ..Default::default()
+        }
+    }
+
+    /// Returns the seed of any sampling: the given one, or else a random seed that stays
+    /// the same for the whole run.
+    pub fn seed(&self) -> u64 {
+        static RANDOM_SEED: OnceLock<u64> = OnceLock::new();
+        self.seed
+            .unwrap_or_else(|| *RANDOM_SEED.get_or_init(|| RandomState::new().hash_one(0u8)))
+    }
+
+    /// Returns how the rows are sampled, with the seed resolved, or `None` when every
+    /// row is analyzed.
+    pub fn sampling(&self) -> Option<Sampling> {
+        self.sample.map(|size| Sampling {
+            size: Some(size),
+            per_group: None,
+            seed: self.seed(),
+        })
+    }
+}
+
+/// Creates a LazyFrame from the input files, or from stdin when the path is `-` or missing.
+///
+/// Glob patterns are expanded and every matching file is scanned into a single LazyFrame,
+/// so the statistics are calculated across all of them combined. For files this does not
+/// read anything yet beyond what is needed to infer each file's schema.
+///
+/// Any type overrides are applied first, and any filter and sampling are applied to the
+/// combined data, so every command only sees the matching, sampled rows.
+pub fn scan(input: &InputOptions) -> Result<LazyFrame> {
+    scan_with(input, |schema_overwrite| {
+        scan_sources(input, schema_overwrite)
+    })
+}
+
+/// Reads data that is already in memory, such as an upload, like [`scan`] reads stdin:
+/// decompressing it if needed and parsing it in the input's format, before applying the
+/// type overrides and filter.
+pub fn scan_bytes(input: &InputOptions, buf: Vec<u8>) -> Result<LazyFrame> {
+    scan_with(input, |schema_overwrite| {
+        scan_stream(
+            buf.as_slice(),
+            Some(buf.len() as u64),
+            input_format(input, None),
+            csv_settings(input, None, schema_overwrite),
+            input.sheet.as_deref(),
+        )
+    })
+}
+
+/// Reads the data with `read`, given the column types to read, then applies the type
+/// overrides and filter.
+fn scan_with(
+    input: &InputOptions,
+    read: impl FnOnce(Option<SchemaRef>) -> Result<LazyFrame>,
+) -> Result<LazyFrame> {
+    // Parse the filter first so a typo fails before any data is read.
+    let predicate = input.filter.as_deref().map(parse_filter).transpose()?;
+    let dtypes = dtype_overrides(input)?;
+    let schema_overwrite = (!dtypes.is_empty()).then(|| {
+        let fields = dtypes
+            .iter()
+            .map(|d| Field::new(d.column.as_str().into(), d.read_dtype()));
+        Arc::new(Schema::from_iter(fields))
+    });
+
+    let mut lf = read(schema_overwrite)?;
+    if !dtypes.is_empty() {
+        let schema = lf.collect_schema()?;
+        let mut conversions = Vec::with_capacity(dtypes.len());
+        for dtype in &dtypes {
+            let Some(scanned) = schema.get(dtype.column.as_str()) else {
+                bail!(
+                    "Cannot override the type of '{}': no such column in {}",
+                    dtype.column,
+                    input_name(input)
+                );
+            };
+            conversions.push(dtype.convert(scanned));
+        }
+        lf = lf.with_columns(conversions);
+    }
+    if let Some(predicate) = predicate {
+        lf = lf.filter(predicate);
+    }
+    match input.sample {
+        Some(sample) => sample_rows(lf, sample, input.seed()),
+        None => Ok(lf),
+    }
+}
+
+/// Keeps a random sample of the rows, without replacement and in their original order.
+fn sample_rows(lf: LazyFrame, sample: Sample, seed: u64) -> Result<LazyFrame> {
+    const ROW: &str = "__sample_row";
+    // Every column is sampled separately, so they must share the seed to keep the same rows.
+    let seed = Some(seed);
+    let sampled = match sample {
+        // Taking the head of the shuffled rows allows asking for more rows than there are,
+        // without knowing the number of rows up front.
+        Sample::Rows(rows) => all().as_expr().shuffle(seed).head(Some(rows)),
+        Sample::Fraction(fraction) => {
+            if !(fraction > 0.0 && fraction <= 1.0) {
+                bail!(
+                    "The sample fraction must be between 0 and 1, got {}",
+                    fraction
+                );
+            }
+            all()
+                .as_expr()
+                .sample_frac(lit(fraction), false, false, seed)
+        }
+    };
+    Ok(lf
+        .with_row_index(ROW, None)
+        .select([sampled])
+        .sort([ROW], SortMultipleOptions::default())
+        .drop(cols([ROW])))
+}
+
+/// Keeps a random sample of the rows of each group, without replacement and in their
+/// original order.
+pub fn sample_groups(lf: LazyFrame, sample: &GroupSample, seed: u64) -> LazyFrame {
+    const ROW: &str = "__sample_row";
+    let group = [col(sample.column.as_str())];
+    // Ranking a random permutation of the row numbers within each group orders each
+    // group's rows randomly, so its first rows in that order are a random sample.
+    let rank = col(ROW)
+        .shuffle(Some(seed))
+        .rank(
+            RankOptions {
+                method: RankMethod::Ordinal,
+                descending: false,
+            },
+            None,
+        )
+        .over(group.clone())
+        .cast(DataType::Float64);
+    let size = if sample.proportional {
+        // The same fraction of every group, sized to take `rows` rows of the average group.
+        let groups = col(sample.column.as_str())
+            .n_unique()
+            .cast(DataType::Float64);
+        let fraction = lit(sample.rows as f64) * groups / len().cast(DataType::Float64);
+        (fraction * len().over(group).cast(DataType::Float64)).ceil()
+    } else {
+        lit(sample.rows as f64)
+    };
+    lf.with_row_index(ROW, None)
+        .filter(rank.lt_eq(size))
+        .drop(cols([ROW]))
+}
+
+/// Collects the type overrides from the schema file and the individual overrides,
+/// keeping only the last one given for each column.
+fn dtype_overrides(input: &InputOptions) -> Result<Vec<DtypeOverride>> {
+    let mut dtypes = match &input.schema_file {
+        Some(path) => read_schema_file(path)?,
+        None => Vec::new(),
+    };
+    for dtype in &input.dtypes {
+        dtypes.retain(|d| d.column != dtype.column);
+        dtypes.push(dtype.clone());
+    }
+    Ok(dtypes)
+}
+
+/// Scans the input files, or stdin, into a single LazyFrame.
+///
+/// `schema_overwrite` sets the types the CSV reader uses for some columns instead of
+/// inferring them.
+fn scan_sources(input: &InputOptions, schema_overwrite: Option<SchemaRef>) -> Result<LazyFrame> {
+    let stdin = || {
+        read_stdin(
+            input_format(input, None),
+            csv_settings(input, None, schema_overwrite.clone()),
+            input.sheet.as_deref(),
+        )
+    };
+    let paths = match input.paths.as_slice() {
+        [] => return stdin(),
+        [path] if path == "-" => return stdin(),
+        paths => expand_paths(paths)?,
+    };
+
+    let mut frames = Vec::with_capacity(paths.len());
+    let mut first_schema: Option<(&str, SchemaRef)> = None;
+    for path in &paths {
+        let format = input_format(input, Some(path));
+        let csv = csv_settings(input, Some(path), schema_overwrite.clone());
+        let mut lf = if is_http_url(path) {
+            // The compression is detected from the data, like for stdin, as URLs often
+            // don't end with the file's extension.
+            let response = download(path, &input.http_headers)?;
+            let size = response.content_length();
+            scan_stream(response, size, format, csv, input.sheet.as_deref())
+                .with_context(|| format!("Failed to read '{}'", path))?
+        } else {
+            scan_path(
+                path,
+                format,
+                csv,
+                input.sheet.as_deref(),
+                input.delta_version,
+            )?
+        };
+        let schema = lf.collect_schema()?;
+        match &first_schema {
+            Some((first_path, expected)) => check_schema(path, &schema, first_path, expected)?,
+            None => first_schema = Some((path, schema)),
+        }
+        frames.push(lf);
+    }
+
+    if frames.len() == 1 {
+        return Ok(frames.remove(0));
+    }
+
+    // Column types inferred per file may differ (e.g. a column that happens to hold only
+    // integers in one file), so combine them using their common supertypes.
+    let args = UnionArgs {
+        to_supertypes: true,
+        ..Default::default()
+    };
+    Ok(concat(frames, args)?)
+}
+
+/// Returns a human-readable name for the input, for use in messages.
+pub fn input_name(input: &InputOptions) -> String {
+    match input.paths.as_slice() {
+        [] => "<stdin>".to_string(),
+        [path] if path == "-" => "<stdin>".to_string(),
+        paths => paths.join(", "),
+    }
+}
+
+/// Splits the input into one set of options per file, expanding glob patterns. Stdin
+/// stays a single input.
+pub fn per_file(input: &InputOptions) -> Result<Vec<InputOptions>> {
+    if input.paths.is_empty() || input.paths == ["-"] {
+        return Ok(vec![input.clone()]);
+    }
+    Ok(expand_paths(&input.paths)?
+        .into_iter()
+        .map(|path| InputOptions {
+            paths: vec![path],
+            ..input.clone()
+        })
+        .collect())
+}
+
+/// Returns the total size of the input files in bytes, or `None` when reading from stdin
+/// or cloud storage.
+pub fn input_size(input: &InputOptions) -> Result<Option<u64>> {
+    if input.paths.is_empty() || input.paths == ["-"] {
+        return Ok(None);
+    }
+    let mut size = 0;
+    for path in expand_paths(&input.paths)? {
+        if is_cloud_url(&path) {
+            return Ok(None);
+        }
+        let files = if Path::new(&path).is_dir() {
+            data_files(Path::new(&path))?
+        } else {
+            vec![PathBuf::from(&path)]
+        };
+        for file in files {
+            size += std::fs::metadata(&file)
+                .with_context(|| format!("Failed to read '{}'", file.display()))?
+                .len();
+        }
+    }
+    Ok(Some(size))
+}
+
+/// Expands any glob patterns in the given paths, keeping plain paths as they are.
+///
+/// Cloud storage URIs are kept as they are too, as Polars expands their patterns itself
+/// when scanning.
+pub(crate) fn expand_paths(paths: &[String]) -> Result<Vec<String>> {
+    let mut expanded = Vec::new();
+    for path in paths {
+        if path == "-" {
+            bail!("Reading from stdin ('-') cannot be combined with other input files");
+        }
+        if is_cloud_url(path) || !path.contains(['*', '?', '[']) {
+            expanded.push(path.clone());
+            continue;
+        }
+
+        let start = expanded.len();
+        for entry in glob::glob(path)? {
+            expanded.push(entry?.to_string_lossy().into_owned());
+        }
+        if expanded.len() == start {
+            bail!("No files match the pattern '{}'", path);
+        }
+    }
+    Ok(expanded)
+}
+
+/// Ensures a file has the same columns, in the same order, as the first input file.
+fn check_schema(path: &str, schema: &Schema, first_path: &str, expected: &Schema) -> Result<()> {
+    let names =
+        |schema: &Schema| -> Vec<String> { schema.iter_names().map(|n| n.to_string()).collect() };
+    let (found, wanted) = (names(schema), names(expected));
+    if found == wanted {
+        return Ok(());
+    }
+
+    let missing: Vec<_> = wanted.iter().filter(|n| !found.contains(n)).collect();
+    let unexpected: Vec<_> = found.iter().filter(|n| !wanted.contains(n)).collect();
+    let mut details = Vec::new();
+    if !missing.is_empty() {
+        details.push(format!("missing columns {:?}", missing));
+    }
+    if !unexpected.is_empty() {
+        details.push(format!("unexpected columns {:?}", unexpected));
+    }
+    if details.is_empty() {
+        details.push(format!("columns are ordered {:?}", found));
+    }
+    bail!(
+        "Schema of '{}' does not match '{}': {}",
+        path,
+        first_path,
+        details.join(", ")
+    );
+}
+
+/// Resolves the input format, preferring an explicit `--format` over the file extension.
+pub(crate) fn input_format(input: &InputOptions, path: Option<&str>) -> InputFormat {
+    if let Some(format) = input.format {
+        return format;
+    }
+    if path.is_some_and(is_delta_table) {
+        return InputFormat::Delta;
+    }
+    // The files of a partitioned directory share a format, so the first one tells it.
+    let first_file = path
+        .filter(|path| Path::new(path).is_dir())
+        .and_then(|dir| data_files(Path::new(dir)).ok())
+        .and_then(|files| files.into_iter().next())
+        .map(|file| file.to_string_lossy().into_owned());
+    let path = first_file.as_deref().or(path);
+    let extension = path
+        .and_then(|path| Path::new(strip_compression(path)).extension())
+        .and_then(|ext| ext.to_str());
+    match extension {
+        Some(ext) if ext.eq_ignore_ascii_case("parquet") => InputFormat::Parquet,
+        Some(ext) if ext.eq_ignore_ascii_case("xlsx") => InputFormat::Xlsx,
+        Some(ext) if ext.eq_ignore_ascii_case("avro") => InputFormat::Avro,
+        Some(ext) if ext.eq_ignore_ascii_case("ndjson") || ext.eq_ignore_ascii_case("jsonl") => {
+            InputFormat::Ndjson
+        }
+        Some(ext)
+            if ["arrow", "ipc", "feather"]
+                .iter()
+                .any(|e| ext.eq_ignore_ascii_case(e)) =>
+        {
+            InputFormat::Ipc
+        }
+        _ => InputFormat::Csv,
+    }
+}
+
+/// Lists the data files in a directory and its subdirectories, in order, skipping hidden
+/// and metadata files such as `.part-0.crc` or `_SUCCESS`.
+fn data_files(directory: &Path) -> Result<Vec<PathBuf>> {
+    let mut entries = std::fs::read_dir(directory)
+        .with_context(|| format!("Failed to read '{}'", directory.display()))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let name = entry
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+        if name.starts_with(['.', '_']) {
+            continue;
+        }
+        if entry.is_dir() {
+            files.extend(data_files(&entry)?);
+        } else {
+            files.push(entry);
+        }
+    }
+    Ok(files)
+}
+
+/// Resolves the CSV field delimiter from `--delimiter`, `--tsv`, or a `.tsv` extension.
+pub(crate) fn separator(input: &InputOptions, path: Option<&str>) -> u8 {
+    if let Some(delimiter) = input.delimiter {
+        return delimiter;
+    }
+    let is_tsv_file = path
+        .and_then(|path| Path::new(strip_compression(path)).extension())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("tsv"));
+    if input.tsv || is_tsv_file {
+        b'\t'
+    } else {
+        b','
+    }
+}
+
+/// How a CSV file is read.
+#[derive(Clone)]
+struct CsvSettings {
+    /// The delimiter and null tokens.
+    parse_options: CsvParseOptions,
+    /// Whether the first row holds the column names.
+    has_header: bool,
+    /// The types to read some columns as instead of inferring them.
+    schema_overwrite: Option<SchemaRef>,
+}
+
+/// Builds the CSV settings for an input file.
+fn csv_settings(
+    input: &InputOptions,
+    path: Option<&str>,
+    schema_overwrite: Option<SchemaRef>,
+) -> CsvSettings {
+    CsvSettings {
+        parse_options: csv_parse_options(input, path),
+        has_header: !input.no_header,
+        schema_overwrite,
+    }
+}
+
+/// Builds the CSV parsing options (delimiter and null tokens) for an input file.
+fn csv_parse_options(input: &InputOptions, path: Option<&str>) -> CsvParseOptions {
+    let null_values = (!input.null_values.is_empty()).then(|| {
+        NullValues::AllColumns(
+            input
+                .null_values
+                .iter()
+                .map(|v| v.as_str().into())
+                .collect(),
+        )
+    });
+    CsvParseOptions::default()
+        .with_separator(separator(input, path))
+        .with_null_values(null_values)
+}
+
+/// The compression formats that are transparently decompressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compression {
+    Gzip,
+    Zstd,
+    Bzip2,
+}
+
+impl Compression {
+    /// Detects the compression from a path's `.gz`, `.zst` or `.bz2` extension.
+    fn from_path(path: &str) -> Option<Self> {
+        let extension = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "gz" => Some(Compression::Gzip),
+            "zst" => Some(Compression::Zstd),
+            "bz2" => Some(Compression::Bzip2),
+            _ => None,
+        }
+    }
+
+    /// Detects the compression from the magic bytes at the start of the data.
+    fn sniff(data: &[u8]) -> Option<Self> {
+        if data.starts_with(&[0x1f, 0x8b]) {
+            Some(Compression::Gzip)
+        } else if data.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Compression::Zstd)
+        } else if data.starts_with(b"BZh") {
+            Some(Compression::Bzip2)
+        } else {
+            None
+        }
+    }
+
+    /// Wraps the reader in a decoder that decompresses it while reading.
+    fn decoder<'a>(self, reader: impl Read + 'a) -> Result<Box<dyn Read + 'a>> {
+        Ok(match self {
+            Compression::Gzip => Box::new(MultiGzDecoder::new(reader)),
+            Compression::Zstd => Box::new(zstd::Decoder::new(reader)?),
+            Compression::Bzip2 => Box::new(MultiBzDecoder::new(reader)),
+        })
+    }
+
+    /// Detects the compression from the magic bytes at the start of a stream, returning
+    /// it with a reader that still starts with those bytes.
+    fn sniff_stream<'a>(mut reader: impl Read + 'a) -> Result<(Option<Self>, Box<dyn Read + 'a>)> {
+        let mut head = Vec::with_capacity(4);
+        reader.by_ref().take(4).read_to_end(&mut head)?;
+        let compression = Self::sniff(&head);
+        Ok((compression, Box::new(Cursor::new(head).chain(reader))))
+    }
+}
+
+/// Copies a stream of the given size, if known, into an unnamed temporary file,
+/// decompressing it on the way, so it can be scanned lazily like a file on disk without
+/// holding it in memory. The file is removed once the last handle to it is closed.
+///
+/// The bytes read are reported to the query observer, to show the copy's progress.
+fn spool(reader: impl Read, size: Option<u64>, compression: Option<Compression>) -> Result<File> {
+    let reader = ObservedReader::new(reader, size);
+    let mut file = tempfile::tempfile().context("Failed to create a temporary file")?;
+    match compression {
+        Some(compression) => io::copy(&mut compression.decoder(reader)?, &mut file)
+            .context("Failed to decompress the data")?,
+        None => io::copy(&mut BufReader::new(reader), &mut file)?,
+    };
+    file.rewind()?;
+    Ok(file)
+}
+
+/// Scans a stream of the given size, if known, such as stdin or a download, through a
+/// temporary file, decompressing it if it is compressed.
+fn scan_stream(
+    reader: impl Read,
+    size: Option<u64>,
+    format: InputFormat,
+    csv: CsvSettings,
+    sheet: Option<&str>,
+) -> Result<LazyFrame> {
+    let (compression, reader) = Compression::sniff_stream(reader)?;
+    let file = spool(reader, size, compression)?;
+    scan_sources_as(ScanSources::Files([file].into()), format, csv, sheet)
+}
+
+/// Whether the path is a cloud storage URI, such as `s3://bucket/data.parquet`, or an
+/// HTTP(S) URL rather than a local file.
+pub(crate) fn is_cloud_url(path: &str) -> bool {
+    PlPath::new(path).is_cloud_url()
+}
+
+/// Whether the path is an HTTP(S) URL.
+fn is_http_url(path: &str) -> bool {
+    let scheme = path.split_once("://").map(|(scheme, _)| scheme);
+    scheme.is_some_and(|s| s.eq_ignore_ascii_case("http") || s.eq_ignore_ascii_case("https"))
+}
+
+/// Requests the file at an HTTP(S) URL, sending the given headers, and returns the
+/// response to read its body from.
+fn download(url: &str, headers: &[(String, String)]) -> Result<reqwest::blocking::Response> {
+    let client = reqwest::blocking::Client::builder()
+        .user_agent(concat!(
+            env!("CARGO_PKG_NAME"),
+            "/",
+            env!("CARGO_PKG_VERSION")
+        ))
+        .build()?;
+    let mut request = client.get(url);
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+    let response = request
+        .send()
+        .with_context(|| format!("Failed to download '{}'", url))?;
+    let status = response.status();
+    if !status.is_success() {
+        bail!("Failed to download '{}': HTTP {}", url, status);
+    }
+    Ok(response)
+}
+
+/// Removes a compression extension, so "data.tsv.gz" is treated like "data.tsv". Any
+/// query string or fragment of a URL is removed too.
+fn strip_compression(path: &str) -> &str {
+    let path = if is_http_url(path) {
+        path.split(['?', '#']).next().unwrap_or(path)
+    } else {
+        path
+    };
+    match Compression::from_path(path) {
+        Some(_) => path.rsplit_once('.').map_or(path, |(stem, _)| stem),
+        None => path,
+    }
+}
+
+/// Opens a file on disk for reading its raw contents, decompressing them while reading
+/// if the file is compressed.
+pub(crate) fn open_raw(path: &str) -> Result<Box<dyn Read>> {
+    let file = File::open(path).with_context(|| format!("Failed to open '{}'", path))?;
+    let reader = BufReader::new(file);
+    match Compression::from_path(path) {
+        Some(compression) => compression.decoder(reader),
+        None => Ok(Box::new(reader)),
+    }
+}
+
+/// Sets up a lazy scan of a file on disk or in cloud storage.
+///
+/// Cloud storage is reached through Polars' object store support, which takes the
+/// credentials from the environment (e.g. `AWS_ACCESS_KEY_ID`,
+/// `GOOGLE_APPLICATION_CREDENTIALS` or `AZURE_STORAGE_ACCOUNT_KEY`) and only fetches the
+/// parts of each file the query needs.
+///
+/// Compressed files are decompressed into an unnamed temporary file, in the system's
+/// temporary directory, which is then scanned lazily like any other file.
+///
+/// Workbooks and Avro files can't be scanned lazily, so they're read into memory first.
+///
+/// A directory of Parquet or Arrow IPC files partitioned Hive-style, such as
+/// `data/year=2024/month=01/part-0.parquet`, is scanned as one input with the partition
+/// keys as extra columns. Filters on them skip the files of other partitions unread.
+fn scan_path(
+    path: &str,
+    format: InputFormat,
+    csv: CsvSettings,
+    sheet: Option<&str>,
+    delta_version: Option<u64>,
+) -> Result<LazyFrame> {
+    if is_cloud_url(path)
+        && (Compression::from_path(path).is_some()
+            || matches!(
+                format,
+                InputFormat::Xlsx | InputFormat::Avro | InputFormat::Delta
+            ))
+    {
+        bail!(
+            "Cannot read '{}' from cloud storage: only uncompressed CSV, Parquet, NDJSON and \
+             Arrow IPC files can be scanned remotely",
+            path
+        );
+    }
+    if Path::new(path).is_dir() && format != InputFormat::Delta {
+        let hive_options = HiveOptions::new_enabled();
+        return Ok(match format {
+            InputFormat::Parquet => LazyFrame::scan_parquet(
+                PlPath::from_str(path),
+                ScanArgsParquet {
+                    hive_options,
+                    ..Default::default()
+                },
+            )?,
+            InputFormat::Ipc => LazyFrame::scan_ipc(
+                PlPath::from_str(path),
+                ScanArgsIpc {
+                    hive_options,
+                    ..Default::default()
+                },
+            )?,
+            _ => bail!(
+                "Cannot read the directory '{}': partitioned directories must hold Parquet \
+                 or Arrow IPC files",
+                path
+            ),
+        });
+    }
+    if format == InputFormat::Delta {
+        return scan_delta(path, delta_version);
+    }
+    let sources = match Compression::from_path(path) {
+        Some(compression) => {
+            let file = File::open(path).with_context(|| format!("Failed to open '{}'", path))?;
+            let size = file.metadata().ok().map(|metadata| metadata.len());
+            let file = spool(file, size, Some(compression))
+                .with_context(|| format!("Failed to decompress '{}'", path))?;
+            ScanSources::Files([file].into())
+        }
+        None => ScanSources::Paths([PlPath::from_str(path)].into()),
+    };
+    scan_sources_as(sources, format, csv, sheet)
+        .with_context(|| format!("Failed to read '{}'", path))
+}
+
+/// Reads data from stdin into a LazyFrame, decompressing it if it is compressed.
+fn read_stdin(format: InputFormat, csv: CsvSettings, sheet: Option<&str>) -> Result<LazyFrame> {
+    scan_stream(io::stdin().lock(), None, format, csv, sheet).context("Failed to read stdin")
+}
+
+/// Sets up a lazy scan of files in the given format.
+///
+/// Workbooks and Avro files can't be scanned lazily, so they're read into memory and
+/// parsed eagerly; the resulting DataFrame then feeds the same lazy query plan.
+fn scan_sources_as(
+    sources: ScanSources,
+    format: InputFormat,
+    csv: CsvSettings,
+    sheet: Option<&str>,
+) -> Result<LazyFrame> {
+    // Only paths can be Hive-partitioned, and Polars refuses to look for partitions in
+    // the others.
+    let hive_options = match sources {
+        ScanSources::Paths(_) => HiveOptions::default(),
+        _ => HiveOptions::new_disabled(),
+    };
+    Ok(match format {
+        InputFormat::Csv => LazyCsvReader::new_with_sources(sources)
+            .with_has_header(csv.has_header)
+            .map_parse_options(|_| csv.parse_options.clone())
+            .with_infer_schema_length(Some(100))
+            .with_dtype_overwrite(csv.schema_overwrite)
+            .finish()?,
+        InputFormat::Parquet => LazyFrame::scan_parquet_sources(
+            sources,
+            ScanArgsParquet {
+                hive_options,
+                ..Default::default()
+            },
+        )?,
+        // Uncompressed IPC files are memory-mapped rather than copied.
+        InputFormat::Ipc => LazyFrame::scan_ipc_sources(
+            sources,
+            ScanArgsIpc {
+                hive_options,
+                ..Default::default()
+            },
+        )?,
+        InputFormat::Ndjson => LazyJsonLineReader::new_with_sources(sources)
+            .with_infer_schema_length(NonZeroUsize::new(100))
+            .finish()?,
+        InputFormat::Xlsx | InputFormat::Avro => {
+            let buf = sources.at(0).to_memslice()?;
+            match format {
+                InputFormat::Xlsx => read_xlsx(&buf, sheet, csv.has_header)?,
+                _ => read_avro(&buf)?,
+            }
+            .lazy()
+        }
+        InputFormat::Delta => bail!("A Delta Lake table can only be read from a directory"),
+    })
+}
+
+/// Returns the names of all columns whose inferred type is numeric, in file order.
+pub fn numeric_columns(lf: &mut LazyFrame) -> Result<Vec<String>> {
+    let schema = lf.collect_schema()?;
+    let names = schema
+        .iter()
+        .filter(|(_, dtype)| dtype.is_primitive_numeric())
+        .map(|(name, _)| name.to_string())
+        .collect();
+    Ok(names)
+}
@@ -0,0 +1,178 @@
+//! Detection of the columns, or small combinations of columns, that uniquely identify
+//! the rows, such as candidates for a primary or join key.
+
+use anyhow::{Result, bail};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::collect;
+
+/// The most column combinations checked for a single combination size.
+const MAX_COMBINATIONS: usize = 10_000;
+
+/// Options controlling the key search.
+#[derive(Debug, Clone)]
+pub struct KeyOptions {
+    /// The columns to consider, or every column when empty.
+    pub columns: Vec<String>,
+    /// The most columns in a combination, at least 1.
+    pub max_size: usize,
+}
+
+impl Default for KeyOptions {
+    fn default() -> Self {
+        KeyOptions {
+            columns: Vec::new(),
+            max_size: 2,
+        }
+    }
+}
+
+/// The columns that uniquely identify the rows of the input.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyReport {
+    /// The number of rows.
+    pub rows: usize,
+    /// The most columns in a combination that was checked.
+    pub max_size: usize,
+    /// The minimal unique column combinations, smallest first: none contains another.
+    pub candidates: Vec<Vec<String>>,
+}
+
+/// Finds the columns and combinations of up to `options.max_size` columns whose values
+/// are distinct in every row and never missing.
+///
+/// The first pass counts the distinct values and nulls of every column; then each
+/// combination size takes a pass, checking only combinations that could be unique and
+/// don't contain a smaller key.
+pub fn find_keys(mut lf: LazyFrame, options: &KeyOptions) -> Result<KeyReport> {
+    if options.max_size == 0 {
+        bail!("The combination size must be at least 1");
+    }
+    let columns: Vec<String> = if options.columns.is_empty() {
+        lf.collect_schema()?
+            .iter_names()
+            .map(|name| name.to_string())
+            .collect()
+    } else {
+        options.columns.clone()
+    };
+
+    let mut counts = vec![len().alias("rows")];
+    for (i, name) in columns.iter().enumerate() {
+        counts.push(col(name.as_str()).n_unique().alias(format!("{}_unique", i)));
+        counts.push(
+            col(name.as_str())
+                .null_count()
+                .alias(format!("{}_nulls", i)),
+        );
+    }
+    let counts_df = collect(lf.clone().select(counts))?;
+    let get_count = |name: &str| -> Result<usize> {
+        Ok(counts_df.column(name)?.get(0)?.try_extract::<u64>()? as usize)
+    };
+    let rows = get_count("rows")?;
+
+    // Columns with nulls can't be part of a key; the rest are candidates, with the number
+    // of distinct values bounding what combinations of them can reach.
+    let mut candidates: Vec<Vec<String>> = Vec::new();
+    let mut eligible: Vec<(usize, usize)> = Vec::new();
+    for (i, name) in columns.iter().enumerate() {
+        if get_count(&format!("{}_nulls", i))? > 0 {
+            continue;
+        }
+        let distinct = get_count(&format!("{}_unique", i))?;
+        if distinct == rows {
+            candidates.push(vec![name.clone()]);
+        } else {
+            eligible.push((i, distinct));
+        }
+    }
+
+    for size in 2..=options.max_size.min(eligible.len()) {
+        let combinations: Vec<Vec<usize>> = combinations(eligible.len(), size)
+            .into_iter()
+            .filter(|combination| {
+                let reach = combination
+                    .iter()
+                    .map(|&e| eligible[e].1)
+                    .try_fold(1usize, |product, distinct| product.checked_mul(distinct));
+                reach.is_none_or(|reach| reach >= rows)
+            })
+            .map(|combination| combination.iter().map(|&e| eligible[e].0).collect())
+            .filter(|combination: &Vec<usize>| {
+                !candidates.iter().any(|key| {
+                    key.iter()
+                        .all(|name| combination.iter().any(|&i| columns[i] == *name))
+                })
+            })
+            .collect();
+        if combinations.is_empty() {
+            continue;
+        }
+        if combinations.len() > MAX_COMBINATIONS {
+            bail!(
+                "Too many combinations of {} columns to check ({}), pick fewer columns or a \
+                 smaller combination size",
+                size,
+                combinations.len()
+            );
+        }
+
+        let distinct_counts: Vec<Expr> = combinations
+            .iter()
+            .enumerate()
+            .map(|(c, combination)| {
+                let fields = combination
+                    .iter()
+                    .map(|&i| col(columns[i].as_str()))
+                    .collect();
+                as_struct(fields)
+                    .n_unique()
+                    .alias(format!("{}_combination", c))
+            })
+            .collect();
+        let distinct_df = collect(lf.clone().select(distinct_counts))?;
+        for (c, combination) in combinations.iter().enumerate() {
+            let distinct = distinct_df
+                .column(&format!("{}_combination", c))?
+                .get(0)?
+                .try_extract::<u64>()? as usize;
+            if distinct == rows {
+                candidates.push(combination.iter().map(|&i| columns[i].clone()).collect());
+            }
+        }
+    }
+
+    Ok(KeyReport {
+        rows,
+        max_size: options.max_size,
+        candidates,
+    })
+}
+
+/// Lists every combination of `size` indexes below `n`, in lexicographic order.
+fn combinations(n: usize, size: usize) -> Vec<Vec<usize>> {
+    let mut result = Vec::new();
+    extend_combination(n, size, &mut Vec::with_capacity(size), &mut result);
+    result
+}
+
+/// Adds every combination starting with `current` to `result`.
+fn extend_combination(
+    n: usize,
+    size: usize,
+    current: &mut Vec<usize>,
+    result: &mut Vec<Vec<usize>>,
+) {
+    if current.len() == size {
+        result.push(current.clone());
+        return;
+    }
+    let start = current.last().map_or(0, |last| last + 1);
+    for i in start..n {
+        current.push(i);
+        extend_combination(n, size, current, result);
+        current.pop();
+    }
+}
@@ -0,0 +1,112 @@
+//! Descriptive statistics for columns of CSV (and Parquet) files, built on Polars.
+//!
+//! The quickest way in is [`compute`], which reads a file and calculates the
+//! statistics for the requested columns:
+//!
+//! ```no_run
+//! use csv_stats::{StatsOptions, compute};
+//!
+//! let options = StatsOptions {
+//!     columns: vec!["Amount Received".to_string()],
+//!     ..Default::default()
+//! };
+//! for stats in compute("transactions.csv", &options)? {
+//!     println!("{}: mean {:?}", stats.column, stats.mean);
+//! }
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+//!
+//! For more control over how the input is read, build an [`InputOptions`], scan it
+//! with [`input::scan`] and pass the resulting LazyFrame to [`compute_stats`].
+
+pub mod assertions;
+pub mod autocorrelation;
+pub mod avro;
+pub mod benford;
+pub mod bootstrap;
+pub mod columns;
+pub mod correlation;
+pub mod delta;
+pub mod drift;
+pub mod duplicates;
+pub mod engine;
+pub mod excel;
+pub mod export;
+pub mod filter;
+pub mod frequency;
+pub mod histogram;
+pub mod input;
+pub mod keys;
+pub mod malformed;
+pub mod metrics;
+pub mod normalize;
+pub mod outliers;
+pub mod output;
+pub mod preview;
+pub mod profile;
+pub mod regression;
+pub mod report;
+pub mod rolling;
+pub mod schema;
+pub mod server;
+pub mod significance;
+pub mod sql;
+pub mod stats;
+pub mod tdigest;
+pub mod template;
+pub mod tui;
+pub mod validation;
+pub mod watch;
+pub mod yaml;
+
+use anyhow::Result;
+use rayon::prelude::*;
+
+pub use crate::input::{InputFormat, InputOptions};
+pub use crate::stats::{
+    BooleanStats, BucketCount, DateStats, ExtendedStats, GroupKey, GroupSort, Mad, MeanConfidence,
+    Mode, Moments, NumericParsing, PeriodCount, QuantileValue, SelectedStats, StatsOptions,
+    StrictParsingFailed, TextStats, ThresholdCount, ThresholdCounts, TimeBuckets, TrimmedMean,
+    Unparseable, UnparseableValue, Weighted, compute_stats,
+};
+
+/// Reads the file at `path` and calculates statistics for the columns in `options`.
+///
+/// The input format is detected from the file extension; use [`input::scan`] with
+/// [`InputOptions`] to read several files, stdin, or custom delimiters.
+pub fn compute(path: &str, options: &StatsOptions) -> Result<Vec<SelectedStats>> {
+    let lf = input::scan(&InputOptions::from_path(path))?;
+    compute_stats(lf, options)
+}
+
+/// Calculates the statistics of each input file separately, processing the files in
+/// parallel.
+///
+/// The statistics are ordered by file and have [`SelectedStats::file`] set. The
+/// statistics of all files combined come from [`compute_stats`] on the combined input.
+pub fn compute_per_file(
+    input: &InputOptions,
+    options: &StatsOptions,
+) -> Result<Vec<SelectedStats>> {
+    let per_file = input::per_file(input)?
+        .into_par_iter()
+        .map(|file_input| {
+            let file = input::input_name(&file_input);
+            let mut stats = compute_stats(input::scan(&file_input)?, options).map_err(|err| {
+                match err.downcast::<StrictParsingFailed>() {
+                    Ok(unparseable) => StrictParsingFailed {
+                        file: Some(file.clone()),
+                        ..unparseable
+                    }
+                    .into(),
+                    Err(err) => err,
+                }
+            })?;
+            for entry in &mut stats {
+                entry.file = Some(file.clone());
+            }
+            Ok(stats)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(per_file.into_iter().flatten().collect())
+}
@@ -1,114 +1,74 @@
-use anyhow::Result;
-use clap::Parser;
-use polars::prelude::*;
-
-/// A CLI tool to calculate statistics for a numeric column in a CSV file.
-#[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
-struct Cli {
-    /// The path to the CSV file.
-    #[arg(short, long)]
-    file_path: String,
-
-    /// The name of the column to analyze.
-    #[arg(short, long, default_value = "Amount Received")]
-    column_name: String,
-}
-
-/// A container for the calculated statistics.
-#[derive(Debug)]
-struct SelectedStats {
-    /// Total number of records (rows).
-    count: usize,
-    /// The minimum value in the column.
-    min: Option<f64>,
-    /// The maximum value in the column.
-    max: Option<f64>,
-    /// The sum of all values in the column.
-    sum: Option<f64>,
-    /// The mean (average) of all values in the column.
-    mean: Option<f64>,
-}
-
-fn main() -> Result<()> {
-    let cli = Cli::parse();
-
-    // Execute the data processing function.
-    let stats = process_csv(&cli.file_path, &cli.column_name)?;
-
-    // Helper to format Option<f64> values consistently to 4 decimal places.
-    let format_opt = |val: Option<f64>| {
-        val.map(|v| format!("{:.4}", v))
-            .unwrap_or_else(|| "N/A".to_string())
-    };
-
-    // Print the results line by line.
-    println!("Output for rust-polars");
-    println!("--- Statistics for '{}' ---", cli.column_name);
-    println!("Count: {}", stats.count);
-    println!("Min:   {}", format_opt(stats.min));
-    println!("Max:   {}", format_opt(stats.max));
-    println!("Sum:   {}", format_opt(stats.sum));
-    println!("Mean:  {}", format_opt(stats.mean));
+mod cli;
+mod commands;
+mod config;
+mod progress;
 
-    Ok(())
-}
+use std::io::{self, IsTerminal};
+use std::process::ExitCode;
 
-/// Reads a CSV file and calculates descriptive statistics for a specified column using LazyFrame.
-///
-/// This function uses the Polars lazy API to build an optimized query plan,
-/// which is ideal for performance on large datasets.
-fn process_csv(file_path: &str, column_name: &str) -> Result<SelectedStats> {
-    // Create a LazyFrame from the CSV file. This does not read the file yet, only sets up the plan.
-    let lf = LazyCsvReader::new(PlPath::from_str(file_path))
-        .with_has_header(true)
-        .with_infer_schema_length(Some(100))
-        .finish()?;
+use anyhow::Result;
+use clap::{CommandFactory, FromArgMatches};
+use csv_stats::StrictParsingFailed;
+use csv_stats::assertions::AssertionsFailed;
+use csv_stats::drift::DriftExceeded;
+use csv_stats::engine;
+use csv_stats::input::input_size;
 
-    // Build a query plan to calculate all statistics in a single pass.
-    // We cast the target column to Float64 to ensure numeric operations are valid.
-    let aggregations = [
-        // The `count` aggregation works on any type, no cast needed.
-        col(column_name).count().alias("count"),
-        // For numeric stats, we first cast the column to f64.
-        col(column_name).cast(DataType::Float64).min().alias("min"),
-        col(column_name).cast(DataType::Float64).max().alias("max"),
-        col(column_name).cast(DataType::Float64).sum().alias("sum"),
-        col(column_name)
-            .cast(DataType::Float64)
-            .mean()
-            .alias("mean"),
-    ];
+use crate::cli::Cli;
+use crate::config::{Config, profile_arg};
+use crate::progress::Spinner;
 
-    // Execute the query. This materializes the result into a DataFrame.
-    // The resulting DataFrame will have a single row with our calculated stats.
-    let stats_df = lf
-        .select(aggregations)
-        .collect_with_engine(Engine::Streaming)?;
+/// The exit code for failed `--assert` checks, distinct from errors (1) and usage errors (2).
+const ASSERTION_FAILED: u8 = 3;
 
-    // Helper to extract an optional f64 stat value from the results DataFrame.
-    // The DataFrame has only one row, so we always get the value at index 0.
-    let get_optional_f64 = |df: &DataFrame, stat_name: &str| -> Result<Option<f64>> {
-        let any_value = df.column(stat_name)?.get(0)?;
-        match any_value {
-            AnyValue::Null => Ok(None),
-            // The `try_extract` method will handle the conversion from AnyValue to f64.
-            // The `?` will propagate any PolarsError, which gets converted into an anyhow::Error.
-            av => Ok(Some(av.try_extract()?)),
+fn main() -> ExitCode {
+    let cli = match parse_args() {
+        Ok(cli) => cli,
+        Err(err) => {
+            eprintln!("Error: {:?}", err);
+            return ExitCode::FAILURE;
         }
     };
+    engine::set_engine(cli.engine);
+    let input = cli.command.input().options();
+    if cli.command.samples() && input.seed.is_none() {
+        eprintln!(
+            "Note: sampling with the random seed {0}; pass --seed {0} to repeat the sampling",
+            input.seed()
+        );
+    }
+    let progress = cli.progress || (!cli.no_progress && io::stderr().is_terminal());
+    if progress && !cli.command.is_interactive() {
+        // A missing input file is reported by the command itself.
+        let size = input_size(&input).unwrap_or(None);
+        engine::set_observer(Box::new(Spinner::new(size)));
+    }
 
-    // The count is a special case as it's a u32, not an optional f64.
-    let count = stats_df.column("count")?.get(0)?.try_extract::<u32>()? as usize;
-
-    // Extract all the required stats using the helpers.
-    let stats = SelectedStats {
-        count,
-        min: get_optional_f64(&stats_df, "min")?,
-        max: get_optional_f64(&stats_df, "max")?,
-        sum: get_optional_f64(&stats_df, "sum")?,
-        mean: get_optional_f64(&stats_df, "mean")?,
-    };
+    // Dispatch to the handler for the requested subcommand.
+    match commands::run(&cli.command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) if err.is::<AssertionsFailed>() || err.is::<DriftExceeded>() => {
+            eprintln!("{}", err);
+            ExitCode::from(ASSERTION_FAILED)
+        }
+        Err(err) => {
+            match err.downcast_ref::<StrictParsingFailed>() {
+                // Row numbers only make sense with the files they are in.
+                Some(unparseable) => eprintln!("Error: {}", unparseable.describe(&input)),
+                None => eprintln!("Error: {:?}", err),
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
 
-    Ok(stats)
+/// Parses the command line, with defaults from any config files.
+fn parse_args() -> Result<Cli> {
+    let mut config = Config::load()?;
+    if let Some(name) = profile_arg(std::env::args_os()) {
+        config.select_profile(&name)?;
+    }
+    let command = config.apply(Cli::command())?;
+    let matches = command.get_matches();
+    Ok(Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit()))
 }
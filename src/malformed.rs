@@ -0,0 +1,229 @@
+//! Detection of malformed CSV rows: rows with more or fewer fields than the header, and
+//! fields with broken quoting.
+//!
+//! The CSV reader pads short rows with nulls and may drop the extra fields of long ones
+//! without a word, while broken quoting fails with a parse error that doesn't say where
+//! the row is. The raw files are scanned here instead, so each problem can be reported
+//! with its line number and byte offset.
+
+use std::fmt;
+use std::io::{BufRead, BufReader};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::input::{
+    InputFormat, InputOptions, expand_paths, input_format, is_cloud_url, open_raw, separator,
+};
+
+/// A malformed row of a CSV file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MalformedRow {
+    /// The file holding the row.
+    pub file: String,
+    /// The line the row starts on, counting from 1.
+    pub line: usize,
+    /// The offset of the start of the row in the file, in bytes from 0. For compressed
+    /// files, the offset in the decompressed data.
+    pub offset: u64,
+    /// What is wrong with the row.
+    pub problem: String,
+}
+
+impl fmt::Display for MalformedRow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "'{}' line {} (byte {}): {}",
+            self.file, self.line, self.offset, self.problem
+        )
+    }
+}
+
+/// The malformed rows found in the input.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MalformedReport {
+    /// The number of rows checked, excluding headers and empty lines.
+    pub rows: usize,
+    /// The number of malformed rows.
+    pub malformed: usize,
+    /// The first malformed rows, up to the limit.
+    pub examples: Vec<MalformedRow>,
+}
+
+/// Scans the CSV input files for malformed rows, keeping up to `limit` of them as
+/// examples.
+///
+/// The expected number of fields is that of the header, or of the first row without
+/// one. Files in other formats or in cloud storage, and stdin, which can't be read twice,
+/// are skipped.
+pub fn find_malformed_rows(input: &InputOptions, limit: usize) -> Result<MalformedReport> {
+    let mut report = MalformedReport::default();
+    if input.paths.is_empty() || input.paths == ["-"] {
+        return Ok(report);
+    }
+    for path in expand_paths(&input.paths)? {
+        if input_format(input, Some(&path)) != InputFormat::Csv || is_cloud_url(&path) {
+            continue;
+        }
+        let mut scanner =
+            Scanner::new(&path, separator(input, Some(&path)), input.no_header, limit);
+        let mut reader = BufReader::new(open_raw(&path)?);
+        loop {
+            let chunk = reader
+                .fill_buf()
+                .with_context(|| format!("Failed to read '{}'", path))?;
+            if chunk.is_empty() {
+                break;
+            }
+            for &byte in chunk {
+                scanner.push(byte, &mut report);
+            }
+            let consumed = chunk.len();
+            reader.consume(consumed);
+        }
+        scanner.finish(&mut report);
+    }
+    Ok(report)
+}
+
+/// Splits raw CSV bytes into rows, checking each as it ends.
+struct Scanner<'a> {
+    file: &'a str,
+    separator: u8,
+    /// Whether the first row is data rather than a header.
+    no_header: bool,
+    limit: usize,
+    /// The number of fields of the header or first row, once it has been read.
+    expected: Option<usize>,
+    /// The line and offset the next byte is on.
+    line: usize,
+    offset: u64,
+    /// Where the current row started.
+    row_line: usize,
+    row_offset: u64,
+    /// The fields of the current row that have ended.
+    fields: usize,
+    /// Whether the current field has any content, including quotes.
+    field_started: bool,
+    /// Whether the scanner is within a quoted field.
+    quoted: bool,
+    /// Whether the last byte closed a quoted field.
+    after_quote: bool,
+    /// A quoting problem found in the current row.
+    problem: Option<String>,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(file: &'a str, separator: u8, no_header: bool, limit: usize) -> Self {
+        Scanner {
+            file,
+            separator,
+            no_header,
+            limit,
+            expected: None,
+            line: 1,
+            offset: 0,
+            row_line: 1,
+            row_offset: 0,
+            fields: 0,
+            field_started: false,
+            quoted: false,
+            after_quote: false,
+            problem: None,
+        }
+    }
+
+    fn push(&mut self, byte: u8, report: &mut MalformedReport) {
+        if self.quoted {
+            match byte {
+                b'"' => {
+                    self.quoted = false;
+                    self.after_quote = true;
+                }
+                b'\n' => self.line += 1,
+                _ => {}
+            }
+        } else {
+            match byte {
+                b'\n' => {
+                    self.end_row(report);
+                    self.line += 1;
+                    self.row_line = self.line;
+                    self.row_offset = self.offset + 1;
+                }
+                b'\r' => {}
+                b if b == self.separator => {
+                    self.fields += 1;
+                    self.field_started = false;
+                    self.after_quote = false;
+                }
+                b'"' if !self.field_started || self.after_quote => {
+                    // Opens a quoted field, or is the second quote of an escaped `""`.
+                    self.quoted = true;
+                    self.field_started = true;
+                    self.after_quote = false;
+                }
+                _ => {
+                    if self.after_quote && self.problem.is_none() {
+                        self.problem = Some(format!(
+                            "text after the closing quote of field {}",
+                            self.fields + 1
+                        ));
+                    }
+                    self.field_started = true;
+                }
+            }
+        }
+        self.offset += 1;
+    }
+
+    /// Checks the row that ends at the end of the file.
+    fn finish(&mut self, report: &mut MalformedReport) {
+        if self.quoted {
+            self.problem = Some(format!(
+                "the quote of field {} is never closed",
+                self.fields + 1
+            ));
+        }
+        self.end_row(report);
+    }
+
+    fn end_row(&mut self, report: &mut MalformedReport) {
+        let empty = self.fields == 0 && !self.field_started;
+        let fields = self.fields + 1;
+        let problem = self.problem.take();
+        self.fields = 0;
+        self.field_started = false;
+        self.after_quote = false;
+        if empty && problem.is_none() {
+            return;
+        }
+
+        let expected = match self.expected {
+            Some(expected) => expected,
+            None => {
+                self.expected = Some(fields);
+                if !self.no_header {
+                    return;
+                }
+                fields
+            }
+        };
+        report.rows += 1;
+        let problem = problem.or_else(|| {
+            (fields != expected).then(|| format!("{} fields instead of {}", fields, expected))
+        });
+        if let Some(problem) = problem {
+            report.malformed += 1;
+            if report.examples.len() < self.limit {
+                report.examples.push(MalformedRow {
+                    file: self.file.to_string(),
+                    line: self.row_line,
+                    offset: self.row_offset,
+                    problem,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,351 @@
+//! Statistics as monitoring metrics: rendered in the Prometheus text format, or pushed
+//! to a StatsD server or Prometheus Pushgateway after each run.
+
+use std::net::{ToSocketAddrs, UdpSocket};
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde_json::Value;
+
+use crate::significance::Anova;
+use crate::stats::SelectedStats;
+
+/// The prefix of every metric name.
+const PREFIX: &str = "csv_stats";
+
+/// The largest StatsD packet sent, to stay below the usual network MTU.
+const MAX_PACKET: usize = 1400;
+
+/// How long a Pushgateway gets to accept the metrics.
+const PUSH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A single value of a metric, such as the mean of a column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    /// The name of the statistic, e.g. `mean`, `moments_skew` or `quantile`.
+    pub name: String,
+    /// What the value describes: the file, column and any group, and for quantiles the
+    /// quantile.
+    pub labels: Vec<(String, String)>,
+    /// The value, as it is written in the metrics.
+    pub value: String,
+}
+
+/// Flattens the statistics into metric samples. Nested statistics are joined with
+/// underscores, e.g. `moments_skew`, and quantiles share the `quantile` name with a
+/// `quantile` label. Bootstrap intervals are named `bootstrap_lower`, `bootstrap_upper`
+/// and `bootstrap_estimate`, with a `statistic` label. The analyses of variance follow,
+/// named `anova_<statistic>`, with the column and the column it was grouped by. Missing
+/// and non-numeric values are left out.
+///
+/// `input` names the file of the statistics that cover all input files combined.
+pub fn samples(input: &str, stats: &[SelectedStats], anova: &[Anova]) -> Result<Vec<Sample>> {
+    let mut samples = Vec::new();
+    for s in stats {
+        let mut labels = vec![
+            (
+                "file".to_string(),
+                s.file.as_deref().unwrap_or(input).to_string(),
+            ),
+            ("column".to_string(), s.column.clone()),
+        ];
+        if let Some(group) = &s.group {
+            labels.push(("group_by".to_string(), group.column.clone()));
+            labels.push(("group".to_string(), group.value.clone().unwrap_or_default()));
+        }
+        let mut add = |name: String, labels: Vec<(String, String)>, value: &Value| {
+            let value = match value {
+                Value::Number(n) => n.to_string(),
+                Value::Bool(b) => u8::from(*b).to_string(),
+                _ => return,
+            };
+            samples.push(Sample {
+                name: metric_name(&name),
+                labels,
+                value,
+            });
+        };
+
+        let Value::Object(fields) = serde_json::to_value(s)? else {
+            bail!("Statistics must serialize to a JSON object");
+        };
+        for (name, value) in fields {
+            match (name.as_str(), value) {
+                ("quantiles", Value::Array(quantiles)) => {
+                    for q in quantiles {
+                        let mut labels = labels.clone();
+                        labels.push(("quantile".to_string(), q["quantile"].to_string()));
+                        add("quantile".to_string(), labels, &q["value"]);
+                    }
+                }
+                ("bootstrap", Value::Object(bootstrap)) => {
+                    for (key, value) in bootstrap {
+                        let Value::Array(intervals) = value else {
+                            add(format!("bootstrap_{}", key), labels.clone(), &value);
+                            continue;
+                        };
+                        for interval in intervals {
+                            let mut labels = labels.clone();
+                            labels.push((
+                                "statistic".to_string(),
+                                interval["statistic"]
+                                    .as_str()
+                                    .unwrap_or_default()
+                                    .to_string(),
+                            ));
+                            if let Some(q) = interval.get("quantile") {
+                                labels.push(("quantile".to_string(), q.to_string()));
+                            }
+                            for bound in ["estimate", "lower", "upper"] {
+                                let name = format!("bootstrap_{}", bound);
+                                add(name, labels.clone(), &interval[bound]);
+                            }
+                        }
+                    }
+                }
+                (_, Value::Object(nested)) => {
+                    for (key, value) in nested {
+                        add(format!("{}_{}", name, key), labels.clone(), &value);
+                    }
+                }
+                (_, value) => add(name, labels.clone(), &value),
+            }
+        }
+    }
+    for analysis in anova {
+        let labels = vec![
+            ("file".to_string(), input.to_string()),
+            ("column".to_string(), analysis.column.clone()),
+            ("group_by".to_string(), analysis.group_by.clone()),
+        ];
+        let Value::Object(fields) = serde_json::to_value(analysis)? else {
+            bail!("An analysis of variance must serialize to a JSON object");
+        };
+        for (name, value) in fields {
+            let value = match value {
+                Value::Number(n) => n.to_string(),
+                Value::Bool(b) => u8::from(b).to_string(),
+                _ => continue,
+            };
+            samples.push(Sample {
+                name: metric_name(&format!("anova_{}", name)),
+                labels: labels.clone(),
+                value,
+            });
+        }
+    }
+    Ok(samples)
+}
+
+/// Renders the statistics as Prometheus gauges named `csv_stats_<statistic>`, such as
+/// `csv_stats_mean{file="x.csv",column="Amount"} 123.4`.
+pub fn to_prometheus(input: &str, stats: &[SelectedStats], anova: &[Anova]) -> Result<String> {
+    // The samples of each metric, which must be listed together, in first-seen order.
+    let mut metrics: Vec<(String, Vec<String>)> = Vec::new();
+    for sample in samples(input, stats, anova)? {
+        let name = format!("{}_{}", PREFIX, sample.name);
+        let labels: Vec<String> = sample
+            .labels
+            .iter()
+            .map(|(label, value)| format!("{}=\"{}\"", label, prometheus_escape(value)))
+            .collect();
+        let line = format!("{}{{{}}} {}", name, labels.join(","), sample.value);
+        match metrics.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, lines)) => lines.push(line),
+            None => metrics.push((name, vec![line])),
+        }
+    }
+
+    let mut out = String::new();
+    for (name, lines) in metrics {
+        out.push_str(&format!("# TYPE {} gauge\n", name));
+        for line in lines {
+            out.push_str(&line);
+            out.push('\n');
+        }
+    }
+    Ok(out)
+}
+
+/// Renders the statistics as StatsD gauges, one per line, named after the file name,
+/// column, any group and the statistic, e.g. `csv_stats.x_csv.Amount.mean:123.4|g`.
+pub fn to_statsd(input: &str, stats: &[SelectedStats], anova: &[Anova]) -> Result<Vec<String>> {
+    Ok(samples(input, stats, anova)?
+        .into_iter()
+        .map(|sample| {
+            let mut parts = vec![PREFIX.to_string()];
+            let mut quantile = None;
+            for (label, value) in &sample.labels {
+                match label.as_str() {
+                    "file" => {
+                        let name = std::path::Path::new(value)
+                            .file_name()
+                            .map_or(value.clone(), |n| n.to_string_lossy().into_owned());
+                        parts.push(statsd_part(&name));
+                    }
+                    "group_by" => {}
+                    "quantile" => quantile = Some(statsd_part(value)),
+                    _ => parts.push(statsd_part(value)),
+                }
+            }
+            parts.push(sample.name);
+            parts.extend(quantile);
+            format!("{}:{}|g", parts.join("."), sample.value)
+        })
+        .collect())
+}
+
+/// Where to push the statistics after each run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PushTarget {
+    /// A StatsD server, as `host:port`, which receives the metrics over UDP.
+    Statsd(String),
+    /// The URL of a Prometheus Pushgateway's job, e.g.
+    /// `http://localhost:9091/metrics/job/csv_stats`.
+    Pushgateway(String),
+}
+
+impl FromStr for PushTarget {
+    type Err = anyhow::Error;
+
+    /// Parses `statsd://host:port`, or an HTTP(S) Pushgateway URL. A Pushgateway URL
+    /// without a path pushes to the `csv_stats` job.
+    fn from_str(target: &str) -> Result<Self> {
+        if let Some(address) = target.strip_prefix("statsd://") {
+            let address = address.trim_end_matches('/');
+            return Ok(if address.contains(':') {
+                PushTarget::Statsd(address.to_string())
+            } else {
+                PushTarget::Statsd(format!("{}:8125", address))
+            });
+        }
+        let Some((_, rest)) = target
+            .split_once("://")
+            .filter(|(scheme, _)| matches!(*scheme, "http" | "https"))
+        else {
+            bail!(
+                "Expected 'statsd://host:port' or a Pushgateway URL, got '{}'",
+                target
+            );
+        };
+        Ok(if rest.trim_end_matches('/').contains('/') {
+            PushTarget::Pushgateway(target.to_string())
+        } else {
+            PushTarget::Pushgateway(format!(
+                "{}/metrics/job/{}",
+                target.trim_end_matches('/'),
+                PREFIX
+            ))
+        })
+    }
+}
+
+/// Sends the statistics to the target as metrics.
+pub fn push(
+    target: &PushTarget,
+    input: &str,
+    stats: &[SelectedStats],
+    anova: &[Anova],
+) -> Result<()> {
+    match target {
+        PushTarget::Statsd(address) => push_statsd(address, &to_statsd(input, stats, anova)?),
+        PushTarget::Pushgateway(url) => push_gateway(url, to_prometheus(input, stats, anova)?),
+    }
+}
+
+/// Sends StatsD lines over UDP, batching as many as fit in each packet.
+fn push_statsd(address: &str, lines: &[String]) -> Result<()> {
+    let target = address
+        .to_socket_addrs()
+        .with_context(|| format!("Failed to resolve StatsD server '{}'", address))?
+        .next()
+        .ok_or_else(|| anyhow!("Failed to resolve StatsD server '{}'", address))?;
+    let local = if target.is_ipv4() {
+        "0.0.0.0:0"
+    } else {
+        "[::]:0"
+    };
+    let socket = UdpSocket::bind(local)?;
+
+    let mut packet = String::new();
+    for line in lines {
+        if !packet.is_empty() && packet.len() + 1 + line.len() > MAX_PACKET {
+            send_packet(&socket, target, &packet)?;
+            packet.clear();
+        }
+        if !packet.is_empty() {
+            packet.push('\n');
+        }
+        packet.push_str(line);
+    }
+    if !packet.is_empty() {
+        send_packet(&socket, target, &packet)?;
+    }
+    Ok(())
+}
+
+fn send_packet(socket: &UdpSocket, target: std::net::SocketAddr, packet: &str) -> Result<()> {
+    socket
+        .send_to(packet.as_bytes(), target)
+        .with_context(|| format!("Failed to send metrics to StatsD server '{}'", target))?;
+    Ok(())
+}
+
+/// Pushes Prometheus metrics to a Pushgateway, replacing those of the same names.
+fn push_gateway(url: &str, body: String) -> Result<()> {
+    let client = reqwest::blocking::Client::builder()
+        .user_agent(concat!(
+            env!("CARGO_PKG_NAME"),
+            "/",
+            env!("CARGO_PKG_VERSION")
+        ))
+        .timeout(PUSH_TIMEOUT)
+        .build()?;
+    let response = client
+        .post(url)
+        .header("Content-Type", "text/plain; version=0.0.4")
+        .body(body)
+        .send()
+        .with_context(|| format!("Failed to push metrics to '{}'", url))?;
+    let status = response.status();
+    if !status.is_success() {
+        bail!("Failed to push metrics to '{}': HTTP {}", url, status);
+    }
+    Ok(())
+}
+
+/// Replaces the characters Prometheus doesn't allow in metric names with underscores.
+fn metric_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Makes a value usable as part of a StatsD metric name, which can't contain the
+/// separators `.`, `:`, `|` or `@`, or whitespace.
+fn statsd_part(value: &str) -> String {
+    let part: String = value
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if part.is_empty() {
+        "_".to_string()
+    } else {
+        part
+    }
+}
+
+/// Escapes a Prometheus label value.
+fn prometheus_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
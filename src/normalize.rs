@@ -0,0 +1,138 @@
+//! Normalization of numeric columns, e.g. to put features on a common scale before
+//! they're used elsewhere.
+
+use std::cmp::Ordering;
+
+use anyhow::{Result, bail};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::stats::{NumericParsing, StatsOptions, compute_stats};
+
+/// How the values are rescaled.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NormalizeMethod {
+    /// The number of sample standard deviations each value lies from the mean.
+    #[default]
+    Zscore,
+    /// The position of each value between the minimum, 0, and the maximum, 1.
+    Minmax,
+}
+
+/// Options controlling the normalization.
+#[derive(Debug, Clone, Default)]
+pub struct NormalizeOptions {
+    pub method: NormalizeMethod,
+    /// With [`NormalizeMethod::Minmax`], scale between these bounds instead of the
+    /// columns' minimum and maximum, e.g. to use the same scale for several files.
+    pub range: Option<(f64, f64)>,
+    /// With `range`, clamp the values outside it to its bounds, so every result lies
+    /// between 0 and 1.
+    pub clip: bool,
+    /// How the values of the normalized columns are parsed.
+    pub parsing: NumericParsing,
+}
+
+/// How a column was rescaled: each value `v` became `(v - shift) / scale`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scaling {
+    pub column: String,
+    /// The value subtracted: the mean, or the minimum.
+    pub shift: f64,
+    /// The value divided by: the standard deviation, or the range.
+    pub scale: f64,
+}
+
+/// Replaces each column with its normalized values, and returns the scaling of each
+/// column.
+///
+/// The means, standard deviations, minimums and maximums come from the statistics
+/// engine, so they match the `stats` command's. Missing and unparseable values stay
+/// missing.
+pub fn normalize(
+    lf: LazyFrame,
+    columns: &[String],
+    options: &NormalizeOptions,
+) -> Result<(LazyFrame, Vec<Scaling>)> {
+    if options.method != NormalizeMethod::Minmax && (options.range.is_some() || options.clip) {
+        bail!("A range and clipping only apply to min-max scaling");
+    }
+    if options.clip && options.range.is_none() {
+        bail!("Clipping needs a range to clip to");
+    }
+    if let Some((low, high)) = options.range
+        && low.partial_cmp(&high) != Some(Ordering::Less)
+    {
+        bail!(
+            "The range must go from low to high, got {} to {}",
+            low,
+            high
+        );
+    }
+
+    let scalings = match (options.method, options.range) {
+        (NormalizeMethod::Minmax, Some((low, high))) => {
+            let mut scalings = Vec::with_capacity(columns.len());
+            for column in columns {
+                options.parsing.check_strict(&lf, column)?;
+                scalings.push(Scaling {
+                    column: column.clone(),
+                    shift: low,
+                    scale: high - low,
+                });
+            }
+            scalings
+        }
+        (method, _) => {
+            let stats_options = StatsOptions {
+                columns: columns.to_vec(),
+                parsing: options.parsing.clone(),
+                ..Default::default()
+            };
+            let stats = compute_stats(lf.clone(), &stats_options)?;
+            let mut scalings = Vec::with_capacity(stats.len());
+            for s in &stats {
+                if !s.is_numeric() {
+                    bail!("Column '{}' isn't numeric", s.column);
+                }
+                let (shift, scale) = match method {
+                    NormalizeMethod::Zscore => (s.mean, s.std),
+                    NormalizeMethod::Minmax => {
+                        (s.min, s.max.zip(s.min).map(|(max, min)| max - min))
+                    }
+                };
+                let (Some(shift), Some(scale)) = (shift, scale) else {
+                    bail!(
+                        "Column '{}' has too few numeric values to normalize",
+                        s.column
+                    );
+                };
+                if scale == 0.0 {
+                    bail!(
+                        "Column '{}' is constant, so it can't be normalized",
+                        s.column
+                    );
+                }
+                scalings.push(Scaling {
+                    column: s.column.clone(),
+                    shift,
+                    scale,
+                });
+            }
+            scalings
+        }
+    };
+
+    let rescaled: Vec<Expr> = scalings
+        .iter()
+        .map(|s| {
+            let mut value = options.parsing.to_float(&s.column);
+            if options.clip {
+                value = value.clip(lit(s.shift), lit(s.shift + s.scale));
+            }
+            ((value - lit(s.shift)) / lit(s.scale)).alias(s.column.as_str())
+        })
+        .collect();
+    Ok((lf.with_columns(rescaled), scalings))
+}
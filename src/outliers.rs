@@ -0,0 +1,172 @@
+//! Detection of outlying values in a numeric column.
+
+use anyhow::{Result, bail};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::collect;
+use crate::stats::NumericParsing;
+
+/// The name of the column holding each outlier's row number.
+pub const ROW_COLUMN: &str = "row";
+
+/// How values are judged to be outliers.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutlierMethod {
+    /// Values more than the threshold times the interquartile range below the first or
+    /// above the third quartile (Tukey's fences). Robust to the outliers themselves.
+    #[default]
+    Iqr,
+    /// Values more than the threshold of standard deviations away from the mean.
+    Zscore,
+}
+
+impl OutlierMethod {
+    /// The conventional threshold for the method: 1.5 IQRs or 3 standard deviations.
+    pub fn default_threshold(self) -> f64 {
+        match self {
+            OutlierMethod::Iqr => 1.5,
+            OutlierMethod::Zscore => 3.0,
+        }
+    }
+}
+
+/// Options controlling outlier detection.
+#[derive(Debug, Clone, Default)]
+pub struct OutlierOptions {
+    /// How outliers are detected.
+    pub method: OutlierMethod,
+    /// The multiplier of the IQR or standard deviation, defaulting to the method's
+    /// conventional threshold.
+    pub threshold: Option<f64>,
+    /// How text values are cleaned before being parsed as numbers.
+    pub parsing: NumericParsing,
+}
+
+/// The outliers found in a column.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutlierReport {
+    /// The name of the column that was checked.
+    pub column: String,
+    /// How outliers were detected.
+    pub method: OutlierMethod,
+    /// The multiplier of the IQR or standard deviation that was used.
+    pub threshold: f64,
+    /// Values below this bound are outliers.
+    pub lower: f64,
+    /// Values above this bound are outliers.
+    pub upper: f64,
+    /// The number of non-null values that were checked.
+    pub count: usize,
+    /// The outlying values in file order.
+    pub outliers: Vec<Outlier>,
+}
+
+/// A single outlying value.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Outlier {
+    /// The zero-based number of the data row, not counting the header.
+    pub row: usize,
+    /// The value in the checked column.
+    pub value: f64,
+}
+
+/// The outliers of a column, with the complete rows they were found in.
+pub struct Outliers {
+    /// The summary of the outliers.
+    pub report: OutlierReport,
+    /// Every column of the outlying rows, preceded by their row numbers in [`ROW_COLUMN`].
+    pub rows: DataFrame,
+}
+
+/// Finds the values of a column that lie outside the bounds set by `options`.
+///
+/// This takes two passes over the data: one to calculate the bounds and one to collect
+/// the rows outside them.
+pub fn compute_outliers(
+    lf: LazyFrame,
+    column_name: &str,
+    options: &OutlierOptions,
+) -> Result<Outliers> {
+    let threshold = options
+        .threshold
+        .unwrap_or_else(|| options.method.default_threshold());
+    if threshold.is_nan() || threshold < 0.0 {
+        bail!(
+            "The outlier threshold must not be negative, got {}",
+            threshold
+        );
+    }
+    options.parsing.check_strict(&lf, column_name)?;
+    let values = options.parsing.to_float(column_name);
+
+    // First pass: the statistics the bounds are derived from.
+    let (center_low, center_high, spread) = match options.method {
+        OutlierMethod::Iqr => {
+            let q1 = values.clone().quantile(lit(0.25), QuantileMethod::Linear);
+            let q3 = values.clone().quantile(lit(0.75), QuantileMethod::Linear);
+            (q1.clone(), q3.clone(), q3 - q1)
+        }
+        OutlierMethod::Zscore => {
+            let mean = values.clone().mean();
+            (mean.clone(), mean, values.clone().std(1))
+        }
+    };
+    let bounds_df = collect(lf.clone().select([
+        (center_low - lit(threshold) * spread.clone()).alias("lower"),
+        (center_high + lit(threshold) * spread).alias("upper"),
+        values.clone().count().alias("count"),
+    ]))?;
+    let get_f64 = |name: &str| -> Result<Option<f64>> {
+        match bounds_df.column(name)?.get(0)? {
+            AnyValue::Null => Ok(None),
+            av => Ok(Some(av.try_extract()?)),
+        }
+    };
+    let (Some(lower), Some(upper)) = (get_f64("lower")?, get_f64("upper")?) else {
+        bail!(
+            "Column '{}' needs at least two numeric values to detect outliers",
+            column_name
+        );
+    };
+    let count = bounds_df.column("count")?.get(0)?.try_extract::<u32>()? as usize;
+
+    // Second pass: the rows outside the bounds, numbered before filtering. The parsed
+    // values ride along in a temporary column.
+    const VALUE_COLUMN: &str = "__outlier_value";
+    let mut rows = collect(
+        lf.with_row_index(ROW_COLUMN, None)
+            .filter(
+                values
+                    .clone()
+                    .lt(lit(lower))
+                    .or(values.clone().gt(lit(upper))),
+            )
+            .with_column(values.alias(VALUE_COLUMN)),
+    )?;
+    let outliers = rows
+        .column(ROW_COLUMN)?
+        .idx()?
+        .into_no_null_iter()
+        .zip(rows.column(VALUE_COLUMN)?.f64()?.into_no_null_iter())
+        .map(|(row, value)| Outlier {
+            row: row as usize,
+            value,
+        })
+        .collect();
+    rows = rows.drop(VALUE_COLUMN)?;
+
+    Ok(Outliers {
+        report: OutlierReport {
+            column: column_name.to_string(),
+            method: options.method,
+            threshold,
+            lower,
+            upper,
+            count,
+            outliers,
+        },
+        rows,
+    })
+}
@@ -0,0 +1,1826 @@
+//! Rendering of calculated statistics in the supported output formats.
+
+use anyhow::{Context, Result, bail};
+use polars::prelude::*;
+use serde::Serialize;
+
+use crate::autocorrelation::Autocorrelation;
+use crate::benford::BenfordReport;
+use crate::correlation::{Correlation, CorrelationMatrix};
+use crate::drift::DriftReport;
+use crate::duplicates::DuplicateReport;
+use crate::frequency::FrequencyTable;
+use crate::histogram::Histogram;
+use crate::keys::KeyReport;
+use crate::malformed::MalformedReport;
+use crate::metrics::to_prometheus;
+use crate::outliers::{OutlierMethod, OutlierReport};
+use crate::regression::Regression;
+use crate::schema::ColumnInfo;
+use crate::significance::{Anova, ChiSquareReport, SignificanceReport, TestResult};
+use crate::stats::{BooleanStats, DateStats, Mode, SelectedStats, TextStats, any_value_to_string};
+use crate::template::Template;
+use crate::validation::Validation;
+use crate::yaml::to_yaml;
+
+/// The format used to print results.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable lines.
+    #[default]
+    Text,
+    /// JSON, suitable for piping into `jq` or other tooling.
+    Json,
+    /// GitHub-flavored Markdown tables, ready to paste into PRs and wiki pages.
+    Markdown,
+    /// YAML, with the same fields as the JSON output.
+    Yaml,
+    /// Prometheus metrics in the text exposition format, for a Pushgateway or the
+    /// node_exporter textfile collector. Only the `stats` command supports it.
+    Prom,
+}
+
+impl OutputFormat {
+    /// Whether the format serializes the results as data (JSON, YAML or Prometheus
+    /// metrics) rather than laying them out for reading.
+    pub fn is_structured(self) -> bool {
+        matches!(
+            self,
+            OutputFormat::Json | OutputFormat::Yaml | OutputFormat::Prom
+        )
+    }
+}
+
+/// Serializes results as pretty-printed JSON, or as YAML for that format. Only statistics
+/// have a Prometheus form, written by [`print_stats`].
+fn serialize<T: Serialize + ?Sized>(format: OutputFormat, value: &T) -> Result<String> {
+    Ok(match format {
+        OutputFormat::Yaml => to_yaml(&serde_json::to_value(value)?),
+        OutputFormat::Prom => bail!("Prometheus output is only supported by the stats command"),
+        _ => serde_json::to_string_pretty(value)?,
+    })
+}
+
+/// A simple table of pre-formatted cells that can be rendered as text or Markdown.
+///
+/// The first column holds labels; all other columns hold values.
+pub struct Table {
+    /// The column headers.
+    pub headers: Vec<String>,
+    /// The rows, each with one cell per header.
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// Renders the table as plain text with aligned columns.
+    ///
+    /// The first column is left-aligned as a label; all other columns are right-aligned values.
+    pub fn to_text(&self) -> String {
+        let widths: Vec<usize> = (0..self.headers.len())
+            .map(|i| {
+                self.rows
+                    .iter()
+                    .map(|row| row[i].chars().count())
+                    .chain(std::iter::once(self.headers[i].chars().count()))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let format_row = |cells: &[String]| {
+            let line = cells
+                .iter()
+                .zip(&widths)
+                .enumerate()
+                .map(|(i, (cell, width))| {
+                    if i == 0 {
+                        format!("{:<width$}", cell, width = width)
+                    } else {
+                        format!("{:>width$}", cell, width = width)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("  ");
+            format!("{}\n", line.trim_end())
+        };
+
+        let mut table = format_row(&self.headers);
+        let separator: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+        table.push_str(&format_row(&separator));
+        for row in &self.rows {
+            table.push_str(&format_row(row));
+        }
+        table
+    }
+
+    /// Renders the table as a GitHub-flavored Markdown table with right-aligned values.
+    pub fn to_markdown(&self) -> String {
+        // Pipes would otherwise end the cell early.
+        let format_row = |cells: &[String]| {
+            let cells: Vec<String> = cells.iter().map(|c| c.replace('|', "\\|")).collect();
+            format!("| {} |\n", cells.join(" | "))
+        };
+
+        let mut table = format_row(&self.headers);
+        let alignments: Vec<String> = (0..self.headers.len())
+            .map(|i| if i == 0 { "---" } else { "---:" }.to_string())
+            .collect();
+        table.push_str(&format!("|{}|\n", alignments.join("|")));
+        for row in &self.rows {
+            table.push_str(&format_row(row));
+        }
+        table
+    }
+
+    /// Renders the table in the given tabular format.
+    fn render(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Markdown => self.to_markdown(),
+            _ => self.to_text(),
+        }
+    }
+}
+
+/// Prints the statistics for each column to stdout in the requested format. `input`
+/// names the input in Prometheus labels, for statistics not of a single file.
+///
+/// The analyses of variance in `anova`, one per analyzed column, follow the table of
+/// their column. In JSON and YAML the statistics are then a `stats` list next to an
+/// `anova` one, rather than the only list.
+pub fn print_stats(
+    format: OutputFormat,
+    input: &str,
+    stats: &[SelectedStats],
+    anova: &[Anova],
+) -> Result<()> {
+    match format {
+        OutputFormat::Text => print_text(stats, anova),
+        OutputFormat::Json | OutputFormat::Yaml if anova.is_empty() => {
+            println!("{}", serialize(format, stats)?)
+        }
+        OutputFormat::Json | OutputFormat::Yaml => println!(
+            "{}",
+            serialize(
+                format,
+                &serde_json::json!({ "stats": stats, "anova": anova })
+            )?
+        ),
+        OutputFormat::Markdown => print_markdown(stats, anova),
+        OutputFormat::Prom => print!("{}", to_prometheus(input, stats, anova)?),
+    }
+    Ok(())
+}
+
+/// Prints the statistics through a template file, which sees the name of the `input`, the
+/// `stats` as they are serialized to JSON and the `anova` list of analyses of variance.
+pub fn print_template(
+    path: &str,
+    input: &str,
+    stats: &[SelectedStats],
+    anova: &[Anova],
+) -> Result<()> {
+    let template = Template::from_file(path)?;
+    let context = serde_json::json!({ "input": input, "stats": stats, "anova": anova });
+    print!("{}", template.render(&context)?);
+    Ok(())
+}
+
+/// Prints a summary table with one row per statistic and one column per analyzed column.
+pub fn print_describe(format: OutputFormat, stats: &[SelectedStats]) -> Result<()> {
+    if format.is_structured() {
+        println!("{}", serialize(format, stats)?);
+        return Ok(());
+    }
+
+    let mut headers = vec!["statistic".to_string()];
+    headers.extend(stats.iter().map(|s| s.column.clone()));
+
+    // Helper to build a table row from a label and a per-column value getter.
+    let row = |label: &str, value: &dyn Fn(&SelectedStats) -> String| {
+        let mut cells = vec![label.to_string()];
+        cells.extend(stats.iter().map(value));
+        cells
+    };
+
+    let mut rows = vec![
+        row("count", &|s| s.count.to_string()),
+        row("mean", &|s| format_opt(s.mean)),
+        row("std", &|s| format_opt(s.std)),
+        row("min", &|s| format_opt(s.min)),
+    ];
+    // The quartiles are passed in as quantiles, so label them as percentiles like pandas does.
+    if let Some(first) = stats.first() {
+        for (i, q) in first.quantiles.iter().enumerate() {
+            rows.push(row(&format!("{}%", q.quantile * 100.0), &|s| {
+                format_opt(s.quantiles[i].value)
+            }));
+        }
+    }
+    rows.push(row("max", &|s| format_opt(s.max)));
+
+    print!("{}", Table { headers, rows }.render(format));
+    Ok(())
+}
+
+/// The width, in characters, of the longest bar in a text histogram.
+const HISTOGRAM_BAR_WIDTH: usize = 50;
+
+/// The width, in characters, of the bar of an autocorrelation of ±1 in text output.
+const ACF_BAR_WIDTH: usize = 30;
+
+/// Prints a histogram, as ASCII bars for text output.
+pub fn print_histogram(format: OutputFormat, histogram: &Histogram) -> Result<()> {
+    let table = Table {
+        headers: vec![
+            "Lower".to_string(),
+            "Upper".to_string(),
+            "Count".to_string(),
+        ],
+        rows: histogram
+            .bins
+            .iter()
+            .map(|b| {
+                vec![
+                    format!("{:.4}", b.lower),
+                    format!("{:.4}", b.upper),
+                    b.count.to_string(),
+                ]
+            })
+            .collect(),
+    };
+
+    match format {
+        OutputFormat::Json | OutputFormat::Yaml | OutputFormat::Prom => {
+            println!("{}", serialize(format, histogram)?)
+        }
+        OutputFormat::Markdown => print!("{}", table.to_markdown()),
+        OutputFormat::Text => {
+            println!("--- Histogram for '{}' ---", histogram.column);
+            let max_count = histogram.bins.iter().map(|b| b.count).max().unwrap_or(0);
+            let label_width = table
+                .rows
+                .iter()
+                .map(|row| row[0].len().max(row[1].len()))
+                .max()
+                .unwrap_or(0);
+            for (i, (bin, row)) in histogram.bins.iter().zip(&table.rows).enumerate() {
+                // Only the last bin includes its upper bound.
+                let close = if i + 1 == histogram.bins.len() {
+                    ']'
+                } else {
+                    ')'
+                };
+                // Scale bars to the fullest bin, keeping any non-empty bin visible.
+                let bar_len = (bin.count * HISTOGRAM_BAR_WIDTH)
+                    .checked_div(max_count)
+                    .unwrap_or(0)
+                    .max(usize::from(bin.count > 0));
+                println!(
+                    "[{:>w$}, {:>w$}{} {:<bar_w$} {}",
+                    row[0],
+                    row[1],
+                    close,
+                    "#".repeat(bar_len),
+                    bin.count,
+                    w = label_width,
+                    bar_w = HISTOGRAM_BAR_WIDTH
+                );
+            }
+            if histogram.out_of_range > 0 {
+                println!(
+                    "{} values outside the range were skipped",
+                    histogram.out_of_range
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Prints a frequency table of a column's most common values.
+pub fn print_frequencies(format: OutputFormat, frequencies: &FrequencyTable) -> Result<()> {
+    if format.is_structured() {
+        println!("{}", serialize(format, frequencies)?);
+        return Ok(());
+    }
+
+    let table = Table {
+        headers: vec![
+            frequencies.column.clone(),
+            "Count".to_string(),
+            "Percent".to_string(),
+        ],
+        rows: frequencies
+            .values
+            .iter()
+            .map(|v| {
+                vec![
+                    v.value.clone().unwrap_or_else(|| "null".to_string()),
+                    v.count.to_string(),
+                    format!("{:.2}%", v.percent),
+                ]
+            })
+            .collect(),
+    };
+
+    if format == OutputFormat::Text {
+        println!(
+            "--- Most frequent values of '{}' ({} distinct in {} rows) ---",
+            frequencies.column, frequencies.distinct, frequencies.total
+        );
+    }
+    print!("{}", table.render(format));
+    Ok(())
+}
+
+/// Prints the leading digit distribution of a column next to Benford's law, followed
+/// by the chi-square test result.
+pub fn print_benford(format: OutputFormat, report: &BenfordReport) -> Result<()> {
+    if format.is_structured() {
+        println!("{}", serialize(format, report)?);
+        return Ok(());
+    }
+
+    let table = Table {
+        headers: vec![
+            "Digit".to_string(),
+            "Count".to_string(),
+            "Observed".to_string(),
+            "Expected".to_string(),
+        ],
+        rows: report
+            .digits
+            .iter()
+            .map(|d| {
+                vec![
+                    d.digit.to_string(),
+                    d.count.to_string(),
+                    format!("{:.2}%", d.observed * 100.0),
+                    format!("{:.2}%", d.expected * 100.0),
+                ]
+            })
+            .collect(),
+    };
+    let verdict = if report.significant {
+        "deviates significantly from"
+    } else {
+        "is consistent with"
+    };
+    let summary = format!(
+        "Chi-square {:.4} (8 degrees of freedom), p-value {:.4}: the distribution {} Benford's law at the {} significance level",
+        report.chi_square, report.p_value, verdict, report.significance
+    );
+
+    if format == OutputFormat::Text {
+        println!(
+            "--- Leading digits of '{}' ({} values) ---",
+            report.column, report.count
+        );
+        print!("{}", table.to_text());
+        println!("{}", summary);
+    } else {
+        print!("{}", table.to_markdown());
+        println!("\n{}", summary);
+    }
+    Ok(())
+}
+
+/// Prints the comparison of a column between two samples and the significance tests.
+pub fn print_significance(format: OutputFormat, report: &SignificanceReport) -> Result<()> {
+    if format.is_structured() {
+        println!("{}", serialize(format, report)?);
+        return Ok(());
+    }
+
+    let samples = Table {
+        headers: vec![
+            "Sample".to_string(),
+            "Count".to_string(),
+            "Mean".to_string(),
+            "Std".to_string(),
+            "Median".to_string(),
+        ],
+        rows: report
+            .samples
+            .iter()
+            .map(|s| {
+                vec![
+                    s.name.clone(),
+                    s.count.to_string(),
+                    format!("{:.4}", s.mean),
+                    format!("{:.4}", s.std),
+                    format!("{:.4}", s.median),
+                ]
+            })
+            .collect(),
+    };
+    let test_row = |name: &str, test: &TestResult| {
+        vec![
+            name.to_string(),
+            format!("{:.4}", test.statistic),
+            test.degrees_of_freedom
+                .map_or(String::new(), |df| format!("{:.2}", df)),
+            format!("{:.4}", test.p_value),
+            if test.significant { "yes" } else { "no" }.to_string(),
+        ]
+    };
+    let tests = Table {
+        headers: vec![
+            "Test".to_string(),
+            "Statistic".to_string(),
+            "DF".to_string(),
+            "p-value".to_string(),
+            "Significant".to_string(),
+        ],
+        rows: vec![
+            test_row("Welch's t", &report.welch_t),
+            test_row("Mann-Whitney U", &report.mann_whitney_u),
+        ],
+    };
+    let summary = format!(
+        "Difference of the means ({} minus {}): {:+.4}",
+        report.samples[1].name, report.samples[0].name, report.mean_difference
+    );
+
+    if format == OutputFormat::Text {
+        println!("--- Comparing '{}' ---", report.column);
+        print!("{}", samples.to_text());
+        println!();
+        print!("{}", tests.to_text());
+        println!("{}", summary);
+    } else {
+        print!("{}", samples.to_markdown());
+        println!();
+        print!("{}", tests.to_markdown());
+        println!("\n{}", summary);
+    }
+    Ok(())
+}
+
+/// Prints a cross-tabulation with its row and column totals, and the chi-square test of
+/// independence.
+pub fn print_chi_square(format: OutputFormat, report: &ChiSquareReport) -> Result<()> {
+    if format.is_structured() {
+        println!("{}", serialize(format, report)?);
+        return Ok(());
+    }
+
+    let mut headers = vec![format!("{} / {}", report.row_column, report.column_column)];
+    headers.extend(report.column_values.iter().cloned());
+    headers.push("Total".to_string());
+    let mut rows: Vec<Vec<String>> = report
+        .row_values
+        .iter()
+        .zip(&report.counts)
+        .map(|(value, counts)| {
+            let mut row = vec![value.clone()];
+            row.extend(counts.iter().map(|c| c.to_string()));
+            row.push(counts.iter().sum::<usize>().to_string());
+            row
+        })
+        .collect();
+    let mut totals = vec!["Total".to_string()];
+    totals.extend((0..report.column_values.len()).map(|c| {
+        report
+            .counts
+            .iter()
+            .map(|row| row[c])
+            .sum::<usize>()
+            .to_string()
+    }));
+    totals.push(report.total.to_string());
+    rows.push(totals);
+    let table = Table { headers, rows };
+
+    let verdict = if report.significant {
+        "are associated"
+    } else {
+        "are consistent with being independent"
+    };
+    let mut summary = format!(
+        "Chi-square {:.4} ({} degrees of freedom), p-value {:.4}, Cramér's V {:.4}: the columns {} at the {} significance level",
+        report.chi_square,
+        report.degrees_of_freedom,
+        report.p_value,
+        report.cramers_v,
+        verdict,
+        report.significance
+    );
+    if report.sparse_cells > 0 {
+        summary.push_str(&format!(
+            "\nNote: {} cell(s) expect fewer than 5 rows, so the p-value may be unreliable",
+            report.sparse_cells
+        ));
+    }
+
+    if format == OutputFormat::Text {
+        println!(
+            "--- '{}' by '{}' ({} rows) ---",
+            report.row_column, report.column_column, report.total
+        );
+        print!("{}", table.to_text());
+        println!("{}", summary);
+    } else {
+        print!("{}", table.to_markdown());
+        println!("\n{}", summary);
+    }
+    Ok(())
+}
+
+/// Prints the autocorrelation at each lag, with a bar for its size in text output.
+pub fn print_autocorrelation(format: OutputFormat, acf: &Autocorrelation) -> Result<()> {
+    if format.is_structured() {
+        println!("{}", serialize(format, acf)?);
+        return Ok(());
+    }
+
+    let mut headers = vec![
+        "Lag".to_string(),
+        "ACF".to_string(),
+        "Significant".to_string(),
+    ];
+    if format == OutputFormat::Text {
+        headers.push(String::new());
+    }
+    let rows = acf
+        .lags
+        .iter()
+        .map(|lag| {
+            let mut row = vec![
+                lag.lag.to_string(),
+                format_opt(lag.acf),
+                if lag.significant { "yes" } else { "no" }.to_string(),
+            ];
+            if format == OutputFormat::Text {
+                let width = (lag.acf.unwrap_or(0.0).abs() * ACF_BAR_WIDTH as f64).round() as usize;
+                let bar = if lag.acf.unwrap_or(0.0) < 0.0 {
+                    "-"
+                } else {
+                    "+"
+                };
+                // Pad the bars, since the table right-aligns its columns.
+                row.push(format!("{:<w$}", bar.repeat(width), w = ACF_BAR_WIDTH));
+            }
+            row
+        })
+        .collect();
+    let table = Table { headers, rows };
+    let summary = format!(
+        "Coefficients beyond ±{:.4} are significant at about the 5% level",
+        acf.bound
+    );
+
+    if format == OutputFormat::Text {
+        match &acf.order_by {
+            Some(order_by) => println!(
+                "--- Autocorrelation of '{}' ordered by '{}' ({} values) ---",
+                acf.column, order_by, acf.count
+            ),
+            None => println!(
+                "--- Autocorrelation of '{}' ({} values) ---",
+                acf.column, acf.count
+            ),
+        }
+        print!("{}", table.to_text());
+        println!("{}", summary);
+    } else {
+        print!("{}", table.to_markdown());
+        println!("\n{}", summary);
+    }
+    Ok(())
+}
+
+/// Prints the number of rows. The text output is just the number, for use in scripts.
+pub fn print_count(format: OutputFormat, count: u64) -> Result<()> {
+    match format {
+        OutputFormat::Json | OutputFormat::Yaml | OutputFormat::Prom => println!(
+            "{}",
+            serialize(format, &serde_json::json!({ "rows": count }))?
+        ),
+        OutputFormat::Text => println!("{}", count),
+        OutputFormat::Markdown => {
+            let table = Table {
+                headers: vec!["Rows".to_string()],
+                rows: vec![vec![count.to_string()]],
+            };
+            print!("{}", table.to_markdown());
+        }
+    }
+    Ok(())
+}
+
+/// Prints how many rows are duplicated.
+pub fn print_duplicates(format: OutputFormat, report: &DuplicateReport) -> Result<()> {
+    let rows = [
+        ("Rows", report.rows),
+        ("Distinct", report.distinct),
+        ("Duplicates", report.duplicates),
+        ("Repeated", report.duplicated),
+    ];
+    let subject = if report.keys.is_empty() {
+        "rows".to_string()
+    } else {
+        let keys: Vec<String> = report.keys.iter().map(|k| format!("'{}'", k)).collect();
+        format!("rows by {}", keys.join(", "))
+    };
+    match format {
+        OutputFormat::Json | OutputFormat::Yaml | OutputFormat::Prom => {
+            println!("{}", serialize(format, report)?)
+        }
+        OutputFormat::Text => {
+            println!("--- Duplicate {} ---", subject);
+            for (label, value) in rows {
+                println!("{:<11} {}", format!("{}:", label), value);
+            }
+        }
+        OutputFormat::Markdown => {
+            let table = Table {
+                headers: vec!["Statistic".to_string(), "Count".to_string()],
+                rows: rows
+                    .into_iter()
+                    .map(|(label, value)| vec![label.to_string(), value.to_string()])
+                    .collect(),
+            };
+            print!("{}", table.to_markdown());
+        }
+    }
+    Ok(())
+}
+
+/// Prints the columns and column combinations that uniquely identify the rows.
+pub fn print_keys(format: OutputFormat, report: &KeyReport) -> Result<()> {
+    if format.is_structured() {
+        println!("{}", serialize(format, report)?);
+        return Ok(());
+    }
+    if report.candidates.is_empty() {
+        println!(
+            "No column or combination of up to {} columns uniquely identifies the {} rows.",
+            report.max_size, report.rows
+        );
+        return Ok(());
+    }
+
+    let table = Table {
+        headers: vec!["Columns".to_string(), "Size".to_string()],
+        rows: report
+            .candidates
+            .iter()
+            .map(|key| vec![key.join(", "), key.len().to_string()])
+            .collect(),
+    };
+    if format == OutputFormat::Text {
+        println!("--- Unique keys of {} rows ---", report.rows);
+    }
+    print!("{}", table.render(format));
+    Ok(())
+}
+
+/// Prints the change of every statistic since the baseline, marking those beyond their
+/// tolerance.
+pub fn print_drift(format: OutputFormat, report: &DriftReport) -> Result<()> {
+    if format.is_structured() {
+        println!("{}", serialize(format, report)?);
+        return Ok(());
+    }
+
+    let headers = [
+        "Column",
+        "Statistic",
+        "Baseline",
+        "Current",
+        "Change",
+        "Change %",
+        "Tolerance",
+        "Status",
+    ];
+    let table = Table {
+        headers: headers.iter().map(|h| h.to_string()).collect(),
+        rows: report
+            .drifts
+            .iter()
+            .map(|d| {
+                vec![
+                    d.subject.clone(),
+                    d.statistic.clone(),
+                    format!("{:.4}", d.baseline),
+                    format!("{:.4}", d.current),
+                    format!("{:+.4}", d.change),
+                    d.percent_change
+                        .map_or("N/A".to_string(), |p| format!("{:+.2}%", p)),
+                    d.tolerance.clone(),
+                    if d.exceeded { "DRIFT" } else { "ok" }.to_string(),
+                ]
+            })
+            .collect(),
+    };
+    if format == OutputFormat::Text {
+        println!("--- Drift from the baseline ---");
+    }
+    print!("{}", table.render(format));
+    for missing in &report.missing {
+        println!("Missing: {}", missing);
+    }
+    Ok(())
+}
+
+/// Prints the correlation between two columns.
+pub fn print_correlation(format: OutputFormat, correlation: &Correlation) -> Result<()> {
+    let rows = [
+        ("Rows", correlation.count.to_string()),
+        ("Pearson", format_opt(correlation.pearson)),
+        ("Spearman", format_opt(correlation.spearman)),
+        ("Cov", format_opt(correlation.covariance)),
+    ];
+    let rows = rows
+        .into_iter()
+        .filter(|(label, _)| *label != "Spearman" || correlation.spearman.is_some());
+    match format {
+        OutputFormat::Json | OutputFormat::Yaml | OutputFormat::Prom => {
+            println!("{}", serialize(format, correlation)?)
+        }
+        OutputFormat::Text => {
+            println!(
+                "--- Correlation of '{}' and '{}' ---",
+                correlation.x, correlation.y
+            );
+            for (label, value) in rows {
+                println!("{:<9} {}", format!("{}:", label), value);
+            }
+        }
+        OutputFormat::Markdown => {
+            let table = Table {
+                headers: vec![
+                    "Statistic".to_string(),
+                    format!("{} / {}", correlation.x, correlation.y),
+                ],
+                rows: rows
+                    .into_iter()
+                    .map(|(label, value)| vec![label.to_string(), value])
+                    .collect(),
+            };
+            print!("{}", table.to_markdown());
+        }
+    }
+    Ok(())
+}
+
+/// Prints the line fitted through two columns.
+pub fn print_regression(format: OutputFormat, regression: &Regression) -> Result<()> {
+    let rows = [
+        ("Rows", regression.count.to_string()),
+        ("Slope", format!("{:.4}", regression.slope)),
+        ("Intercept", format!("{:.4}", regression.intercept)),
+        ("R²", format!("{:.4}", regression.r_squared)),
+        ("Residual std", format_opt(regression.residual_std)),
+    ];
+    match format {
+        OutputFormat::Json | OutputFormat::Yaml | OutputFormat::Prom => {
+            println!("{}", serialize(format, regression)?)
+        }
+        OutputFormat::Text => {
+            println!(
+                "--- Regression of '{}' on '{}' ---",
+                regression.y, regression.x
+            );
+            for (label, value) in rows {
+                println!("{:<13} {}", format!("{}:", label), value);
+            }
+        }
+        OutputFormat::Markdown => {
+            let table = Table {
+                headers: vec![
+                    "Statistic".to_string(),
+                    format!("{} ~ {}", regression.y, regression.x),
+                ],
+                rows: rows
+                    .into_iter()
+                    .map(|(label, value)| vec![label.to_string(), value])
+                    .collect(),
+            };
+            print!("{}", table.to_markdown());
+        }
+    }
+    Ok(())
+}
+
+/// Prints a correlation matrix as a table with a row and a column per analyzed column.
+pub fn print_correlation_matrix(format: OutputFormat, matrix: &CorrelationMatrix) -> Result<()> {
+    if format.is_structured() {
+        println!("{}", serialize(format, matrix)?);
+        return Ok(());
+    }
+
+    let (name, coefficients) = match &matrix.spearman {
+        Some(spearman) => ("Spearman", spearman),
+        None => ("Pearson", &matrix.pearson),
+    };
+    let mut headers = vec![String::new()];
+    headers.extend(matrix.columns.iter().cloned());
+    let rows = matrix
+        .columns
+        .iter()
+        .zip(coefficients)
+        .map(|(column, values)| {
+            let mut row = vec![column.clone()];
+            row.extend(values.iter().map(|v| format_opt(*v)));
+            row
+        })
+        .collect();
+
+    if format == OutputFormat::Text {
+        println!("--- {} correlation matrix ---", name);
+    }
+    print!("{}", Table { headers, rows }.render(format));
+
+    let mut headers = vec![String::new()];
+    headers.extend(matrix.columns.iter().cloned());
+    let rows = matrix
+        .columns
+        .iter()
+        .zip(&matrix.covariance)
+        .map(|(column, values)| {
+            let mut row = vec![column.clone()];
+            row.extend(values.iter().map(|v| format_opt(*v)));
+            row
+        })
+        .collect();
+    if format == OutputFormat::Text {
+        println!("--- Covariance matrix ---");
+    } else {
+        println!();
+    }
+    print!("{}", Table { headers, rows }.render(format));
+    Ok(())
+}
+
+/// Prints the outliers found in a column, with their row numbers and values.
+pub fn print_outliers(format: OutputFormat, report: &OutlierReport) -> Result<()> {
+    if format.is_structured() {
+        println!("{}", serialize(format, report)?);
+        return Ok(());
+    }
+
+    let method = match report.method {
+        OutlierMethod::Iqr => format!("beyond {} IQRs", report.threshold),
+        OutlierMethod::Zscore => format!("beyond {} standard deviations", report.threshold),
+    };
+    let summary = format!(
+        "{} of {} values are {} (outside {:.4} to {:.4})",
+        report.outliers.len(),
+        report.count,
+        method,
+        report.lower,
+        report.upper
+    );
+    let table = Table {
+        headers: vec!["Row".to_string(), report.column.clone()],
+        rows: report
+            .outliers
+            .iter()
+            .map(|o| vec![o.row.to_string(), format!("{:.4}", o.value)])
+            .collect(),
+    };
+
+    if format == OutputFormat::Text {
+        println!("--- Outliers in '{}' ---", report.column);
+        println!("{}", summary);
+    } else {
+        println!("### Outliers in '{}'\n\n{}\n", report.column, summary);
+    }
+    if !report.outliers.is_empty() {
+        print!("{}", table.render(format));
+    }
+    Ok(())
+}
+
+/// Prints how many rows violate each rule, followed by the first violating rows of each
+/// violated rule.
+pub fn print_validation(format: OutputFormat, validation: &Validation) -> Result<()> {
+    let report = &validation.report;
+    if format.is_structured() {
+        println!("{}", serialize(format, report)?);
+        return Ok(());
+    }
+
+    let table = Table {
+        headers: vec![
+            "Column".to_string(),
+            "Rule".to_string(),
+            "Violations".to_string(),
+        ],
+        rows: report
+            .results
+            .iter()
+            .map(|r| vec![r.column.clone(), r.rule.clone(), r.violations.to_string()])
+            .collect(),
+    };
+    let failures = report.failures().count();
+    let summary = format!(
+        "{} of {} rules violated in {} rows",
+        failures,
+        report.results.len(),
+        report.rows
+    );
+    if format == OutputFormat::Text {
+        println!("--- Validation ---");
+        println!("{}", summary);
+    } else {
+        println!("### Validation\n\n{}\n", summary);
+    }
+    print!("{}", table.render(format));
+
+    for (result, samples) in report.results.iter().zip(&validation.samples) {
+        if samples.height() == 0 {
+            continue;
+        }
+        let title = format!(
+            "First {} of {} rows violating '{}' {}",
+            samples.height(),
+            result.violations,
+            result.column,
+            result.rule
+        );
+        if format == OutputFormat::Text {
+            println!("\n--- {} ---", title);
+        } else {
+            println!("\n#### {}\n", title);
+        }
+        print!("{}", dataframe_table(samples)?.render(format));
+    }
+    Ok(())
+}
+
+/// Prints the malformed rows found in the input.
+pub fn print_malformed(format: OutputFormat, report: &MalformedReport) -> Result<()> {
+    if format.is_structured() {
+        println!("{}", serialize(format, report)?);
+        return Ok(());
+    }
+
+    let mut summary = format!("{} of {} rows are malformed", report.malformed, report.rows);
+    if report.examples.len() < report.malformed {
+        summary.push_str(&format!(", showing the first {}", report.examples.len()));
+    }
+    let table = Table {
+        headers: vec![
+            "File".to_string(),
+            "Line".to_string(),
+            "Byte".to_string(),
+            "Problem".to_string(),
+        ],
+        rows: report
+            .examples
+            .iter()
+            .map(|row| {
+                vec![
+                    row.file.clone(),
+                    row.line.to_string(),
+                    row.offset.to_string(),
+                    row.problem.clone(),
+                ]
+            })
+            .collect(),
+    };
+    if format == OutputFormat::Text {
+        println!("--- Malformed rows ---");
+        println!("{}", summary);
+    } else {
+        println!("### Malformed rows\n\n{}\n", summary);
+    }
+    if !report.examples.is_empty() {
+        print!("{}", table.render(format));
+    }
+    Ok(())
+}
+
+/// Writes a DataFrame to a CSV file with a header row.
+pub fn write_csv(path: &str, df: &mut DataFrame) -> Result<()> {
+    let file =
+        std::fs::File::create(path).with_context(|| format!("Failed to create '{}'", path))?;
+    CsvWriter::new(file).finish(df)?;
+    Ok(())
+}
+
+/// The file format of results written to a file.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FileFormat {
+    /// Comma-separated values with a header row.
+    #[default]
+    Csv,
+    /// Apache Parquet, keeping the column types.
+    Parquet,
+}
+
+impl FileFormat {
+    /// Picks the format from a file's extension: Parquet for `.parquet`, CSV otherwise.
+    pub fn from_path(path: &str) -> Self {
+        let extension = std::path::Path::new(path).extension();
+        if extension.is_some_and(|ext| ext.eq_ignore_ascii_case("parquet")) {
+            FileFormat::Parquet
+        } else {
+            FileFormat::Csv
+        }
+    }
+}
+
+/// Writes a DataFrame to a file in the given format.
+pub fn write_dataframe(path: &str, df: &mut DataFrame, format: FileFormat) -> Result<()> {
+    match format {
+        FileFormat::Csv => write_csv(path, df),
+        FileFormat::Parquet => {
+            let file = std::fs::File::create(path)
+                .with_context(|| format!("Failed to create '{}'", path))?;
+            ParquetWriter::new(file).finish(df)?;
+            Ok(())
+        }
+    }
+}
+
+/// Appends the statistics to a file, one row per file, column and group, with the time
+/// of the run in the first column. The file is created if it doesn't exist.
+///
+/// Statistics of all input files combined are recorded with `input` as their file.
+///
+/// Every statistic gets a column, with nested ones such as `moments.skew` flattened and
+/// quantiles named like `q0.25`. When a run has statistics the file has no column for
+/// yet, the file is rewritten with the new columns, empty in the earlier rows. Parquet
+/// files can't be appended to, so they are always rewritten.
+pub fn append_stats(
+    path: &str,
+    format: FileFormat,
+    input: &str,
+    stats: &[SelectedStats],
+    timestamp: &str,
+) -> Result<()> {
+    let mut df = stats_dataframe(stats, input, timestamp)?;
+    if !std::path::Path::new(path).exists() {
+        return write_dataframe(path, &mut df, format);
+    }
+
+    let existing = match format {
+        // Read every column as text, so earlier values are written back exactly as they were.
+        FileFormat::Csv => CsvReadOptions::default()
+            .with_infer_schema_length(Some(0))
+            .try_into_reader_with_file_path(Some(path.into()))?
+            .finish(),
+        FileFormat::Parquet => ParquetReader::new(
+            std::fs::File::open(path).with_context(|| format!("Failed to open '{}'", path))?,
+        )
+        .finish(),
+    }
+    .with_context(|| format!("Failed to read '{}'", path))?;
+    let same_columns = df
+        .get_column_names()
+        .iter()
+        .all(|name| existing.get_column_names().contains(name));
+    if format == FileFormat::Csv && same_columns {
+        let mut df = df.select(existing.get_column_names_str())?;
+        let file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open '{}'", path))?;
+        CsvWriter::new(file).include_header(false).finish(&mut df)?;
+        return Ok(());
+    }
+    let args = UnionArgs {
+        diagonal: true,
+        to_supertypes: true,
+        ..Default::default()
+    };
+    let mut combined = concat([existing.lazy(), df.lazy()], args)?.collect()?;
+    write_dataframe(path, &mut combined, format)
+}
+
+/// Builds a DataFrame of the statistics with a row per file, column and group.
+fn stats_dataframe(stats: &[SelectedStats], input: &str, timestamp: &str) -> Result<DataFrame> {
+    let records = stats
+        .iter()
+        .map(|s| stats_record(s, input, timestamp))
+        .collect::<Result<Vec<_>>>()?;
+    let mut names: Vec<&str> = Vec::new();
+    for (name, _) in records.iter().flatten() {
+        if !names.contains(&name.as_str()) {
+            names.push(name);
+        }
+    }
+    let columns: Vec<Column> = names
+        .iter()
+        .map(|&name| {
+            let values: Vec<&serde_json::Value> = records
+                .iter()
+                .map(|record| {
+                    record
+                        .iter()
+                        .find(|(n, _)| n == name)
+                        .map_or(&serde_json::Value::Null, |(_, value)| value)
+                })
+                .collect();
+            json_column(name, &values)
+        })
+        .collect();
+    Ok(DataFrame::new(columns)?)
+}
+
+/// Builds a column from JSON values: integers or floats when every value is one, and
+/// text otherwise.
+fn json_column(name: &str, values: &[&serde_json::Value]) -> Column {
+    let name = PlSmallStr::from(name);
+    if values.iter().all(|v| v.is_null() || v.is_i64()) {
+        Column::new(name, values.iter().map(|v| v.as_i64()).collect::<Vec<_>>())
+    } else if values.iter().all(|v| v.is_null() || v.is_number()) {
+        Column::new(name, values.iter().map(|v| v.as_f64()).collect::<Vec<_>>())
+    } else {
+        let text = values.iter().map(|value| match value {
+            serde_json::Value::Null => None,
+            serde_json::Value::String(s) => Some(s.clone()),
+            value => Some(value.to_string()),
+        });
+        Column::new(name, text.collect::<Vec<_>>())
+    }
+}
+
+/// Flattens the statistics of a column into named cells for a row.
+fn stats_record(
+    stats: &SelectedStats,
+    input: &str,
+    timestamp: &str,
+) -> Result<Vec<(String, serde_json::Value)>> {
+    let group = stats.group.as_ref();
+    let file = stats.file.as_deref().unwrap_or(input);
+    let mut record = vec![
+        ("timestamp".to_string(), timestamp.into()),
+        ("file".to_string(), file.into()),
+        ("column".to_string(), stats.column.as_str().into()),
+        (
+            "group_by".to_string(),
+            group.map(|g| g.column.as_str()).into(),
+        ),
+        (
+            "group".to_string(),
+            group.and_then(|g| g.value.as_deref()).into(),
+        ),
+    ];
+    let serde_json::Value::Object(fields) = serde_json::to_value(stats)? else {
+        bail!("Statistics must serialize to a JSON object");
+    };
+    for (name, value) in fields {
+        match (name.as_str(), value) {
+            ("column" | "file" | "group", _) => {}
+            ("quantiles", serde_json::Value::Array(quantiles)) => {
+                for q in quantiles {
+                    record.push((format!("q{}", q["quantile"]), q["value"].clone()));
+                }
+            }
+            (_, serde_json::Value::Object(nested)) => {
+                for (key, value) in nested {
+                    record.push((format!("{}.{}", name, key), value));
+                }
+            }
+            (_, value) => record.push((name, value)),
+        }
+    }
+    Ok(record)
+}
+
+/// Builds the summary of [`print_describe`] as a DataFrame, with a row per statistic and
+/// a column per analyzed column.
+pub fn describe_dataframe(stats: &[SelectedStats]) -> Result<DataFrame> {
+    let mut labels = vec!["count", "mean", "std", "min"];
+    let quantile_labels: Vec<String> = stats.first().map_or_else(Vec::new, |first| {
+        first
+            .quantiles
+            .iter()
+            .map(|q| format!("{}%", q.quantile * 100.0))
+            .collect()
+    });
+    labels.extend(quantile_labels.iter().map(String::as_str));
+    labels.push("max");
+
+    let mut columns = vec![Column::new("statistic".into(), &labels)];
+    for s in stats {
+        let mut values = vec![Some(s.count as f64), s.mean, s.std, s.min];
+        values.extend(s.quantiles.iter().map(|q| q.value));
+        values.push(s.max);
+        columns.push(Column::new(s.column.as_str().into(), values));
+    }
+    Ok(DataFrame::new(columns)?)
+}
+
+/// Prints the columns of the input with their types and example values.
+pub fn print_schema(format: OutputFormat, columns: &[ColumnInfo]) -> Result<()> {
+    if format.is_structured() {
+        println!("{}", serialize(format, columns)?);
+        return Ok(());
+    }
+
+    let table = Table {
+        headers: vec![
+            "Column".to_string(),
+            "Type".to_string(),
+            "Example".to_string(),
+        ],
+        rows: columns
+            .iter()
+            .map(|c| {
+                vec![
+                    c.name.clone(),
+                    c.dtype.clone(),
+                    c.example.clone().unwrap_or_else(|| "null".to_string()),
+                ]
+            })
+            .collect(),
+    };
+    print!("{}", table.render(format));
+    Ok(())
+}
+
+/// Helper to format Option<f64> values consistently to 4 decimal places.
+pub(crate) fn format_opt(val: Option<f64>) -> String {
+    val.map(|v| format!("{:.4}", v))
+        .unwrap_or_else(|| "N/A".to_string())
+}
+
+/// Formats an optional text value, such as a date, like [`format_opt`] does numbers.
+fn format_text(val: &Option<String>) -> String {
+    val.clone().unwrap_or_else(|| "N/A".to_string())
+}
+
+/// Prints a DataFrame, such as the result of a SQL query, in the requested format.
+///
+/// JSON output is an array with one object per row, keyed by column name.
+pub fn print_dataframe(format: OutputFormat, df: &DataFrame) -> Result<()> {
+    if format.is_structured() {
+        let columns = df.get_columns();
+        let mut rows = Vec::with_capacity(df.height());
+        for row in 0..df.height() {
+            let mut object = serde_json::Map::new();
+            for column in columns {
+                object.insert(
+                    column.name().to_string(),
+                    any_value_to_json(column.get(row)?),
+                );
+            }
+            rows.push(serde_json::Value::Object(object));
+        }
+        println!("{}", serialize(format, &rows)?);
+        return Ok(());
+    }
+    print!("{}", dataframe_table(df)?.render(format));
+    Ok(())
+}
+
+/// Formats the values of a DataFrame as a table, showing missing values as `null`.
+fn dataframe_table(df: &DataFrame) -> Result<Table> {
+    let columns = df.get_columns();
+    let mut rows = Vec::with_capacity(df.height());
+    for row in 0..df.height() {
+        let cells = columns
+            .iter()
+            .map(|column| {
+                Ok(any_value_to_string(column.get(row)?).unwrap_or_else(|| "null".to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        rows.push(cells);
+    }
+    Ok(Table {
+        headers: columns.iter().map(|c| c.name().to_string()).collect(),
+        rows,
+    })
+}
+
+/// Converts a single value to JSON, keeping numbers and booleans unquoted.
+fn any_value_to_json(value: AnyValue) -> serde_json::Value {
+    match value {
+        AnyValue::Boolean(b) => b.into(),
+        AnyValue::Float32(f) => f.into(),
+        AnyValue::Float64(f) => f.into(),
+        v if v.is_signed_integer() => v.extract::<i64>().into(),
+        v if v.is_unsigned_integer() => v.extract::<u64>().into(),
+        v => any_value_to_string(v).into(),
+    }
+}
+
+/// Prints the statistics that changed between two runs over the same columns, such as
+/// successive runs in watch mode.
+///
+/// Results are matched up by column and group; anything without a counterpart in the
+/// previous run is skipped.
+pub fn print_deltas(
+    format: OutputFormat,
+    previous: &[SelectedStats],
+    current: &[SelectedStats],
+) -> Result<()> {
+    let mut rows = Vec::new();
+    for stats in current {
+        let group_value = |s: &SelectedStats| s.group.as_ref().map(|g| g.value.clone());
+        let Some(before) = previous.iter().find(|p| {
+            p.column == stats.column && p.file == stats.file && group_value(p) == group_value(stats)
+        }) else {
+            continue;
+        };
+
+        let mut subject = stats.column.clone();
+        if let Some(group) = &stats.group {
+            let value = group.value.as_deref().unwrap_or("null");
+            subject = format!("{} ({}={})", subject, group.column, value);
+        }
+        if let Some(file) = &stats.file {
+            subject = format!("{} in {}", subject, file);
+        }
+        let before_rows = stat_rows(before);
+        for (label, value) in stat_rows(stats) {
+            let Some((_, old)) = before_rows.iter().find(|(l, _)| *l == label) else {
+                continue;
+            };
+            if *old == value {
+                continue;
+            }
+            // Show the difference for numbers; other values (like modes) just show both.
+            let change = match (old.parse::<f64>(), value.parse::<f64>()) {
+                (Ok(old), Ok(new)) => format!("{:+.4}", new - old),
+                _ => String::new(),
+            };
+            rows.push(vec![subject.clone(), label, old.clone(), value, change]);
+        }
+    }
+
+    match format {
+        OutputFormat::Json | OutputFormat::Yaml => {
+            let changes: Vec<_> = rows
+                .iter()
+                .map(|row| {
+                    serde_json::json!({
+                        "column": row[0],
+                        "statistic": row[1],
+                        "previous": row[2],
+                        "current": row[3],
+                        "change": row[4],
+                    })
+                })
+                .collect();
+            println!("{}", serialize(format, &changes)?);
+        }
+        _ if rows.is_empty() => println!("No statistics changed since the previous run."),
+        _ => {
+            let headers = ["Column", "Statistic", "Previous", "Current", "Change"];
+            let table = Table {
+                headers: headers.iter().map(|h| h.to_string()).collect(),
+                rows,
+            };
+            print!("{}", table.render(format));
+        }
+    }
+    Ok(())
+}
+
+/// Prints the statistics of the same columns in two inputs side by side, with the change
+/// from the left to the right one.
+pub fn print_diff(
+    format: OutputFormat,
+    names: [&str; 2],
+    left: &[SelectedStats],
+    right: &[SelectedStats],
+) -> Result<()> {
+    let mut columns = Vec::new();
+    for (before, after) in left.iter().zip(right) {
+        let after_rows = stat_rows(after);
+        let rows: Vec<Vec<String>> = stat_rows(before)
+            .into_iter()
+            .map(|(label, old)| {
+                let new = value_of(&after_rows, &label);
+                // Show the difference for numbers; other values (like modes) just show both.
+                let (change, percent) = match (old.parse::<f64>(), new.parse::<f64>()) {
+                    (Ok(old), Ok(new)) => (
+                        format!("{:+.4}", new - old),
+                        if old == 0.0 {
+                            "N/A".to_string()
+                        } else {
+                            format!("{:+.2}%", (new - old) / old.abs() * 100.0)
+                        },
+                    ),
+                    _ => (String::new(), String::new()),
+                };
+                vec![label, old, new, change, percent]
+            })
+            .collect();
+        columns.push((&before.column, rows));
+    }
+
+    if format.is_structured() {
+        let columns: Vec<_> = columns
+            .iter()
+            .map(|(column, rows)| {
+                let changes: Vec<_> = rows
+                    .iter()
+                    .map(|row| {
+                        serde_json::json!({
+                            "statistic": row[0],
+                            "left": row[1],
+                            "right": row[2],
+                            "change": row[3],
+                            "change_percent": row[4],
+                        })
+                    })
+                    .collect();
+                serde_json::json!({ "column": column, "changes": changes })
+            })
+            .collect();
+        let diff = serde_json::json!({ "left": names[0], "right": names[1], "columns": columns });
+        println!("{}", serialize(format, &diff)?);
+        return Ok(());
+    }
+
+    for (column, rows) in columns {
+        if format == OutputFormat::Text {
+            println!("--- Statistics for '{}' ---", column);
+        }
+        let table = Table {
+            headers: vec![
+                "Statistic".to_string(),
+                names[0].to_string(),
+                names[1].to_string(),
+                "Change".to_string(),
+                "Change %".to_string(),
+            ],
+            rows,
+        };
+        print!("{}", table.render(format));
+    }
+    Ok(())
+}
+
+/// Formats the mode, noting when tied values were left out.
+fn format_mode(mode: &Mode) -> String {
+    if mode.values.is_empty() {
+        return "N/A".to_string();
+    }
+    let values = mode.values.join(", ");
+    if mode.count > mode.values.len() {
+        format!(
+            "{} (first {} of {} tied values)",
+            values,
+            mode.values.len(),
+            mode.count
+        )
+    } else {
+        values
+    }
+}
+
+/// Lists the labelled, formatted statistics for a column in display order.
+///
+/// Every human-readable format is built from this list, so a new statistic only
+/// needs to be added here to appear in all of them.
+pub fn stat_rows(stats: &SelectedStats) -> Vec<(String, String)> {
+    if let Some(dates) = &stats.dates {
+        return date_rows(stats, dates);
+    }
+    if let Some(text) = &stats.text {
+        return text_rows(stats, text);
+    }
+    if let Some(boolean) = &stats.boolean {
+        return boolean_rows(stats, boolean);
+    }
+
+    let mut rows = vec![
+        ("Count".to_string(), stats.count.to_string()),
+        ("Nulls".to_string(), stats.null_count.to_string()),
+        ("Null %".to_string(), format_opt(stats.null_percent)),
+        (
+            "Nulls after cast".to_string(),
+            stats.numeric_null_count.to_string(),
+        ),
+        (
+            "Null % after cast".to_string(),
+            format_opt(stats.numeric_null_percent),
+        ),
+        ("Unique".to_string(), stats.n_unique.to_string()),
+        ("Min".to_string(), format_opt(stats.min)),
+        ("Max".to_string(), format_opt(stats.max)),
+        ("Sum".to_string(), format_opt(stats.sum)),
+        ("Mean".to_string(), format_opt(stats.mean)),
+        ("Median".to_string(), format_opt(stats.median)),
+        ("Std".to_string(), format_opt(stats.std)),
+        ("Var".to_string(), format_opt(stats.var)),
+    ];
+    rows.extend(
+        stats
+            .quantiles
+            .iter()
+            .map(|q| (format!("Q{}", q.quantile), format_opt(q.value))),
+    );
+    if let Some(weighted) = &stats.weighted {
+        rows.push(("Weighted Sum".to_string(), format_opt(weighted.sum)));
+        rows.push(("Weighted Mean".to_string(), format_opt(weighted.mean)));
+    }
+    if let Some(trimmed) = &stats.trimmed {
+        rows.push((
+            format!("Trimmed Mean ({})", trimmed.trim),
+            format_opt(trimmed.mean),
+        ));
+    }
+    if let Some(winsorized) = &stats.winsorized {
+        rows.push((
+            format!("Winsorized Mean ({})", winsorized.trim),
+            format_opt(winsorized.mean),
+        ));
+    }
+    if let Some(confidence) = &stats.confidence {
+        // Rounded so that levels like 0.57 don't print as 56.99999999999999%.
+        let level = (confidence.level * 10_000.0).round() / 100.0;
+        rows.push(("SE".to_string(), format_opt(confidence.std_error)));
+        rows.push((format!("{}% CI Low", level), format_opt(confidence.lower)));
+        rows.push((format!("{}% CI High", level), format_opt(confidence.upper)));
+    }
+    if let Some(bootstrap) = &stats.bootstrap {
+        let level = (bootstrap.level * 10_000.0).round() / 100.0;
+        rows.push(("Resamples".to_string(), bootstrap.resamples.to_string()));
+        for interval in &bootstrap.intervals {
+            let statistic = match (interval.quantile, interval.statistic.as_str()) {
+                (Some(q), _) => format!("Q{}", q),
+                (None, "mean") => "Mean".to_string(),
+                (None, "median") => "Median".to_string(),
+                (None, other) => other.to_string(),
+            };
+            rows.push((
+                format!("{}% Boot {} Low", level, statistic),
+                format_opt(Some(interval.lower)),
+            ));
+            rows.push((
+                format!("{}% Boot {} High", level, statistic),
+                format_opt(Some(interval.upper)),
+            ));
+        }
+    }
+    if let Some(mad) = &stats.mad {
+        rows.push(("MAD".to_string(), format_opt(mad.mad)));
+        rows.push(("MAD (scaled)".to_string(), format_opt(mad.scaled)));
+    }
+    if let Some(extended) = &stats.extended {
+        rows.push(("Geo Mean".to_string(), format_opt(extended.geometric_mean)));
+        rows.push(("Harm Mean".to_string(), format_opt(extended.harmonic_mean)));
+    }
+    if let Some(moments) = &stats.moments {
+        rows.push(("Skew".to_string(), format_opt(moments.skew)));
+        rows.push(("Kurtosis".to_string(), format_opt(moments.kurtosis)));
+    }
+    if let Some(mode) = &stats.mode {
+        rows.push(("Mode".to_string(), format_mode(mode)));
+    }
+    if let Some(thresholds) = &stats.thresholds {
+        for t in &thresholds.thresholds {
+            rows.push((format!("< {}", t.threshold), t.below.to_string()));
+            rows.push((format!(">= {}", t.threshold), t.at_or_above.to_string()));
+        }
+        // The outer buckets repeat the counts of the lowest and highest thresholds.
+        for bucket in &thresholds.buckets {
+            if let (Some(lower), Some(upper)) = (bucket.lower, bucket.upper) {
+                rows.push((format!("[{}, {})", lower, upper), bucket.count.to_string()));
+            }
+        }
+    }
+    rows
+}
+
+/// Lists the statistics of a date or datetime column: its range and counts per year and
+/// month.
+fn date_rows(stats: &SelectedStats, dates: &DateStats) -> Vec<(String, String)> {
+    let mut rows = vec![
+        ("Count".to_string(), stats.count.to_string()),
+        ("Nulls".to_string(), stats.null_count.to_string()),
+        ("Null %".to_string(), format_opt(stats.null_percent)),
+        ("Unique".to_string(), stats.n_unique.to_string()),
+        ("Min".to_string(), format_text(&dates.min)),
+        ("Max".to_string(), format_text(&dates.max)),
+        ("Span (days)".to_string(), format_opt(dates.span_days)),
+    ];
+    if let Some(mode) = &stats.mode {
+        rows.push(("Mode".to_string(), format_mode(mode)));
+    }
+    for period in dates.years.iter().chain(&dates.months) {
+        rows.push((period.period.clone(), period.count.to_string()));
+    }
+    rows
+}
+
+/// Lists the statistics of a text column.
+fn text_rows(stats: &SelectedStats, text: &TextStats) -> Vec<(String, String)> {
+    let length = |l: Option<usize>| l.map_or_else(|| "N/A".to_string(), |l| l.to_string());
+    let mut rows = vec![
+        ("Count".to_string(), stats.count.to_string()),
+        ("Nulls".to_string(), stats.null_count.to_string()),
+        ("Null %".to_string(), format_opt(stats.null_percent)),
+        ("Unique".to_string(), stats.n_unique.to_string()),
+        ("Empty".to_string(), text.empty_count.to_string()),
+        ("Min Length".to_string(), length(text.min_length)),
+        ("Max Length".to_string(), length(text.max_length)),
+        ("Mean Length".to_string(), format_opt(text.mean_length)),
+    ];
+    if let Some(mode) = &stats.mode {
+        rows.push(("Mode".to_string(), format_mode(mode)));
+    }
+    rows
+}
+
+/// Lists the statistics of a boolean column.
+fn boolean_rows(stats: &SelectedStats, boolean: &BooleanStats) -> Vec<(String, String)> {
+    // Rounded so that levels like 0.57 don't print as 56.99999999999999%.
+    let level = (boolean.level * 10_000.0).round() / 100.0;
+    let mut rows = vec![
+        ("Count".to_string(), stats.count.to_string()),
+        ("Nulls".to_string(), stats.null_count.to_string()),
+        ("Null %".to_string(), format_opt(stats.null_percent)),
+        ("True".to_string(), boolean.true_count.to_string()),
+        ("False".to_string(), boolean.false_count.to_string()),
+        ("True Ratio".to_string(), format_opt(boolean.true_ratio)),
+        (format!("{}% CI Low", level), format_opt(boolean.lower)),
+        (format!("{}% CI High", level), format_opt(boolean.upper)),
+    ];
+    if let Some(mode) = &stats.mode {
+        rows.push(("Mode".to_string(), format_mode(mode)));
+    }
+    rows
+}
+
+/// Collects the labels of several lists of statistics, in order of first appearance.
+///
+/// Columns of different types, and date columns covering different months, don't share
+/// the same statistics, so tables combining them leave the missing ones blank.
+fn all_labels(lists: &[Vec<(String, String)>]) -> Vec<String> {
+    let mut labels: Vec<String> = Vec::new();
+    for (label, _) in lists.iter().flatten() {
+        if !labels.contains(label) {
+            labels.push(label.clone());
+        }
+    }
+    labels
+}
+
+/// Looks up a statistic by label, blank when the list doesn't have it.
+fn value_of(list: &[(String, String)], label: &str) -> String {
+    list.iter()
+        .find(|(l, _)| l == label)
+        .map_or_else(String::new, |(_, value)| value.clone())
+}
+
+/// Builds a table with one row per group for a single column's grouped statistics.
+fn group_table(stats: &[SelectedStats]) -> Table {
+    let group_column = stats[0].group.as_ref().map_or("group", |g| &g.column);
+    let lists: Vec<_> = stats.iter().map(stat_rows).collect();
+    let labels = all_labels(&lists);
+    let mut headers = vec![group_column.to_string()];
+    headers.extend(labels.iter().cloned());
+
+    let rows = stats
+        .iter()
+        .zip(&lists)
+        .map(|(s, list)| {
+            let group_value = s
+                .group
+                .as_ref()
+                .and_then(|g| g.value.clone())
+                .unwrap_or_else(|| "null".to_string());
+            let mut row = vec![group_value];
+            row.extend(labels.iter().map(|label| value_of(list, label)));
+            row
+        })
+        .collect();
+
+    Table { headers, rows }
+}
+
+/// Builds a table with one row per statistic and one column per analyzed column.
+fn columns_table(stats: &[SelectedStats]) -> Table {
+    let mut headers = vec!["Statistic".to_string()];
+    headers.extend(stats.iter().map(|s| match &s.file {
+        Some(file) => format!("{} ({})", s.column, file),
+        None => s.column.clone(),
+    }));
+
+    let columns: Vec<_> = stats.iter().map(stat_rows).collect();
+    let rows = all_labels(&columns)
+        .into_iter()
+        .map(|label| {
+            let mut row = vec![label.clone()];
+            row.extend(columns.iter().map(|c| value_of(c, &label)));
+            row
+        })
+        .collect();
+
+    Table { headers, rows }
+}
+
+/// Prints the results line by line, one block per column.
+///
+/// Grouped statistics are printed as one table per column with a row for each group.
+fn print_text(stats: &[SelectedStats], anova: &[Anova]) {
+    println!("Output for rust-polars");
+    if let Some(sampling) = stats.first().and_then(|s| s.sample.as_ref()) {
+        println!("Sample: {}", sampling);
+    }
+    for column_stats in stats.chunk_by(same_table) {
+        match &column_stats[0].group {
+            Some(group) => {
+                println!(
+                    "--- Statistics for '{}' by '{}'{} ---",
+                    column_stats[0].column,
+                    group.column,
+                    file_suffix(&column_stats[0])
+                );
+                print!("{}", group_table(column_stats).to_text());
+                if let Some(anova) = anova_of(column_stats, anova) {
+                    println!("{}", anova_summary(anova));
+                }
+            }
+            None => column_stats.iter().for_each(print_text_block),
+        }
+    }
+}
+
+/// Prints the statistics block for a single column.
+fn print_text_block(stats: &SelectedStats) {
+    println!(
+        "--- Statistics for '{}'{} ---",
+        stats.column,
+        file_suffix(stats)
+    );
+    for (label, value) in stat_rows(stats) {
+        println!("{:<6} {}", format!("{}:", label), value);
+    }
+}
+
+/// Prints the results as GitHub-flavored Markdown tables.
+///
+/// Ungrouped statistics share one table with a column per analyzed column;
+/// grouped statistics get a headed table per column with a row for each group.
+fn print_markdown(stats: &[SelectedStats], anova: &[Anova]) {
+    if let Some(sampling) = stats.first().and_then(|s| s.sample.as_ref()) {
+        println!("Sample: {}\n", sampling);
+    }
+    if stats.first().is_none_or(|s| s.group.is_none()) {
+        print!("{}", columns_table(stats).to_markdown());
+        return;
+    }
+
+    for (i, column_stats) in stats.chunk_by(same_table).enumerate() {
+        if i > 0 {
+            println!();
+        }
+        let group_column = column_stats[0].group.as_ref().map_or("", |g| &g.column);
+        println!(
+            "### Statistics for '{}' by '{}'{}\n",
+            column_stats[0].column,
+            group_column,
+            file_suffix(&column_stats[0])
+        );
+        print!("{}", group_table(column_stats).to_markdown());
+        if let Some(anova) = anova_of(column_stats, anova) {
+            println!("\n{}", anova_summary(anova));
+        }
+    }
+}
+
+/// Returns whether two sets of grouped statistics belong in the same table: those of
+/// the same column in the same file.
+fn same_table(a: &SelectedStats, b: &SelectedStats) -> bool {
+    a.column == b.column && a.file == b.file
+}
+
+/// Summarizes the analysis of variance of a column across its groups in a line.
+/// Finds the analysis of variance of the column and grouping of a group table, which
+/// covers all input files combined.
+fn anova_of<'a>(column_stats: &[SelectedStats], anova: &'a [Anova]) -> Option<&'a Anova> {
+    let first = &column_stats[0];
+    let group = first.group.as_ref()?;
+    if first.file.is_some() {
+        return None;
+    }
+    anova
+        .iter()
+        .find(|a| a.column == first.column && a.group_by == group.column)
+}
+
+fn anova_summary(anova: &Anova) -> String {
+    format!(
+        "One-way ANOVA: F = {:.4} (DF {}, {}), p-value = {:.4}, {}",
+        anova.f_statistic,
+        anova.df_between,
+        anova.df_within,
+        anova.p_value,
+        if anova.significant {
+            "significant"
+        } else {
+            "not significant"
+        }
+    )
+}
+
+/// Describes the file the statistics belong to, for titles, or nothing for the
+/// statistics of all files combined.
+fn file_suffix(stats: &SelectedStats) -> String {
+    match &stats.file {
+        Some(file) => format!(" in '{}'", file),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,26 @@
+//! Quick looks at the input: its first or last rows, and its number of rows.
+
+use anyhow::Result;
+use polars::prelude::*;
+
+use crate::engine::collect;
+
+/// Returns the first `n` rows. Only as much of the input as needed is read.
+pub fn head(lf: LazyFrame, n: usize) -> Result<DataFrame> {
+    collect(lf.limit(n as IdxSize))
+}
+
+/// Returns the last `n` rows.
+pub fn tail(lf: LazyFrame, n: usize) -> Result<DataFrame> {
+    collect(lf.tail(n as IdxSize))
+}
+
+/// Returns the number of rows, without parsing or casting any values.
+///
+/// A plan that only counts rows lets Polars count the lines of a CSV file directly
+/// instead of reading its columns.
+pub fn count_rows(lf: LazyFrame) -> Result<u64> {
+    let df = collect(lf.select([len()]))?;
+    let count = df.get_columns()[0].get(0)?.extract::<u64>();
+    Ok(count.unwrap_or(0))
+}
@@ -0,0 +1,151 @@
+//! Data profiles: an overview of every column of the input, such as a lightweight
+//! pandas-profiling report.
+//!
+//! A profile combines the other analyses in this crate. Every column gets its null rate,
+//! distinct count and most frequent values; numeric columns also get their statistics
+//! and a histogram.
+
+use anyhow::Result;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::collect;
+use crate::frequency::{FrequencyTable, compute_frequencies};
+use crate::histogram::{Histogram, HistogramOptions, compute_histogram};
+use crate::stats::{SelectedStats, StatsOptions, compute_stats};
+
+/// The quartiles calculated for every numeric column.
+const QUARTILES: [f64; 3] = [0.25, 0.5, 0.75];
+
+/// Options controlling what a profile includes.
+#[derive(Debug, Clone)]
+pub struct ProfileOptions {
+    /// The number of histogram bins for numeric columns.
+    pub bins: usize,
+    /// The number of most frequent values listed per column.
+    pub top_n: usize,
+}
+
+impl Default for ProfileOptions {
+    fn default() -> Self {
+        Self { bins: 10, top_n: 5 }
+    }
+}
+
+/// The profile of a whole table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    /// A description of where the data came from, such as the file name.
+    pub source: String,
+    /// The number of rows.
+    pub rows: usize,
+    /// The profile of each column, in file order.
+    pub columns: Vec<ColumnProfile>,
+}
+
+/// The profile of a single column.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnProfile {
+    /// The name of the column.
+    pub name: String,
+    /// The column's type, as inferred or overridden.
+    pub dtype: String,
+    /// The number of missing values.
+    pub null_count: usize,
+    /// The percentage of rows that are missing, or `None` when there are no rows.
+    pub null_percent: Option<f64>,
+    /// The number of distinct non-null values.
+    pub n_unique: usize,
+    /// The statistics of a numeric column, including its quartiles.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<SelectedStats>,
+    /// The distribution of a numeric column, unless it only holds nulls.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub histogram: Option<Histogram>,
+    /// The most frequent values.
+    pub top_values: FrequencyTable,
+}
+
+/// Profiles every column of the input.
+///
+/// The null and distinct counts of all columns and the statistics of all numeric columns
+/// take a pass each; the frequency tables and histograms take one or two passes per
+/// column.
+pub fn compute_profile(
+    mut lf: LazyFrame,
+    source: &str,
+    options: &ProfileOptions,
+) -> Result<Profile> {
+    let schema = lf.collect_schema()?;
+
+    let mut counts = vec![len().alias("rows")];
+    for (i, name) in schema.iter_names().enumerate() {
+        counts.push(col(name.clone()).null_count().alias(format!("{}_nulls", i)));
+        counts.push(
+            col(name.clone())
+                .drop_nulls()
+                .n_unique()
+                .alias(format!("{}_unique", i)),
+        );
+    }
+    let counts_df = collect(lf.clone().select(counts))?;
+    let get_count = |name: &str| -> Result<usize> {
+        Ok(counts_df.column(name)?.get(0)?.try_extract::<u32>()? as usize)
+    };
+    let rows = get_count("rows")?;
+
+    let numeric: Vec<String> = schema
+        .iter()
+        .filter(|(_, dtype)| dtype.is_primitive_numeric())
+        .map(|(name, _)| name.to_string())
+        .collect();
+    let mut stats = if numeric.is_empty() {
+        Vec::new()
+    } else {
+        let stats_options = StatsOptions {
+            columns: numeric,
+            quantiles: QUARTILES.to_vec(),
+            ..Default::default()
+        };
+        compute_stats(lf.clone(), &stats_options)?
+    }
+    .into_iter();
+
+    let mut columns = Vec::with_capacity(schema.len());
+    for (i, (name, dtype)) in schema.iter().enumerate() {
+        let null_count = get_count(&format!("{}_nulls", i))?;
+        let (stats, histogram) = if dtype.is_primitive_numeric() {
+            let stats = stats.next();
+            let has_values = stats.as_ref().is_some_and(|s| s.count > 0);
+            let histogram = if has_values {
+                let histogram_options = HistogramOptions {
+                    bins: options.bins,
+                    ..Default::default()
+                };
+                Some(compute_histogram(lf.clone(), name, &histogram_options)?)
+            } else {
+                None
+            };
+            (stats, histogram)
+        } else {
+            (None, None)
+        };
+
+        columns.push(ColumnProfile {
+            name: name.to_string(),
+            dtype: dtype.to_string(),
+            null_count,
+            null_percent: (rows > 0).then(|| null_count as f64 / rows as f64 * 100.0),
+            n_unique: get_count(&format!("{}_unique", i))?,
+            stats,
+            histogram,
+            top_values: compute_frequencies(lf.clone(), name, options.top_n)?,
+        });
+    }
+
+    Ok(Profile {
+        source: source.to_string(),
+        rows,
+        columns,
+    })
+}
@@ -0,0 +1,162 @@
+//! A spinner on stderr while queries run, so that long scans give some feedback.
+//!
+//! Polars memory-maps local files and doesn't report how far a scan has got, so the
+//! spinner shows the size of the input and the elapsed time rather than a percentage.
+//! Stdin, downloads and compressed files are first copied into a temporary file though,
+//! and while they are the spinner shows the bytes read, with a percentage and the time
+//! left when the size of the input is known.
+
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use csv_stats::engine::QueryObserver;
+
+/// Queries finishing within this time don't show the spinner at all.
+const DELAY: Duration = Duration::from_millis(500);
+
+/// How often the spinner is redrawn.
+const TICK: Duration = Duration::from_millis(100);
+
+/// The frames of the spinner animation.
+const FRAMES: [char; 4] = ['|', '/', '-', '\\'];
+
+/// Shows a spinner on stderr while each query runs.
+pub struct Spinner {
+    /// The total size of the input files, if known.
+    input_size: Option<u64>,
+    /// The bytes read of the input being copied.
+    bytes_read: Arc<AtomicU64>,
+    /// The drawing thread of the running query and the flag that stops it.
+    running: Mutex<Option<(Arc<AtomicBool>, JoinHandle<()>)>>,
+}
+
+impl Spinner {
+    /// Creates a spinner for an input of the given size.
+    pub fn new(input_size: Option<u64>) -> Self {
+        Spinner {
+            input_size,
+            bytes_read: Arc::new(AtomicU64::new(0)),
+            running: Mutex::new(None),
+        }
+    }
+
+    /// Starts drawing the spinner, with a label given the elapsed time.
+    fn start(&self, label: impl Fn(Duration) -> String + Send + 'static) {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+        let handle = thread::spawn(move || draw(label, &thread_stop));
+        if let Ok(mut running) = self.running.lock() {
+            *running = Some((stop, handle));
+        }
+    }
+
+    /// Stops drawing the spinner.
+    fn stop(&self) {
+        let running = self.running.lock().ok().and_then(|mut r| r.take());
+        if let Some((stop, handle)) = running {
+            stop.store(true, Ordering::Relaxed);
+            let _ = handle.join();
+        }
+    }
+}
+
+impl QueryObserver for Spinner {
+    fn query_started(&self) {
+        let label = match self.input_size {
+            Some(size) => format!("Scanning {}", format_bytes(size)),
+            None => "Scanning".to_string(),
+        };
+        self.start(move |elapsed| format!("{}... {:.1}s", label, elapsed.as_secs_f64()));
+    }
+
+    fn query_finished(&self) {
+        self.stop();
+    }
+
+    fn read_started(&self, size: Option<u64>) {
+        self.bytes_read.store(0, Ordering::Relaxed);
+        let bytes_read = Arc::clone(&self.bytes_read);
+        self.start(move |elapsed| read_progress(bytes_read.load(Ordering::Relaxed), size, elapsed));
+    }
+
+    fn bytes_read(&self, bytes: u64) {
+        self.bytes_read.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    fn read_finished(&self) {
+        self.stop();
+    }
+}
+
+/// Describes how far the copy of an input has got, e.g. "Reading 12.0 MiB of 96.0 MiB
+/// (12%), 44s left". The time left assumes the rest is read at the rate so far.
+fn read_progress(read: u64, size: Option<u64>, elapsed: Duration) -> String {
+    match size {
+        Some(size) if size > 0 && read > 0 => {
+            let fraction = (read as f64 / size as f64).min(1.0);
+            let left = elapsed.as_secs_f64() * (1.0 - fraction) / fraction;
+            format!(
+                "Reading {} of {} ({:.0}%), {:.0}s left",
+                format_bytes(read),
+                format_bytes(size),
+                fraction * 100.0,
+                left.ceil()
+            )
+        }
+        Some(size) => format!("Reading 0 B of {}", format_bytes(size)),
+        None => format!(
+            "Reading {}... {:.1}s",
+            format_bytes(read),
+            elapsed.as_secs_f64()
+        ),
+    }
+}
+
+/// Redraws the spinner until stopped, then clears its line if it was drawn.
+fn draw(label: impl Fn(Duration) -> String, stop: &AtomicBool) {
+    let start = Instant::now();
+    let mut stderr = io::stderr();
+    let mut drawn = false;
+    let mut frame = 0;
+    while !stop.load(Ordering::Relaxed) {
+        thread::sleep(TICK);
+        let elapsed = start.elapsed();
+        if elapsed < DELAY {
+            continue;
+        }
+        // The label's length varies, so the rest of the line is erased.
+        let _ = write!(
+            stderr,
+            "\r{} {}\x1b[K",
+            FRAMES[frame % FRAMES.len()],
+            label(elapsed)
+        );
+        let _ = stderr.flush();
+        drawn = true;
+        frame += 1;
+    }
+    if drawn {
+        // Erase the line so the results start on a clean one.
+        let _ = write!(stderr, "\r\x1b[2K");
+        let _ = stderr.flush();
+    }
+}
+
+/// Formats a number of bytes with a binary unit, e.g. "101.3 MiB".
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
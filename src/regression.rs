@@ -0,0 +1,102 @@
+//! Simple linear regression of one numeric column on another.
+
+use anyhow::{Result, bail};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::collect;
+use crate::stats::NumericParsing;
+
+/// The least-squares line `y = intercept + slope * x` through two columns.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Regression {
+    /// The name of the explanatory column.
+    pub x: String,
+    /// The name of the response column.
+    pub y: String,
+    /// The number of rows where both columns have a value; only these rows are used.
+    pub count: usize,
+    /// The change of y per unit of x.
+    pub slope: f64,
+    /// The value of y where x is 0.
+    pub intercept: f64,
+    /// The proportion of the variance of y explained by x, between 0 and 1.
+    pub r_squared: f64,
+    /// The standard deviation of the residuals, with n - 2 degrees of freedom.
+    pub residual_std: Option<f64>,
+}
+
+/// Fits `y ~ x` in a single pass over the data.
+///
+/// The fit only needs the means, variances and covariance of the complete rows, which
+/// are all aggregations, so memory use doesn't grow with the number of rows.
+pub fn compute_regression(
+    lf: LazyFrame,
+    x_column: &str,
+    y_column: &str,
+    parsing: &NumericParsing,
+) -> Result<Regression> {
+    parsing.check_strict(&lf, x_column)?;
+    parsing.check_strict(&lf, y_column)?;
+
+    let x = parsing.to_float(x_column);
+    let y = parsing.to_float(y_column);
+    let complete = x.clone().is_not_null().and(y.clone().is_not_null());
+    // Masking rather than filtering keeps the columns the same length, which the
+    // streaming engine needs.
+    let x = when(complete.clone()).then(x).otherwise(lit(NULL));
+    let y = when(complete.clone()).then(y).otherwise(lit(NULL));
+    let df = collect(lf.select([
+        complete.sum().alias("count"),
+        x.clone().mean().alias("x_mean"),
+        y.clone().mean().alias("y_mean"),
+        x.clone().var(1).alias("x_var"),
+        y.clone().var(1).alias("y_var"),
+        cov(x, y, 1).alias("covariance"),
+    ]))?;
+
+    let get = |name: &str| -> Result<f64> {
+        Ok(df
+            .column(name)?
+            .get(0)?
+            .try_extract::<f64>()
+            .unwrap_or(f64::NAN))
+    };
+    let count = df.column("count")?.get(0)?.try_extract::<u32>()? as usize;
+    if count < 2 {
+        bail!(
+            "Need at least two rows with values of both '{}' and '{}' to fit a line, found {}",
+            x_column,
+            y_column,
+            count
+        );
+    }
+    let x_var = get("x_var")?;
+    if x_var == 0.0 {
+        bail!("Column '{}' is constant, so no line fits", x_column);
+    }
+    let (y_var, covariance) = (get("y_var")?, get("covariance")?);
+
+    let slope = covariance / x_var;
+    let intercept = get("y_mean")? - slope * get("x_mean")?;
+    // The squared residuals sum to (n - 1) * (var(y) - cov² / var(x)); clamp the rounding
+    // error of a perfect fit.
+    let residual_var = (y_var - covariance * slope).max(0.0);
+    let r_squared = if y_var == 0.0 {
+        1.0
+    } else {
+        1.0 - residual_var / y_var
+    };
+    let residual_std =
+        (count > 2).then(|| (residual_var * (count - 1) as f64 / (count - 2) as f64).sqrt());
+
+    Ok(Regression {
+        x: x_column.to_string(),
+        y: y_column.to_string(),
+        count,
+        slope,
+        intercept,
+        r_squared,
+        residual_std,
+    })
+}
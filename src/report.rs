@@ -0,0 +1,315 @@
+//! Rendering of data profiles as standalone documents.
+
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::output::{Table, format_opt, stat_rows};
+use crate::profile::{ColumnProfile, Profile};
+use crate::stats::SelectedStats;
+
+/// The format a profile is rendered in.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProfileFormat {
+    /// A summary table with one line per column.
+    #[default]
+    Text,
+    /// The complete profile as JSON.
+    Json,
+    /// A Markdown document with statistics, histograms and top values per column, e.g.
+    /// for a data documentation repository.
+    #[value(alias = "md")]
+    Markdown,
+    /// A self-contained HTML page with statistics, histograms and top values per column.
+    Html,
+}
+
+impl ProfileFormat {
+    /// Guesses the format from a report file's extension.
+    pub fn from_path(path: &str) -> Option<Self> {
+        let extension = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "html" | "htm" => Some(ProfileFormat::Html),
+            "json" => Some(ProfileFormat::Json),
+            "md" | "markdown" => Some(ProfileFormat::Markdown),
+            "txt" => Some(ProfileFormat::Text),
+            _ => None,
+        }
+    }
+}
+
+/// Renders a profile as a complete document in the given format.
+pub fn render_profile(format: ProfileFormat, profile: &Profile) -> Result<String> {
+    Ok(match format {
+        ProfileFormat::Text => profile_to_text(profile),
+        ProfileFormat::Json => serde_json::to_string_pretty(profile)? + "\n",
+        ProfileFormat::Markdown => profile_to_markdown(profile),
+        ProfileFormat::Html => profile_to_html(profile),
+    })
+}
+
+/// Builds the overview table with one row per column.
+fn overview_table(profile: &Profile) -> Table {
+    Table {
+        headers: ["Column", "Type", "Null %", "Unique", "Min", "Mean", "Max"]
+            .map(String::from)
+            .to_vec(),
+        rows: profile
+            .columns
+            .iter()
+            .map(|c| {
+                let stat = |value: fn(&SelectedStats) -> Option<f64>| {
+                    c.stats
+                        .as_ref()
+                        .map_or_else(String::new, |s| format_opt(value(s)))
+                };
+                vec![
+                    c.name.clone(),
+                    c.dtype.clone(),
+                    format_opt(c.null_percent),
+                    c.n_unique.to_string(),
+                    stat(|s| s.min),
+                    stat(|s| s.mean),
+                    stat(|s| s.max),
+                ]
+            })
+            .collect(),
+    }
+}
+
+/// The statistics listed for a column: every statistic for numeric columns, and the
+/// counts for the others.
+fn column_rows(profile: &Profile, column: &ColumnProfile) -> Vec<(String, String)> {
+    match &column.stats {
+        Some(stats) => stat_rows(stats),
+        None => vec![
+            (
+                "Count".to_string(),
+                (profile.rows - column.null_count).to_string(),
+            ),
+            ("Nulls".to_string(), column.null_count.to_string()),
+            ("Null %".to_string(), format_opt(column.null_percent)),
+            ("Unique".to_string(), column.n_unique.to_string()),
+        ],
+    }
+}
+
+/// Renders the summary of a profile as plain text.
+fn profile_to_text(profile: &Profile) -> String {
+    format!(
+        "--- Profile of {} ({} rows, {} columns) ---\n{}",
+        profile.source,
+        profile.rows,
+        profile.columns.len(),
+        overview_table(profile).to_text()
+    )
+}
+
+/// The width, in characters, of the longest bar in a Markdown histogram.
+const MARKDOWN_BAR_WIDTH: usize = 30;
+
+/// Renders a profile as a Markdown document.
+fn profile_to_markdown(profile: &Profile) -> String {
+    let mut markdown = format!(
+        "# Profile of {}\n\n{} rows, {} columns\n\n## Overview\n\n{}",
+        profile.source,
+        profile.rows,
+        profile.columns.len(),
+        overview_table(profile).to_markdown()
+    );
+
+    for column in &profile.columns {
+        markdown.push_str(&format!("\n## {} ({})\n\n", column.name, column.dtype));
+        let statistics = Table {
+            headers: vec!["Statistic".to_string(), "Value".to_string()],
+            rows: column_rows(profile, column)
+                .into_iter()
+                .map(|(label, value)| vec![label, value])
+                .collect(),
+        };
+        markdown.push_str(&statistics.to_markdown());
+
+        if let Some(histogram) = &column.histogram {
+            let max = histogram.bins.iter().map(|b| b.count).max().unwrap_or(0);
+            let table = Table {
+                headers: vec!["Range".to_string(), "Count".to_string(), String::new()],
+                rows: histogram
+                    .bins
+                    .iter()
+                    .map(|bin| {
+                        vec![
+                            format!("{:.4} – {:.4}", bin.lower, bin.upper),
+                            bin.count.to_string(),
+                            text_bar(bin.count, max),
+                        ]
+                    })
+                    .collect(),
+            };
+            markdown.push_str(&format!("\n### Histogram\n\n{}", table.to_markdown()));
+        }
+
+        let top = &column.top_values;
+        let table = Table {
+            headers: vec![
+                "Value".to_string(),
+                "Count".to_string(),
+                "Percent".to_string(),
+            ],
+            rows: top
+                .values
+                .iter()
+                .map(|v| {
+                    vec![
+                        v.value.clone().unwrap_or_else(|| "null".to_string()),
+                        v.count.to_string(),
+                        format!("{:.2}%", v.percent),
+                    ]
+                })
+                .collect(),
+        };
+        markdown.push_str(&format!(
+            "\n### Top values ({} distinct)\n\n{}",
+            top.distinct,
+            table.to_markdown()
+        ));
+    }
+    markdown
+}
+
+/// Draws a bar of block characters scaled to the largest count.
+fn text_bar(count: usize, max: usize) -> String {
+    let len = (count * MARKDOWN_BAR_WIDTH).checked_div(max).unwrap_or(0);
+    "█".repeat(len)
+}
+
+/// The styles of the HTML report, inlined so the file can be shared on its own.
+const HTML_STYLE: &str = "
+body { font-family: system-ui, sans-serif; margin: 2rem auto; max-width: 70rem; color: #222; }
+h1 { margin-bottom: 0.2rem; }
+h2 { border-bottom: 1px solid #ddd; padding-bottom: 0.3rem; margin-top: 2.5rem; }
+.dtype { color: #777; font-size: 0.8em; font-weight: normal; }
+.muted { color: #777; }
+table { border-collapse: collapse; margin: 0.5rem 0; }
+th, td { padding: 0.2rem 0.7rem; text-align: right; border-bottom: 1px solid #eee; }
+th:first-child, td:first-child { text-align: left; }
+.panels { display: flex; flex-wrap: wrap; gap: 2rem; align-items: flex-start; }
+.bar { background: #4c78a8; height: 0.9rem; }
+td.bar-cell { width: 12rem; }
+";
+
+/// Renders a profile as a self-contained HTML page.
+fn profile_to_html(profile: &Profile) -> String {
+    let mut html = String::new();
+    let title = format!("Profile of {}", escape_html(&profile.source));
+    html.push_str(&format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<h1>{}</h1>\n\
+         <p class=\"muted\">{} rows, {} columns</p>\n",
+        title,
+        HTML_STYLE,
+        title,
+        profile.rows,
+        profile.columns.len()
+    ));
+
+    html.push_str("<h2>Overview</h2>\n");
+    let overview = overview_table(profile);
+    html_table(&mut html, &overview.headers, &overview.rows);
+
+    for column in &profile.columns {
+        html.push_str(&format!(
+            "<h2>{} <span class=\"dtype\">{}</span></h2>\n<div class=\"panels\">\n",
+            escape_html(&column.name),
+            escape_html(&column.dtype)
+        ));
+
+        html.push_str("<div>\n<h3>Statistics</h3>\n");
+        let rows: Vec<Vec<String>> = column_rows(profile, column)
+            .into_iter()
+            .map(|(label, value)| vec![label, value])
+            .collect();
+        html_table(
+            &mut html,
+            &["Statistic".to_string(), "Value".to_string()],
+            &rows,
+        );
+        html.push_str("</div>\n");
+
+        if let Some(histogram) = &column.histogram {
+            html.push_str("<div>\n<h3>Histogram</h3>\n<table>\n");
+            let max = histogram.bins.iter().map(|b| b.count).max().unwrap_or(0);
+            for bin in &histogram.bins {
+                html.push_str(&format!(
+                    "<tr><td>{:.4} – {:.4}</td>{}<td>{}</td></tr>\n",
+                    bin.lower,
+                    bin.upper,
+                    bar_cell(bin.count, max),
+                    bin.count
+                ));
+            }
+            html.push_str("</table>\n</div>\n");
+        }
+
+        let top = &column.top_values;
+        html.push_str(&format!(
+            "<div>\n<h3>Top values</h3>\n<p class=\"muted\">{} distinct</p>\n<table>\n",
+            top.distinct
+        ));
+        let max = top.values.first().map_or(0, |v| v.count);
+        for value in &top.values {
+            let label = match &value.value {
+                Some(value) => escape_html(value),
+                None => "<span class=\"muted\">null</span>".to_string(),
+            };
+            html.push_str(&format!(
+                "<tr><td>{}</td>{}<td>{}</td><td>{:.2}%</td></tr>\n",
+                label,
+                bar_cell(value.count, max),
+                value.count,
+                value.percent
+            ));
+        }
+        html.push_str("</table>\n</div>\n</div>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Appends an HTML table with the given headers and rows of plain text cells.
+fn html_table(html: &mut String, headers: &[String], rows: &[Vec<String>]) {
+    html.push_str("<table>\n<tr>");
+    for header in headers {
+        html.push_str(&format!("<th>{}</th>", escape_html(header)));
+    }
+    html.push_str("</tr>\n");
+    for row in rows {
+        html.push_str("<tr>");
+        for cell in row {
+            html.push_str(&format!("<td>{}</td>", escape_html(cell)));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n");
+}
+
+/// Builds a table cell holding a bar scaled to the largest count.
+fn bar_cell(count: usize, max: usize) -> String {
+    let percent = if max > 0 {
+        count as f64 * 100.0 / max as f64
+    } else {
+        0.0
+    };
+    format!(
+        "<td class=\"bar-cell\"><div class=\"bar\" style=\"width: {:.1}%\"></div></td>",
+        percent
+    )
+}
+
+/// Escapes the characters with a special meaning in HTML text and attributes.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,267 @@
+//! Rolling-window statistics of a numeric column, such as a 30-day moving average,
+//! cumulative statistics, such as a running total, and smoothed series for plotting.
+
+use std::str::FromStr;
+
+use anyhow::{Result, anyhow, bail};
+use polars::prelude::*;
+
+use crate::engine::collect;
+use crate::schema::temporal_column;
+use crate::stats::NumericParsing;
+
+/// The extent of each rolling window.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RollingWindow {
+    /// The current row and the rows before it, up to this many in total.
+    Rows(usize),
+    /// The rows whose time lies within this duration up to and including the current
+    /// row's, in Polars' duration notation, e.g. `30d` or `1h`.
+    Duration(String),
+    /// Every row up to and including the current one.
+    Cumulative,
+}
+
+impl FromStr for RollingWindow {
+    type Err = anyhow::Error;
+
+    /// Parses a number of rows such as `100`, or a duration such as `30d`.
+    fn from_str(source: &str) -> Result<Self> {
+        let source = source.trim();
+        if let Ok(rows) = source.parse::<usize>() {
+            if rows == 0 {
+                bail!("The window must span at least one row");
+            }
+            return Ok(RollingWindow::Rows(rows));
+        }
+        Duration::try_parse(source).map_err(|_| {
+            anyhow!(
+                "Invalid window '{}', expected a number of rows such as 100 or a duration \
+                 such as 30d",
+                source
+            )
+        })?;
+        Ok(RollingWindow::Duration(source.to_string()))
+    }
+}
+
+/// Options controlling rolling-window statistics.
+#[derive(Debug, Clone)]
+pub struct RollingOptions {
+    /// The extent of each window.
+    pub window: RollingWindow,
+    /// Order the rows by this column first, rather than keeping the file order. Required
+    /// for duration windows, for which it must hold dates, datetimes or parseable text.
+    pub order_by: Option<String>,
+    /// How text values are cleaned before being parsed as numbers.
+    pub parsing: NumericParsing,
+}
+
+/// Calculates the rolling mean, standard deviation, minimum and maximum of a column for
+/// every row, or for a cumulative window its running count, sum and mean.
+///
+/// The result holds the ordering column, unless it's the analyzed one, the parsed values,
+/// and a column per statistic named like `rolling_mean` or `cumulative_sum`. Windows at
+/// the start hold fewer rows, and rows with a missing time are left out.
+pub fn compute_rolling(
+    mut lf: LazyFrame,
+    column_name: &str,
+    options: &RollingOptions,
+) -> Result<DataFrame> {
+    options.parsing.check_strict(&lf, column_name)?;
+    let values = options.parsing.to_float(column_name);
+    let mut selection = Vec::with_capacity(6);
+
+    let stats = match &options.window {
+        RollingWindow::Rows(_) | RollingWindow::Cumulative => {
+            if let Some(order_by) = options.order_by.as_deref() {
+                lf = lf.sort(
+                    [order_by],
+                    SortMultipleOptions::default().with_nulls_last(true),
+                );
+                if order_by != column_name {
+                    selection.push(col(order_by));
+                }
+            }
+            match options.window {
+                RollingWindow::Rows(rows) => {
+                    let window = RollingOptionsFixedWindow {
+                        window_size: rows,
+                        min_periods: 1,
+                        ..Default::default()
+                    };
+                    vec![
+                        values
+                            .clone()
+                            .rolling_mean(window.clone())
+                            .alias("rolling_mean"),
+                        values
+                            .clone()
+                            .rolling_std(window.clone())
+                            .alias("rolling_std"),
+                        values
+                            .clone()
+                            .rolling_min(window.clone())
+                            .alias("rolling_min"),
+                        values.clone().rolling_max(window).alias("rolling_max"),
+                    ]
+                }
+                _ => {
+                    // Missing values don't count, and the running totals carry over them.
+                    let count = values.clone().cum_count(false);
+                    let sum = values.clone().fill_null(lit(0.0)).cum_sum(false);
+                    vec![
+                        count.clone().alias("cumulative_count"),
+                        sum.clone().alias("cumulative_sum"),
+                        (sum / count.cast(DataType::Float64)).alias("cumulative_mean"),
+                    ]
+                }
+            }
+        }
+        RollingWindow::Duration(duration) => {
+            let Some(order_by) = options.order_by.as_deref() else {
+                bail!(
+                    "A window of {} needs a date or datetime column to order by",
+                    duration
+                );
+            };
+            let time = temporal_column(&mut lf, order_by)?;
+            lf = lf
+                .with_column(time)
+                .drop_nulls(Some(cols([order_by])))
+                .sort([order_by], SortMultipleOptions::default());
+            selection.push(col(order_by));
+            let window = RollingOptionsDynamicWindow {
+                window_size: Duration::parse(duration),
+                min_periods: 1,
+                closed_window: ClosedWindow::Right,
+                fn_params: None,
+            };
+            let by = col(order_by);
+            vec![
+                values
+                    .clone()
+                    .rolling_mean_by(by.clone(), window.clone())
+                    .alias("rolling_mean"),
+                values
+                    .clone()
+                    .rolling_std_by(by.clone(), window.clone())
+                    .alias("rolling_std"),
+                values
+                    .clone()
+                    .rolling_min_by(by.clone(), window.clone())
+                    .alias("rolling_min"),
+                values
+                    .clone()
+                    .rolling_max_by(by, window)
+                    .alias("rolling_max"),
+            ]
+        }
+    };
+
+    selection.push(values.alias(column_name));
+    selection.extend(stats);
+    collect(lf.select(selection))
+}
+
+/// Options controlling the smoothed series of a column.
+#[derive(Debug, Clone, Default)]
+pub struct SmoothingOptions {
+    /// The windows of the simple moving averages, each a number of rows or a duration.
+    pub moving_averages: Vec<RollingWindow>,
+    /// The spans, in rows, of the exponentially weighted moving averages. A span of `n`
+    /// weights each value by `2 / (n + 1)` against the average before it.
+    pub ewma_spans: Vec<usize>,
+    /// Order the rows by this column first, rather than keeping the file order. Required
+    /// for duration windows, for which it must hold dates, datetimes or parseable text.
+    pub order_by: Option<String>,
+    /// How text values are cleaned before being parsed as numbers.
+    pub parsing: NumericParsing,
+}
+
+/// Calculates moving averages of a column for every row, e.g. to plot the trend of a
+/// noisy daily series.
+///
+/// The result holds the ordering column, unless it's the analyzed one, the parsed values,
+/// and a column per average named like `sma_7`, `sma_30d` or `ewma_7`. Averages at the
+/// start cover fewer rows, and missing values are skipped.
+pub fn compute_smoothed(
+    mut lf: LazyFrame,
+    column_name: &str,
+    options: &SmoothingOptions,
+) -> Result<DataFrame> {
+    if options.moving_averages.is_empty() && options.ewma_spans.is_empty() {
+        bail!("Choose at least one moving average to calculate");
+    }
+    if options.ewma_spans.contains(&0) {
+        bail!("The span of an exponentially weighted moving average must be at least 1");
+    }
+    options.parsing.check_strict(&lf, column_name)?;
+    let values = options.parsing.to_float(column_name);
+    let mut selection = Vec::new();
+
+    let has_durations = options
+        .moving_averages
+        .iter()
+        .any(|w| matches!(w, RollingWindow::Duration(_)));
+    match options.order_by.as_deref() {
+        Some(order_by) if has_durations => {
+            let time = temporal_column(&mut lf, order_by)?;
+            lf = lf
+                .with_column(time)
+                .drop_nulls(Some(cols([order_by])))
+                .sort([order_by], SortMultipleOptions::default());
+        }
+        Some(order_by) => {
+            lf = lf.sort(
+                [order_by],
+                SortMultipleOptions::default().with_nulls_last(true),
+            );
+        }
+        None if has_durations => {
+            bail!("Duration windows need a date or datetime column to order by")
+        }
+        None => {}
+    }
+    if let Some(order_by) = options.order_by.as_deref()
+        && order_by != column_name
+    {
+        selection.push(col(order_by));
+    }
+    selection.push(values.clone().alias(column_name));
+
+    for window in &options.moving_averages {
+        let (name, average) = match window {
+            RollingWindow::Rows(rows) => {
+                let window = RollingOptionsFixedWindow {
+                    window_size: *rows,
+                    min_periods: 1,
+                    ..Default::default()
+                };
+                (format!("sma_{}", rows), values.clone().rolling_mean(window))
+            }
+            RollingWindow::Duration(duration) => {
+                let window = RollingOptionsDynamicWindow {
+                    window_size: Duration::parse(duration),
+                    min_periods: 1,
+                    closed_window: ClosedWindow::Right,
+                    fn_params: None,
+                };
+                let by = col(options.order_by.as_deref().unwrap_or_default());
+                let average = values.clone().rolling_mean_by(by, window);
+                (format!("sma_{}", duration), average)
+            }
+            RollingWindow::Cumulative => bail!("A moving average needs a window"),
+        };
+        selection.push(average.alias(name));
+    }
+    for span in &options.ewma_spans {
+        let ewma = values.clone().ewm_mean(EWMOptions {
+            alpha: 2.0 / (*span as f64 + 1.0),
+            ..Default::default()
+        });
+        selection.push(ewma.alias(format!("ewma_{}", span)));
+    }
+
+    collect(lf.select(selection))
+}
@@ -0,0 +1,210 @@
+//! Inspecting and overriding the column types inferred from CSV input.
+//!
+//! The CSV reader infers each column's type from the first 100 rows, which goes wrong for
+//! sparse columns: a column that is empty or integral early on can hold decimals later,
+//! failing the read or the numeric cast. Overrides are given as `name=type` pairs, e.g.
+//! `Amount Received=f64` or `Timestamp=datetime[%Y/%m/%d %H:%M]`, or as a JSON schema
+//! file mapping column names to the same type names.
+
+use std::fs;
+use std::str::FromStr;
+
+use anyhow::{Context, Result, anyhow, bail};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::collect;
+use crate::stats::any_value_to_string;
+
+/// A column of the input, as it will be read.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnInfo {
+    /// The exact column name, as it must be passed to other commands.
+    pub name: String,
+    /// The column's type, in the notation accepted by `--dtype`.
+    pub dtype: String,
+    /// The first non-null value of the column, or `None` if it only holds nulls.
+    pub example: Option<String>,
+}
+
+/// Lists every column of the input with its type and an example value, in file order.
+pub fn inspect_schema(mut lf: LazyFrame) -> Result<Vec<ColumnInfo>> {
+    let schema = lf.collect_schema()?;
+    let examples: Vec<Expr> = schema
+        .iter_names()
+        .map(|name| col(name.clone()).drop_nulls().first())
+        .collect();
+    let examples_df = collect(lf.select(examples))?;
+
+    schema
+        .iter()
+        .zip(examples_df.get_columns())
+        .map(|((name, dtype), example)| {
+            Ok(ColumnInfo {
+                name: name.to_string(),
+                dtype: dtype.to_string(),
+                example: any_value_to_string(example.get(0)?),
+            })
+        })
+        .collect()
+}
+
+/// A column type given in place of the inferred one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DtypeOverride {
+    /// The name of the column.
+    pub column: String,
+    /// The type to read the column as.
+    pub dtype: DataType,
+    /// The strftime-style format of a date or datetime column, inferred when `None`.
+    pub format: Option<String>,
+}
+
+impl DtypeOverride {
+    /// Parses a type name such as `f64`, `str` or `date[%d.%m.%Y]` for the given column.
+    pub fn new(column: &str, type_name: &str) -> Result<Self> {
+        let type_name = type_name.trim();
+        let (name, format) = match type_name.split_once('[') {
+            Some((name, rest)) => {
+                let format = rest
+                    .strip_suffix(']')
+                    .ok_or_else(|| anyhow!("Missing ']' in type '{}'", type_name))?;
+                (name, Some(format.to_string()))
+            }
+            None => (type_name, None),
+        };
+
+        let dtype = match name.to_ascii_lowercase().as_str() {
+            "f64" | "float64" | "float" => DataType::Float64,
+            "f32" | "float32" => DataType::Float32,
+            "i64" | "int64" | "int" => DataType::Int64,
+            "i32" | "int32" => DataType::Int32,
+            "i16" | "int16" => DataType::Int16,
+            "i8" | "int8" => DataType::Int8,
+            "u64" | "uint64" => DataType::UInt64,
+            "u32" | "uint32" => DataType::UInt32,
+            "u16" | "uint16" => DataType::UInt16,
+            "u8" | "uint8" => DataType::UInt8,
+            "str" | "string" | "utf8" => DataType::String,
+            "bool" | "boolean" => DataType::Boolean,
+            "date" => DataType::Date,
+            "datetime" => DataType::Datetime(TimeUnit::Microseconds, None),
+            other => bail!(
+                "Unknown type '{}' for column '{}', expected one of f64, f32, i64, i32, i16, i8, \
+                 u64, u32, u16, u8, str, bool, date or datetime",
+                other,
+                column
+            ),
+        };
+        if format.is_some() && !dtype.is_temporal() {
+            bail!(
+                "A format can only be given for date and datetime types, not '{}'",
+                type_name
+            );
+        }
+
+        Ok(DtypeOverride {
+            column: column.to_string(),
+            dtype,
+            format,
+        })
+    }
+
+    /// The type the CSV reader should produce for this column.
+    ///
+    /// Dates and datetimes are read as strings and parsed afterwards, since the reader
+    /// can't be given a format.
+    pub(crate) fn read_dtype(&self) -> DataType {
+        if self.dtype.is_temporal() {
+            DataType::String
+        } else {
+            self.dtype.clone()
+        }
+    }
+
+    /// Converts the column, as scanned, into the requested type.
+    ///
+    /// Values that don't parse become null, like the numeric cast used for statistics.
+    pub(crate) fn convert(&self, scanned: &DataType) -> Expr {
+        let column = col(self.column.as_str());
+        if !self.dtype.is_temporal() || *scanned != DataType::String {
+            return column.cast(self.dtype.clone());
+        }
+
+        let options = StrptimeOptions {
+            format: self.format.as_deref().map(Into::into),
+            strict: false,
+            ..Default::default()
+        };
+        match self.dtype {
+            DataType::Date => column.str().to_date(options),
+            _ => column.str().to_datetime(None, None, options, lit("raise")),
+        }
+    }
+}
+
+impl FromStr for DtypeOverride {
+    type Err = anyhow::Error;
+
+    /// Parses a `name=type` pair. The column name may itself contain `=`, so the type
+    /// starts after the last `=` outside of a format.
+    fn from_str(source: &str) -> Result<Self> {
+        let before_format = source.find('[').unwrap_or(source.len());
+        let split = source[..before_format]
+            .rfind('=')
+            .ok_or_else(|| anyhow!("Expected COLUMN=TYPE, got '{}'", source))?;
+        Self::new(&source[..split], &source[split + 1..])
+    }
+}
+
+/// Returns an expression for a column as dates or datetimes, for ordering and bucketing
+/// by time.
+///
+/// Text is parsed as datetimes with the format inferred, and values that don't parse
+/// become null. A format can be given with a `--dtype` override instead.
+pub(crate) fn temporal_column(lf: &mut LazyFrame, column: &str) -> Result<Expr> {
+    match lf.collect_schema()?.get(column) {
+        Some(DataType::String) => {
+            let options = StrptimeOptions {
+                strict: false,
+                ..Default::default()
+            };
+            Ok(col(column).str().to_datetime(
+                Some(TimeUnit::Microseconds),
+                None,
+                options,
+                lit("raise"),
+            ))
+        }
+        Some(DataType::Date | DataType::Datetime(..)) => Ok(col(column)),
+        Some(other) => bail!(
+            "Column '{}' holds {} values, expected dates, datetimes or text",
+            column,
+            other
+        ),
+        None => bail!("Column '{}' not found", column),
+    }
+}
+
+/// Reads type overrides from a JSON file mapping column names to type names, e.g.
+/// `{"Amount Received": "f64", "Timestamp": "datetime[%Y/%m/%d %H:%M]"}`.
+pub fn read_schema_file(path: &str) -> Result<Vec<DtypeOverride>> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("Failed to read schema '{}'", path))?;
+    let columns: serde_json::Map<String, serde_json::Value> = serde_json::from_str(&contents)
+        .with_context(|| format!("Schema '{}' must be a JSON object of column types", path))?;
+
+    columns
+        .iter()
+        .map(|(column, type_name)| {
+            let type_name = type_name.as_str().ok_or_else(|| {
+                anyhow!(
+                    "The type of '{}' in schema '{}' must be a string",
+                    column,
+                    path
+                )
+            })?;
+            DtypeOverride::new(column, type_name)
+        })
+        .collect()
+}
@@ -0,0 +1,370 @@
+//! A small HTTP API over the statistics engine, so other services can request analyses
+//! without spawning a process for each.
+//!
+//! `POST /stats` analyzes either the files named by a JSON request, such as
+//! `{"file": "data.csv", "columns": ["Amount"], "group_by": "Currency"}`, or data
+//! uploaded as the request body, with the same options in the query string, e.g.
+//! `/stats?columns=Amount&format=parquet`. It responds with the statistics as in the
+//! `stats` command's JSON output, or with `{"error": "..."}`. `GET /health` responds
+//! while the server is up.
+//!
+//! Requests can name any file the server can read, so the server should only be
+//! reachable by trusted clients.
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+
+use crate::input::{InputFormat, InputOptions, numeric_columns, scan, scan_bytes};
+use crate::stats::{NumericParsing, StatsOptions, compute_stats};
+
+/// The largest request head (request line and headers) accepted.
+const MAX_HEAD: usize = 64 * 1024;
+
+/// The largest request body accepted, such as an uploaded file.
+const MAX_BODY: usize = 256 * 1024 * 1024;
+
+/// How long a client may take to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The most headers accepted in a request.
+const MAX_HEADERS: usize = 64;
+
+/// Defaults that apply to every request.
+#[derive(Debug, Clone, Default)]
+pub struct ServerOptions {
+    /// How to read the input, including the files analyzed when a request names none.
+    pub input: InputOptions,
+    /// How text values are converted to numbers.
+    pub parsing: NumericParsing,
+}
+
+/// The options of a `POST /stats` request.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct StatsRequest {
+    /// The file, or list of files, to analyze.
+    file: Option<Files>,
+    /// The format of the input, e.g. "parquet", detected from the extension by default.
+    format: Option<String>,
+    /// A filter expression selecting the rows to analyze.
+    filter: Option<String>,
+    /// The columns to analyze; all numeric columns by default.
+    columns: Vec<String>,
+    /// Calculate the statistics separately for each distinct value of this column.
+    group_by: Option<String>,
+    /// Quantiles to calculate, each between 0 and 1.
+    quantiles: Vec<f64>,
+    /// Delta degrees of freedom for std and var; 1 (sample statistics) by default.
+    ddof: Option<u8>,
+    /// Also calculate skewness and kurtosis.
+    moments: bool,
+    /// Also calculate the median absolute deviation.
+    mad: bool,
+    /// Also calculate the geometric and harmonic means.
+    extended: bool,
+    /// Also find the most frequent value(s).
+    mode: bool,
+    /// Estimate the distinct count, median and quantiles instead of calculating them exactly.
+    approx: bool,
+}
+
+/// A single file name, or a list of them.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Files {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl StatsRequest {
+    /// Reads the options from a query string such as `columns=a,b&moments=true`. Lists
+    /// are comma-separated.
+    fn from_query(query: &str) -> Result<Self> {
+        let mut fields = Map::new();
+        for pair in query.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let (key, value) = (percent_decode(key)?, percent_decode(value)?);
+            let value = match key.as_str() {
+                "file" | "columns" => value.split(',').map(Value::from).collect(),
+                "quantiles" => value
+                    .split(',')
+                    .map(|q| q.trim().parse::<f64>().map(Value::from))
+                    .collect::<Result<_, _>>()
+                    .with_context(|| format!("Invalid quantiles '{}'", value))?,
+                "ddof" => Value::from(
+                    value
+                        .parse::<u8>()
+                        .with_context(|| format!("Invalid ddof '{}'", value))?,
+                ),
+                "moments" | "mad" | "extended" | "mode" | "approx" => {
+                    Value::from(matches!(value.as_str(), "" | "true" | "1"))
+                }
+                _ => Value::from(value),
+            };
+            fields.insert(key, value);
+        }
+        Ok(serde_json::from_value(Value::Object(fields))?)
+    }
+}
+
+/// Listens on the address, such as `127.0.0.1:8080`, and answers requests until
+/// interrupted, each on its own thread.
+pub fn serve(address: &str, options: ServerOptions) -> Result<()> {
+    let listener =
+        TcpListener::bind(address).with_context(|| format!("Failed to listen on {}", address))?;
+    eprintln!("Listening on http://{}", listener.local_addr()?);
+    let options = Arc::new(options);
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                eprintln!("Failed to accept a connection: {}", err);
+                continue;
+            }
+        };
+        let options = Arc::clone(&options);
+        std::thread::spawn(move || {
+            if let Err(err) = handle(stream, &options) {
+                eprintln!("Failed to answer a request: {:#}", err);
+            }
+        });
+    }
+    Ok(())
+}
+
+/// The error for a request body larger than [`MAX_BODY`], answered with a 413.
+#[derive(Debug)]
+struct BodyTooLarge(usize);
+
+impl std::fmt::Display for BodyTooLarge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "The request body of {} bytes is larger than the limit of {} bytes",
+            self.0, MAX_BODY
+        )
+    }
+}
+
+impl std::error::Error for BodyTooLarge {}
+
+/// A parsed HTTP request.
+struct Request {
+    method: String,
+    path: String,
+    query: String,
+    content_type: Option<String>,
+    body: Vec<u8>,
+}
+
+/// Reads one request from the connection, answers it and closes the connection.
+fn handle(mut stream: TcpStream, options: &ServerOptions) -> Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let (status, body) = match read_request(&mut stream) {
+        Ok(request) => {
+            let (status, body) = route(&request, options);
+            eprintln!("{} {} -> {}", request.method, request.path, status);
+            (status, body)
+        }
+        Err(err) if err.is::<BodyTooLarge>() => (413, json!({ "error": format!("{:#}", err) })),
+        Err(err) => (400, json!({ "error": format!("{:#}", err) })),
+    };
+    let body = serde_json::to_string_pretty(&body)?;
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        status,
+        reason(status),
+        body.len(),
+        body
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+/// Reads the request head and its body, whose length must be given up front.
+fn read_request(stream: &mut TcpStream) -> Result<Request> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 8192];
+    loop {
+        let n = stream.read(&mut chunk)?;
+        if n == 0 {
+            bail!("The connection closed before the request was complete");
+        }
+        buf.extend_from_slice(&chunk[..n]);
+
+        let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+        let mut parsed = httparse::Request::new(&mut headers);
+        let httparse::Status::Complete(head_len) = parsed.parse(&buf)? else {
+            if buf.len() > MAX_HEAD {
+                bail!("The request headers are too large");
+            }
+            continue;
+        };
+
+        let header = |name: &str| {
+            parsed
+                .headers
+                .iter()
+                .find(|h| h.name.eq_ignore_ascii_case(name))
+                .map(|h| String::from_utf8_lossy(h.value).trim().to_string())
+        };
+        if header("Transfer-Encoding").is_some() {
+            bail!("Chunked requests aren't supported; send a Content-Length");
+        }
+        let length = match header("Content-Length") {
+            Some(length) => length
+                .parse::<usize>()
+                .with_context(|| format!("Invalid Content-Length '{}'", length))?,
+            None => 0,
+        };
+        // Refuse before reading any of the body, so a client can't exhaust the memory.
+        if length > MAX_BODY {
+            return Err(BodyTooLarge(length).into());
+        }
+        let expects_continue =
+            header("Expect").is_some_and(|e| e.eq_ignore_ascii_case("100-continue"));
+        let target = parsed.path.unwrap_or("/");
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let (method, path, query) = (
+            parsed.method.unwrap_or("GET").to_string(),
+            path.to_string(),
+            query.to_string(),
+        );
+        let content_type = header("Content-Type");
+        let mut request = Request {
+            method,
+            path,
+            query,
+            content_type,
+            body: buf.split_off(head_len),
+        };
+        if expects_continue {
+            stream.write_all(b"HTTP/1.1 100 Continue\r\n\r\n")?;
+        }
+        if request.body.len() < length {
+            let remaining = (length - request.body.len()) as u64;
+            stream.take(remaining).read_to_end(&mut request.body)?;
+        }
+        if request.body.len() < length {
+            bail!("The connection closed before the request body was complete");
+        }
+        request.body.truncate(length);
+        return Ok(request);
+    }
+}
+
+/// Answers a request with a status code and a JSON body.
+fn route(request: &Request, options: &ServerOptions) -> (u16, Value) {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/health") => (200, json!({ "status": "ok" })),
+        ("POST", "/stats") => match stats(request, options) {
+            Ok(stats) => (200, stats),
+            Err(err) => (400, json!({ "error": format!("{:#}", err) })),
+        },
+        (_, "/health" | "/stats") => (405, json!({ "error": "Method not allowed" })),
+        _ => (404, json!({ "error": "Not found" })),
+    }
+}
+
+/// Calculates the statistics a `POST /stats` request asks for.
+fn stats(request: &Request, options: &ServerOptions) -> Result<Value> {
+    let is_json = request
+        .content_type
+        .as_deref()
+        .is_some_and(|t| t.starts_with("application/json"));
+    let (params, upload) = if is_json {
+        let params: StatsRequest =
+            serde_json::from_slice(&request.body).context("Invalid JSON request")?;
+        (params, None)
+    } else {
+        let params = StatsRequest::from_query(&request.query)?;
+        let upload = (!request.body.is_empty()).then(|| request.body.clone());
+        (params, upload)
+    };
+
+    let mut input = options.input.clone();
+    match params.file {
+        Some(_) if upload.is_some() => bail!("Either name a 'file' or upload the data, not both"),
+        Some(Files::One(path)) => input.paths = vec![path],
+        Some(Files::Many(paths)) => input.paths = paths,
+        None if upload.is_none() && input.paths.is_empty() => {
+            bail!("Name a 'file' to analyze, or upload the data as the request body")
+        }
+        None => {}
+    }
+    if let Some(format) = &params.format {
+        let format = <InputFormat as clap::ValueEnum>::from_str(format, true)
+            .map_err(|_| anyhow!("Unknown format '{}'", format))?;
+        input.format = Some(format);
+    }
+    if params.filter.is_some() {
+        input.filter = params.filter;
+    }
+
+    let mut lf = match upload {
+        Some(data) => scan_bytes(&input, data)?,
+        None => scan(&input)?,
+    };
+    let columns = if params.columns.is_empty() {
+        numeric_columns(&mut lf)?
+    } else {
+        params.columns
+    };
+    let stats_options = StatsOptions {
+        columns,
+        quantiles: params.quantiles,
+        ddof: params.ddof.unwrap_or(1),
+        group_by: params.group_by,
+        parsing: options.parsing.clone(),
+        moments: params.moments,
+        mad: params.mad,
+        extended: params.extended,
+        mode: params.mode,
+        approx: params.approx,
+        ..Default::default()
+    };
+    Ok(serde_json::to_value(compute_stats(lf, &stats_options)?)?)
+}
+
+/// Decodes `%XX` escapes, and `+` for spaces, in a query string component.
+fn percent_decode(text: &str) -> Result<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex = rest
+                    .get(..2)
+                    .and_then(|h| std::str::from_utf8(h).ok())
+                    .and_then(|h| u8::from_str_radix(h, 16).ok())
+                    .ok_or_else(|| anyhow!("Invalid escape in '{}'", text))?;
+                bytes.push(hex);
+                rest = &rest[2..];
+            }
+            _ => bytes.push(byte),
+        }
+    }
+    String::from_utf8(bytes).with_context(|| format!("Invalid UTF-8 in '{}'", text))
+}
+
+/// The reason phrase of the status codes the server uses.
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Content Too Large",
+        _ => "Internal Server Error",
+    }
+}
@@ -0,0 +1,486 @@
+//! Significance tests: of a numeric column between two inputs or two groups of one
+//! input, or across all the groups of an input, and of the association of two
+//! categorical columns.
+//!
+//! Welch's t-test asks whether the means differ without assuming equal variances. The
+//! Mann–Whitney U test asks whether values from one sample tend to be larger than the
+//! other's, using only their ranks, so it holds up for skewed data and outliers. A
+//! one-way analysis of variance (ANOVA) asks whether the means of any of several groups
+//! differ. For two categorical columns, a chi-square test asks whether they are
+//! independent.
+
+use anyhow::{Result, bail};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use statrs::distribution::{ChiSquared, ContinuousCDF, FisherSnedecor, Normal, StudentsT};
+
+use crate::engine::collect;
+use crate::stats::NumericParsing;
+
+/// Options controlling the tests.
+#[derive(Debug, Clone)]
+pub struct SignificanceOptions {
+    /// The p-value below which a difference is flagged as significant.
+    pub significance: f64,
+    /// How text values are cleaned before being parsed as numbers.
+    pub parsing: NumericParsing,
+}
+
+impl SignificanceOptions {
+    fn check(&self) -> Result<()> {
+        if !(self.significance > 0.0 && self.significance < 1.0) {
+            bail!(
+                "The significance level must be between 0 and 1, got {}",
+                self.significance
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Default for SignificanceOptions {
+    fn default() -> Self {
+        SignificanceOptions {
+            significance: 0.05,
+            parsing: NumericParsing::default(),
+        }
+    }
+}
+
+/// One of the two samples, described by its name and a few summary statistics.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SampleSummary {
+    /// The file or group the values came from.
+    pub name: String,
+    /// The number of numeric values.
+    pub count: usize,
+    pub mean: f64,
+    /// The sample standard deviation.
+    pub std: f64,
+    pub median: f64,
+}
+
+/// The result of one test.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestResult {
+    /// The test statistic: t for Welch's test, U of the first sample for Mann–Whitney.
+    pub statistic: f64,
+    /// The degrees of freedom of Welch's test (Welch–Satterthwaite), or `None`.
+    pub degrees_of_freedom: Option<f64>,
+    /// The two-sided probability of a difference at least this large if the samples
+    /// came from the same population.
+    pub p_value: f64,
+    /// Whether the p-value is below the significance level.
+    pub significant: bool,
+}
+
+/// The comparison of a column between two samples.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignificanceReport {
+    /// The name of the compared column.
+    pub column: String,
+    pub samples: [SampleSummary; 2],
+    /// The difference of the means, second minus first.
+    pub mean_difference: f64,
+    pub welch_t: TestResult,
+    /// The Mann–Whitney U test, using the normal approximation with a correction for
+    /// ties and for continuity.
+    pub mann_whitney_u: TestResult,
+    /// The p-value below which a difference is flagged.
+    pub significance: f64,
+}
+
+/// Compares the column between two inputs, each given with its name.
+pub fn compare_inputs(
+    first: (&str, LazyFrame),
+    second: (&str, LazyFrame),
+    column_name: &str,
+    options: &SignificanceOptions,
+) -> Result<SignificanceReport> {
+    let x = numeric_values(first.1, column_name, &options.parsing)?;
+    let y = numeric_values(second.1, column_name, &options.parsing)?;
+    compare_samples(column_name, (first.0, &x), (second.0, &y), options)
+}
+
+/// Compares the column between two groups of `group_by`. Without `groups`, the column
+/// must have exactly two distinct values.
+pub fn compare_groups(
+    lf: LazyFrame,
+    column_name: &str,
+    group_by: &str,
+    groups: Option<[String; 2]>,
+    options: &SignificanceOptions,
+) -> Result<SignificanceReport> {
+    let key = col(group_by).cast(DataType::String);
+    let groups = match groups {
+        Some(groups) => groups,
+        None => {
+            let distinct = collect(
+                lf.clone()
+                    .select([key.clone().drop_nulls().unique().sort(Default::default())]),
+            )?;
+            let values: Vec<String> = distinct
+                .column(group_by)?
+                .str()?
+                .into_no_null_iter()
+                .map(str::to_string)
+                .collect();
+            match <[String; 2]>::try_from(values) {
+                Ok(groups) => groups,
+                Err(values) => bail!(
+                    "Column '{}' has {} groups; name the two to compare",
+                    group_by,
+                    values.len()
+                ),
+            }
+        }
+    };
+
+    let sample = |group: &str| {
+        let lf = lf.clone().filter(key.clone().eq(lit(group)));
+        numeric_values(lf, column_name, &options.parsing)
+    };
+    let x = sample(&groups[0])?;
+    let y = sample(&groups[1])?;
+    let names = groups.map(|group| format!("{}={}", group_by, group));
+    compare_samples(column_name, (&names[0], &x), (&names[1], &y), options)
+}
+
+/// Runs both tests on two samples of values.
+pub fn compare_samples(
+    column_name: &str,
+    (x_name, x): (&str, &[f64]),
+    (y_name, y): (&str, &[f64]),
+    options: &SignificanceOptions,
+) -> Result<SignificanceReport> {
+    options.check()?;
+    for (name, values) in [(x_name, x), (y_name, y)] {
+        if values.len() < 2 {
+            bail!(
+                "'{}' needs at least two numeric values of '{}' to compare, found {}",
+                name,
+                column_name,
+                values.len()
+            );
+        }
+    }
+
+    let first = summarize(x_name, x);
+    let second = summarize(y_name, y);
+    let flag = |statistic, degrees_of_freedom, p_value: f64| TestResult {
+        statistic,
+        degrees_of_freedom,
+        p_value,
+        significant: p_value < options.significance,
+    };
+
+    let (t, df, p) = welch(&first, &second);
+    let welch_t = flag(t, Some(df), p);
+    let (u, p) = mann_whitney(x, y);
+    let mann_whitney_u = flag(u, None, p);
+
+    Ok(SignificanceReport {
+        column: column_name.to_string(),
+        mean_difference: second.mean - first.mean,
+        samples: [first, second],
+        welch_t,
+        mann_whitney_u,
+        significance: options.significance,
+    })
+}
+
+/// Reads the column's numeric values, skipping nulls and values that don't parse.
+fn numeric_values(lf: LazyFrame, column_name: &str, parsing: &NumericParsing) -> Result<Vec<f64>> {
+    parsing.check_strict(&lf, column_name)?;
+    let value = parsing.to_float(column_name);
+    let df = collect(lf.select([value.clone().filter(value.is_finite())]))?;
+    Ok(df.column(column_name)?.f64()?.into_no_null_iter().collect())
+}
+
+fn summarize(name: &str, values: &[f64]) -> SampleSummary {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let middle = sorted.len() / 2;
+    let median = if sorted.len().is_multiple_of(2) {
+        (sorted[middle - 1] + sorted[middle]) / 2.0
+    } else {
+        sorted[middle]
+    };
+    SampleSummary {
+        name: name.to_string(),
+        count: values.len(),
+        mean,
+        std: variance.sqrt(),
+        median,
+    }
+}
+
+/// Welch's t statistic, its degrees of freedom and two-sided p-value.
+fn welch(x: &SampleSummary, y: &SampleSummary) -> (f64, f64, f64) {
+    let x_error = x.std.powi(2) / x.count as f64;
+    let y_error = y.std.powi(2) / y.count as f64;
+    let std_error = (x_error + y_error).sqrt();
+    if std_error == 0.0 {
+        // Both samples are constant: either identical or certainly different.
+        return if x.mean == y.mean {
+            (0.0, f64::NAN, 1.0)
+        } else {
+            (f64::INFINITY.copysign(x.mean - y.mean), f64::NAN, 0.0)
+        };
+    }
+    let t = (x.mean - y.mean) / std_error;
+    let df = (x_error + y_error).powi(2)
+        / (x_error.powi(2) / (x.count - 1) as f64 + y_error.powi(2) / (y.count - 1) as f64);
+    let p = StudentsT::new(0.0, 1.0, df).map_or(f64::NAN, |dist| 2.0 * dist.sf(t.abs()));
+    (t, df, p.min(1.0))
+}
+
+/// The Mann–Whitney U statistic of the first sample and its two-sided p-value.
+fn mann_whitney(x: &[f64], y: &[f64]) -> (f64, f64) {
+    let mut values: Vec<(f64, bool)> = x
+        .iter()
+        .map(|&v| (v, true))
+        .chain(y.iter().map(|&v| (v, false)))
+        .collect();
+    values.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    // Tied values share the average of their ranks.
+    let mut x_rank_sum = 0.0;
+    let mut tie_term = 0.0;
+    let mut start = 0;
+    while start < values.len() {
+        let end = start + values[start..].partition_point(|v| v.0 == values[start].0);
+        let rank = (start + end + 1) as f64 / 2.0;
+        let in_x = values[start..end].iter().filter(|v| v.1).count();
+        x_rank_sum += rank * in_x as f64;
+        let ties = (end - start) as f64;
+        tie_term += ties.powi(3) - ties;
+        start = end;
+    }
+
+    let (n1, n2) = (x.len() as f64, y.len() as f64);
+    let n = n1 + n2;
+    let u = x_rank_sum - n1 * (n1 + 1.0) / 2.0;
+    let mean = n1 * n2 / 2.0;
+    let std = (n1 * n2 / 12.0 * ((n + 1.0) - tie_term / (n * (n - 1.0)))).sqrt();
+    if std == 0.0 {
+        return (u, 1.0);
+    }
+    let z = ((u - mean).abs() - 0.5).max(0.0) / std;
+    (u, (2.0 * Normal::standard().sf(z)).min(1.0))
+}
+
+/// A one-way analysis of variance of a column across the groups of another.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Anova {
+    /// The name of the analyzed column.
+    pub column: String,
+    /// The name of the column the data was grouped by.
+    pub group_by: String,
+    /// The number of groups with numeric values.
+    pub groups: usize,
+    /// The number of numeric values in all groups.
+    pub count: usize,
+    /// The mean square between the groups divided by the mean square within them.
+    pub f_statistic: f64,
+    /// The degrees of freedom between the groups: the number of groups minus 1.
+    pub df_between: usize,
+    /// The degrees of freedom within the groups: the number of values minus the
+    /// number of groups.
+    pub df_within: usize,
+    /// The probability of an F statistic at least this large if all groups had the same
+    /// mean.
+    pub p_value: f64,
+    /// Whether the p-value is below the significance level.
+    pub significant: bool,
+}
+
+/// Tests whether the column's mean differs between the groups of `group_by`, in a single
+/// pass over the data. The group of nulls counts as a group.
+pub fn one_way_anova(
+    lf: LazyFrame,
+    column_name: &str,
+    group_by: &str,
+    options: &SignificanceOptions,
+) -> Result<Anova> {
+    options.check()?;
+    options.parsing.check_strict(&lf, column_name)?;
+    let value = options.parsing.to_float(column_name);
+    let value = value.clone().filter(value.is_finite());
+    let df = collect(lf.group_by([col(group_by)]).agg([
+        value.clone().count().alias("count"),
+        value.clone().mean().alias("mean"),
+        value.var(1).alias("var"),
+    ]))?;
+
+    let counts = df.column("count")?.cast(&DataType::UInt64)?;
+    let groups: Vec<(f64, f64, f64)> = counts
+        .u64()?
+        .into_no_null_iter()
+        .zip(df.column("mean")?.f64()?)
+        .zip(df.column("var")?.f64()?)
+        .filter(|((n, _), _)| *n > 0)
+        .map(|((n, mean), var)| (n as f64, mean.unwrap_or(0.0), var.unwrap_or(0.0)))
+        .collect();
+    let count: f64 = groups.iter().map(|(n, _, _)| n).sum();
+    if groups.len() < 2 || count as usize <= groups.len() {
+        bail!(
+            "The analysis of variance of '{}' needs at least two groups of '{}' and more \
+             values than groups",
+            column_name,
+            group_by
+        );
+    }
+
+    let grand_mean = groups.iter().map(|(n, mean, _)| n * mean).sum::<f64>() / count;
+    let between: f64 = groups
+        .iter()
+        .map(|(n, mean, _)| n * (mean - grand_mean).powi(2))
+        .sum();
+    let within: f64 = groups.iter().map(|(n, _, var)| (n - 1.0) * var).sum();
+    let df_between = groups.len() - 1;
+    let df_within = count as usize - groups.len();
+    let f_statistic = (between / df_between as f64) / (within / df_within as f64);
+    let p_value = FisherSnedecor::new(df_between as f64, df_within as f64)
+        .map_or(f64::NAN, |dist| dist.sf(f_statistic));
+
+    Ok(Anova {
+        column: column_name.to_string(),
+        group_by: group_by.to_string(),
+        groups: groups.len(),
+        count: count as usize,
+        f_statistic,
+        df_between,
+        df_within,
+        p_value,
+        significant: p_value < options.significance,
+    })
+}
+
+/// The cross-tabulation of two categorical columns and a chi-square test of whether they
+/// are independent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChiSquareReport {
+    /// The column whose values label the rows of the table.
+    pub row_column: String,
+    /// The column whose values label the columns of the table.
+    pub column_column: String,
+    /// The distinct values of the row column, sorted.
+    pub row_values: Vec<String>,
+    /// The distinct values of the column column, sorted.
+    pub column_values: Vec<String>,
+    /// The number of rows with each combination of values, indexed like the values.
+    pub counts: Vec<Vec<usize>>,
+    /// The number of rows where both columns have a value; only these rows are counted.
+    pub total: usize,
+    /// Pearson's chi-square statistic of the counts against those expected if the columns
+    /// were independent.
+    pub chi_square: f64,
+    pub degrees_of_freedom: usize,
+    /// The probability of a chi-square statistic at least this large if the columns were
+    /// independent.
+    pub p_value: f64,
+    /// Cramér's V, the strength of the association from 0 (none) to 1 (complete).
+    pub cramers_v: f64,
+    /// The number of cells expecting fewer than 5 rows, which make the p-value unreliable.
+    pub sparse_cells: usize,
+    /// The p-value below which the association is flagged as significant.
+    pub significance: f64,
+    /// Whether the p-value is below the significance level.
+    pub significant: bool,
+}
+
+/// Counts the combinations of the values of two columns in a single pass, and tests
+/// whether the columns are independent. Rows where either column is null are skipped.
+pub fn chi_square_independence(
+    lf: LazyFrame,
+    row_column: &str,
+    column_column: &str,
+    options: &SignificanceOptions,
+) -> Result<ChiSquareReport> {
+    options.check()?;
+    const ROW: &str = "row";
+    const COLUMN: &str = "column";
+    let df = collect(
+        lf.select([
+            col(row_column).cast(DataType::String).alias(ROW),
+            col(column_column).cast(DataType::String).alias(COLUMN),
+        ])
+        .drop_nulls(None)
+        .group_by([col(ROW), col(COLUMN)])
+        .agg([len().alias("count")]),
+    )?;
+
+    let rows: Vec<&str> = df.column(ROW)?.str()?.into_no_null_iter().collect();
+    let columns: Vec<&str> = df.column(COLUMN)?.str()?.into_no_null_iter().collect();
+    let distinct = |values: &[&str]| {
+        let mut distinct: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+        distinct.sort();
+        distinct.dedup();
+        distinct
+    };
+    let row_values = distinct(&rows);
+    let column_values = distinct(&columns);
+    if row_values.len() < 2 || column_values.len() < 2 {
+        bail!(
+            "Both '{}' and '{}' need at least two distinct values to test their independence",
+            row_column,
+            column_column
+        );
+    }
+
+    let mut counts = vec![vec![0usize; column_values.len()]; row_values.len()];
+    let position =
+        |values: &[String], value: &str| values.binary_search_by(|v| v.as_str().cmp(value));
+    for ((row, column), count) in rows
+        .iter()
+        .zip(&columns)
+        .zip(df.column("count")?.idx()?.into_no_null_iter())
+    {
+        if let (Ok(r), Ok(c)) = (position(&row_values, row), position(&column_values, column)) {
+            counts[r][c] = count as usize;
+        }
+    }
+
+    let total: usize = counts.iter().flatten().sum();
+    let row_totals: Vec<usize> = counts.iter().map(|row| row.iter().sum()).collect();
+    let column_totals: Vec<usize> = (0..column_values.len())
+        .map(|c| counts.iter().map(|row| row[c]).sum())
+        .collect();
+    let mut chi_square = 0.0;
+    let mut sparse_cells = 0;
+    for (row, row_total) in counts.iter().zip(&row_totals) {
+        for (&count, column_total) in row.iter().zip(&column_totals) {
+            let expected = (row_total * column_total) as f64 / total as f64;
+            if expected < 5.0 {
+                sparse_cells += 1;
+            }
+            chi_square += (count as f64 - expected).powi(2) / expected;
+        }
+    }
+    let degrees_of_freedom = (row_values.len() - 1) * (column_values.len() - 1);
+    let p_value = ChiSquared::new(degrees_of_freedom as f64)
+        .expect("at least 1 degree of freedom is valid")
+        .sf(chi_square);
+    let smaller_side = row_values.len().min(column_values.len()) - 1;
+    let cramers_v = (chi_square / (total as f64 * smaller_side as f64)).sqrt();
+
+    Ok(ChiSquareReport {
+        row_column: row_column.to_string(),
+        column_column: column_column.to_string(),
+        row_values,
+        column_values,
+        counts,
+        total,
+        chi_square,
+        degrees_of_freedom,
+        p_value,
+        cramers_v,
+        sparse_cells,
+        significance: options.significance,
+        significant: p_value < options.significance,
+    })
+}
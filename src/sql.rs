@@ -0,0 +1,17 @@
+//! Running SQL queries against the input, using Polars' SQL engine.
+
+use anyhow::Result;
+use polars::prelude::*;
+use polars::sql::SQLContext;
+
+use crate::engine::collect;
+
+/// Runs a SQL query with the input registered as the table `table`, returning the result.
+///
+/// The query is translated into a lazy query plan, so it benefits from the same
+/// optimizations and streaming execution as the built-in commands.
+pub fn run_sql(lf: LazyFrame, table: &str, query: &str) -> Result<DataFrame> {
+    let mut context = SQLContext::new();
+    context.register(table, lf);
+    collect(context.execute(query)?)
+}
@@ -0,0 +1,1716 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use anyhow::{Result, anyhow, bail};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use statrs::distribution::{ContinuousCDF, Normal, StudentsT};
+
+use crate::bootstrap::Bootstrap;
+use crate::engine::{collect, collect_in_memory};
+use crate::input::{InputOptions, Sampling, expand_paths, input_name};
+use crate::schema::temporal_column;
+use crate::tdigest::TDigest;
+
+/// A container for the calculated statistics.
+///
+/// The serialized field names are part of the JSON output format and are kept stable.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelectedStats {
+    /// The name of the column the statistics were calculated for.
+    pub column: String,
+    /// The input file these statistics belong to, when each file was analyzed separately.
+    /// Statistics without a file cover all input files combined.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// The group these statistics belong to, when the data was grouped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<GroupKey>,
+    /// How the rows were sampled, when only a random sample of them was analyzed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample: Option<Sampling>,
+    /// The number of non-null values in the column.
+    pub count: usize,
+    /// The number of missing values in the column, including any configured null tokens.
+    pub null_count: usize,
+    /// The percentage of rows that are missing, or `None` when there are no rows.
+    pub null_percent: Option<f64>,
+    /// The number of values that are null after the numeric conversion: the missing values
+    /// plus any that couldn't be parsed as numbers. The numeric statistics only cover the rest.
+    pub numeric_null_count: usize,
+    /// The percentage of rows left null by the numeric conversion, or `None` when there are
+    /// no rows.
+    pub numeric_null_percent: Option<f64>,
+    /// The number of distinct non-null values in the column, estimated when
+    /// [`StatsOptions::approx`] is set.
+    pub n_unique: usize,
+    /// The minimum value in the column.
+    pub min: Option<f64>,
+    /// The maximum value in the column.
+    pub max: Option<f64>,
+    /// The sum of all values in the column.
+    pub sum: Option<f64>,
+    /// The mean (average) of all values in the column.
+    pub mean: Option<f64>,
+    /// The median (50th percentile) of all values in the column.
+    pub median: Option<f64>,
+    /// The standard deviation of all values in the column.
+    pub std: Option<f64>,
+    /// The variance of all values in the column.
+    pub var: Option<f64>,
+    /// The requested quantiles with their values, in the order they were requested.
+    pub quantiles: Vec<QuantileValue>,
+    /// The shape of the distribution, when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moments: Option<Moments>,
+    /// The standard error and confidence interval of the mean, when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<MeanConfidence>,
+    /// The bootstrap confidence intervals of the mean, median and quantiles, when
+    /// requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bootstrap: Option<Bootstrap>,
+    /// The median absolute deviation, when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mad: Option<Mad>,
+    /// The geometric and harmonic means, when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extended: Option<ExtendedStats>,
+    /// The most frequent value(s), when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<Mode>,
+    /// The sum and mean weighted by another column, when a weight column is given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weighted: Option<Weighted>,
+    /// The mean without the lowest and highest values, when a trim proportion is given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trimmed: Option<TrimmedMean>,
+    /// The mean with the lowest and highest values replaced by the nearest remaining
+    /// value, when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub winsorized: Option<TrimmedMean>,
+    /// The number of values below and above each threshold, when thresholds are given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thresholds: Option<ThresholdCounts>,
+    /// The range of a date or datetime column, which gets these instead of the numeric
+    /// statistics.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dates: Option<DateStats>,
+    /// The lengths of a text column's values, which gets these instead of the numeric
+    /// statistics when none of its values are numbers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<TextStats>,
+    /// The true and false counts of a boolean column, which gets these instead of the
+    /// numeric statistics.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boolean: Option<BooleanStats>,
+}
+
+impl SelectedStats {
+    /// Returns whether the column was analyzed as numbers, rather than by its type.
+    pub fn is_numeric(&self) -> bool {
+        self.dates.is_none() && self.text.is_none() && self.boolean.is_none()
+    }
+}
+
+/// The statistics of a boolean column.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BooleanStats {
+    /// The number of true values.
+    pub true_count: usize,
+    /// The number of false values.
+    pub false_count: usize,
+    /// The proportion of the non-null values that are true, or `None` without any.
+    pub true_ratio: Option<f64>,
+    /// The confidence level of the interval, e.g. 0.95.
+    pub level: f64,
+    /// The lower bound of the confidence interval of the true ratio.
+    pub lower: Option<f64>,
+    /// The upper bound of the confidence interval of the true ratio.
+    pub upper: Option<f64>,
+}
+
+impl BooleanStats {
+    /// Calculates the true ratio and its Wilson score interval, which unlike the normal
+    /// approximation stays within 0 and 1 for small counts and extreme ratios.
+    fn new(true_count: usize, false_count: usize, level: f64) -> Self {
+        let n = (true_count + false_count) as f64;
+        let true_ratio = (n > 0.0).then(|| true_count as f64 / n);
+        let z = Normal::standard().inverse_cdf((1.0 + level) / 2.0);
+        let interval = true_ratio.map(|p| {
+            let z2 = z * z;
+            let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+            let margin = z / (1.0 + z2 / n) * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+            (center - margin, center + margin)
+        });
+        BooleanStats {
+            true_count,
+            false_count,
+            true_ratio,
+            level,
+            lower: interval.map(|(lower, _)| lower),
+            upper: interval.map(|(_, upper)| upper),
+        }
+    }
+}
+
+/// The statistics of a text column.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextStats {
+    /// The length of the shortest value, in characters.
+    pub min_length: Option<usize>,
+    /// The length of the longest value, in characters.
+    pub max_length: Option<usize>,
+    /// The mean length of the values, in characters.
+    pub mean_length: Option<f64>,
+    /// The number of empty values, which are distinct from missing ones.
+    pub empty_count: usize,
+}
+
+/// The statistics of a date or datetime column.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DateStats {
+    /// The earliest value.
+    pub min: Option<String>,
+    /// The latest value.
+    pub max: Option<String>,
+    /// The time between the earliest and latest values, in days.
+    pub span_days: Option<f64>,
+    /// The number of values in each calendar year, in order.
+    pub years: Vec<PeriodCount>,
+    /// The number of values in each calendar month, in order.
+    pub months: Vec<PeriodCount>,
+}
+
+/// The number of values in a calendar period.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeriodCount {
+    /// The period, such as `2022` for a year or `2022-09` for a month.
+    pub period: String,
+    /// The number of values in the period.
+    pub count: usize,
+}
+
+/// How a column is analyzed, chosen by its type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnKind {
+    /// Numbers, or text parsed as numbers.
+    Numeric,
+    /// Dates and datetimes.
+    Temporal,
+    /// Text, which is analyzed as numbers if any of it parses as one.
+    Text,
+    /// True and false values.
+    Boolean,
+}
+
+impl ColumnKind {
+    fn of(dtype: &DataType) -> Self {
+        match dtype {
+            DataType::Date | DataType::Datetime(..) => ColumnKind::Temporal,
+            DataType::String => ColumnKind::Text,
+            DataType::Boolean => ColumnKind::Boolean,
+            _ => ColumnKind::Numeric,
+        }
+    }
+
+    /// Returns whether the column's values may be analyzed as numbers.
+    fn may_be_numeric(self) -> bool {
+        matches!(self, ColumnKind::Numeric | ColumnKind::Text)
+    }
+}
+
+/// How precisely the mean estimates the mean of the population the data was sampled from.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct MeanConfidence {
+    /// The confidence level of the interval, e.g. 0.95.
+    pub level: f64,
+    /// The standard error of the mean: the sample standard deviation over the square
+    /// root of the count.
+    pub std_error: Option<f64>,
+    /// The lower bound of the confidence interval, based on Student's t-distribution.
+    pub lower: Option<f64>,
+    /// The upper bound of the confidence interval.
+    pub upper: Option<f64>,
+}
+
+impl MeanConfidence {
+    /// Calculates the standard error and confidence interval from the basic statistics.
+    ///
+    /// Both need at least two values.
+    fn new(level: f64, count: usize, mean: Option<f64>, sample_std: Option<f64>) -> Self {
+        let interval = match (mean, sample_std) {
+            (Some(mean), Some(std)) if count >= 2 => {
+                let std_error = std / (count as f64).sqrt();
+                let t = StudentsT::new(0.0, 1.0, (count - 1) as f64)
+                    .ok()
+                    .map(|dist| dist.inverse_cdf((1.0 + level) / 2.0));
+                t.map(|t| (std_error, mean - t * std_error, mean + t * std_error))
+            }
+            _ => None,
+        };
+        MeanConfidence {
+            level,
+            std_error: interval.map(|(se, _, _)| se),
+            lower: interval.map(|(_, lower, _)| lower),
+            upper: interval.map(|(_, _, upper)| upper),
+        }
+    }
+}
+
+/// The median absolute deviation (MAD), a dispersion measure robust to outliers.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Mad {
+    /// The median of the absolute deviations from the median.
+    pub mad: Option<f64>,
+    /// The MAD multiplied by 1.4826, which estimates the standard deviation for normally
+    /// distributed data while ignoring outliers.
+    pub scaled: Option<f64>,
+}
+
+/// The factor that makes the MAD a consistent estimator of the normal standard deviation.
+const MAD_SCALE: f64 = 1.4826;
+
+/// The confidence level of the interval of a boolean column's true ratio, unless another
+/// is requested.
+pub const DEFAULT_CONFIDENCE_LEVEL: f64 = 0.95;
+
+/// The number of microseconds in a day, for date spans.
+const MICROSECONDS_PER_DAY: f64 = 86_400_000_000.0;
+
+/// Alternative means for strictly positive data such as rates, prices and ratios.
+///
+/// Both are only defined when no value is negative; the harmonic mean also requires every
+/// value to be non-zero. Otherwise they are `None`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ExtendedStats {
+    /// The n-th root of the product of the n values; zero if any value is zero.
+    pub geometric_mean: Option<f64>,
+    /// The number of values divided by the sum of their reciprocals.
+    pub harmonic_mean: Option<f64>,
+}
+
+/// A mean that limits the influence of the most extreme values.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TrimmedMean {
+    /// The proportion of values cut from, or clamped at, each end.
+    pub trim: f64,
+    /// The mean of the remaining values.
+    pub mean: Option<f64>,
+}
+
+/// How many values fall on either side of a set of thresholds, and between them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThresholdCounts {
+    /// The counts on either side of each threshold, in increasing order of threshold.
+    pub thresholds: Vec<ThresholdCount>,
+    /// The counts between consecutive thresholds, starting with the values below the
+    /// lowest threshold and ending with those at or above the highest.
+    pub buckets: Vec<BucketCount>,
+}
+
+/// The number of values on either side of a single threshold.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ThresholdCount {
+    /// The threshold.
+    pub threshold: f64,
+    /// The number of values less than the threshold.
+    pub below: usize,
+    /// The number of values greater than or equal to the threshold.
+    pub at_or_above: usize,
+}
+
+/// The number of values in a half-open range `[lower, upper)`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BucketCount {
+    /// The inclusive lower bound, or `None` for the bucket below the lowest threshold.
+    pub lower: Option<f64>,
+    /// The exclusive upper bound, or `None` for the bucket above the highest threshold.
+    pub upper: Option<f64>,
+    /// The number of values in the range.
+    pub count: usize,
+}
+
+impl ThresholdCounts {
+    /// Derives the bucket counts from the counts on either side of each threshold.
+    fn new(thresholds: Vec<ThresholdCount>) -> Self {
+        let mut buckets = Vec::with_capacity(thresholds.len() + 1);
+        let mut lower: Option<&ThresholdCount> = None;
+        for upper in &thresholds {
+            buckets.push(BucketCount {
+                lower: lower.map(|t| t.threshold),
+                upper: Some(upper.threshold),
+                count: upper.below - lower.map_or(0, |t| t.below),
+            });
+            lower = Some(upper);
+        }
+        if let Some(last) = lower {
+            buckets.push(BucketCount {
+                lower: Some(last.threshold),
+                upper: None,
+                count: last.at_or_above,
+            });
+        }
+        ThresholdCounts {
+            thresholds,
+            buckets,
+        }
+    }
+}
+
+/// Statistics weighted by the values of another column.
+///
+/// Rows where either the value or its weight is missing are left out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Weighted {
+    /// The name of the column holding the weights.
+    pub weight_column: String,
+    /// The sum of each value multiplied by its weight.
+    pub sum: Option<f64>,
+    /// The weighted sum divided by the sum of the weights, or `None` when the weights sum to zero.
+    pub mean: Option<f64>,
+}
+
+/// The most frequent value(s) of a column.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Mode {
+    /// The most frequent values in ascending order, limited to [`MAX_MODES`] when
+    /// several values are tied.
+    pub values: Vec<String>,
+    /// The total number of tied most frequent values; more than one for multi-modal columns.
+    pub count: usize,
+}
+
+/// The maximum number of tied modes reported for a multi-modal column.
+pub const MAX_MODES: usize = 5;
+
+/// Higher moments describing the shape of a column's distribution.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Moments {
+    /// The bias-corrected sample skewness; positive when the right tail is longer.
+    pub skew: Option<f64>,
+    /// The bias-corrected excess (Fisher) kurtosis; 0 for a normal distribution,
+    /// positive for heavier tails.
+    pub kurtosis: Option<f64>,
+}
+
+/// A single requested quantile and its calculated value.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct QuantileValue {
+    /// The requested quantile, between 0 and 1.
+    pub quantile: f64,
+    /// The value at that quantile, or `None` if the column had no numeric values.
+    pub value: Option<f64>,
+}
+
+/// Options controlling which statistics are calculated and how.
+#[derive(Debug, Clone)]
+pub struct StatsOptions {
+    /// The names of the columns to analyze.
+    pub columns: Vec<String>,
+    /// Quantiles to calculate for each column, each between 0 and 1.
+    pub quantiles: Vec<f64>,
+    /// Delta degrees of freedom for std and var: 0 for population, 1 for sample statistics.
+    pub ddof: u8,
+    /// Calculate the statistics separately for each distinct value of this column.
+    pub group_by: Option<String>,
+    /// With `group_by`, order the groups by this statistic of the first column, largest
+    /// first, rather than by group value.
+    pub sort_by: Option<GroupSort>,
+    /// With `group_by`, only keep this many groups, after sorting.
+    pub top: Option<usize>,
+    /// Calculate the statistics separately for each interval of a date or datetime column,
+    /// as a time series. Can't be combined with `group_by`.
+    pub time_buckets: Option<TimeBuckets>,
+    /// How text values are cleaned before being parsed as numbers.
+    pub parsing: NumericParsing,
+    /// Also calculate skewness and kurtosis.
+    pub moments: bool,
+    /// Also calculate the standard error of the mean and a confidence interval at this
+    /// level, between 0 and 1 (e.g. 0.95).
+    pub confidence_level: Option<f64>,
+    /// Also calculate the median absolute deviation. This takes a second pass over the data.
+    pub mad: bool,
+    /// Also calculate the geometric and harmonic means.
+    pub extended: bool,
+    /// Also find the most frequent value(s).
+    pub mode: bool,
+    /// Estimate the distinct count with HyperLogLog, and the median and quantiles with a
+    /// t-digest, rather than calculating them exactly. This keeps memory use bounded for
+    /// high-cardinality columns and lets them stream. Per time bucket, the median and
+    /// quantiles stay exact.
+    pub approx: bool,
+    /// Also calculate the sum and mean weighted by this column.
+    pub weight_column: Option<String>,
+    /// Also calculate the mean without this proportion of the values at each end,
+    /// between 0 and 0.5.
+    pub trim: Option<f64>,
+    /// With `trim`, also calculate the winsorized mean.
+    pub winsorize: bool,
+    /// Also count the values below and above each of these thresholds, which must be in
+    /// increasing order.
+    pub thresholds: Vec<f64>,
+}
+
+impl Default for StatsOptions {
+    fn default() -> Self {
+        Self {
+            columns: Vec::new(),
+            quantiles: Vec::new(),
+            ddof: 1,
+            group_by: None,
+            sort_by: None,
+            top: None,
+            time_buckets: None,
+            parsing: NumericParsing::default(),
+            moments: false,
+            confidence_level: None,
+            mad: false,
+            extended: false,
+            mode: false,
+            approx: false,
+            weight_column: None,
+            trim: None,
+            winsorize: false,
+            thresholds: Vec::new(),
+        }
+    }
+}
+
+impl StatsOptions {
+    /// Returns whether the median and quantiles are estimated with sketches.
+    fn sketches_quantiles(&self) -> bool {
+        self.approx && self.time_buckets.is_none()
+    }
+}
+
+/// Aggregation into consecutive intervals of a date or datetime column, e.g. days.
+#[derive(Debug, Clone)]
+pub struct TimeBuckets {
+    /// The column holding each row's time. Text is parsed as datetimes, with the format
+    /// inferred unless it's given with a `--dtype` override.
+    pub column: String,
+    /// The length of each interval in Polars' duration notation, e.g. `1h`, `1d`, `1w` or
+    /// `1mo`.
+    pub every: String,
+}
+
+/// A statistic groups can be ordered by.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GroupSort {
+    Count,
+    NullCount,
+    NUnique,
+    Min,
+    Max,
+    Sum,
+    Mean,
+    Median,
+    Std,
+    Var,
+}
+
+impl GroupSort {
+    /// The name of the statistic's aggregation.
+    fn stat_name(self) -> &'static str {
+        match self {
+            GroupSort::Count => "count",
+            GroupSort::NullCount => "null_count",
+            GroupSort::NUnique => "n_unique",
+            GroupSort::Min => "min",
+            GroupSort::Max => "max",
+            GroupSort::Sum => "sum",
+            GroupSort::Mean => "mean",
+            GroupSort::Median => "median",
+            GroupSort::Std => "std",
+            GroupSort::Var => "var",
+        }
+    }
+}
+
+/// The values of a column that couldn't be parsed as numbers.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Unparseable {
+    /// The number of values that couldn't be parsed.
+    pub count: usize,
+    /// The most common of the distinct values, most common first.
+    pub values: Vec<UnparseableValue>,
+}
+
+/// A distinct value that couldn't be parsed as a number.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnparseableValue {
+    /// The value as it appears in the input.
+    pub value: String,
+    /// How many times it occurs.
+    pub count: usize,
+}
+
+/// The most unparseable values listed when strict parsing fails.
+const STRICT_EXAMPLES: usize = 5;
+
+/// Cleaning applied to a column's values before they are cast to Float64.
+///
+/// Without cleaning, values such as "$1,234.56" fail the cast and silently become null.
+#[derive(Debug, Clone, Default)]
+pub struct NumericParsing {
+    /// Remove currency symbols such as `$`, `€` and `£`.
+    pub strip_currency: bool,
+    /// Remove this thousands separator, e.g. `,` in "1,234".
+    pub thousands_sep: Option<String>,
+    /// Remove leading and trailing whitespace.
+    pub strip_whitespace: bool,
+    /// Fail if any value can't be parsed, rather than treating it as missing.
+    pub strict: bool,
+}
+
+impl NumericParsing {
+    /// Returns true if any cleaning step is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.strip_currency || self.thousands_sep.is_some() || self.strip_whitespace
+    }
+
+    /// Builds an expression that cleans the column's values and casts them to Float64.
+    ///
+    /// When no cleaning is enabled this is a plain cast, so numeric columns don't pay
+    /// for a round trip through strings.
+    pub fn to_float(&self, column_name: &str) -> Expr {
+        if !self.is_enabled() {
+            return col(column_name).cast(DataType::Float64);
+        }
+
+        let mut expr = col(column_name).cast(DataType::String);
+        if self.strip_currency {
+            // `\p{Sc}` is the Unicode "currency symbol" category.
+            expr = expr.str().replace_all(lit(r"\p{Sc}"), lit(""), false);
+        }
+        if let Some(sep) = &self.thousands_sep {
+            expr = expr.str().replace_all(lit(sep.as_str()), lit(""), true);
+        }
+        // Always strip whitespace last, since removing a symbol can expose some (e.g. "$ 12").
+        expr.str().strip_chars(lit(NULL)).cast(DataType::Float64)
+    }
+
+    /// Counts the values of the column that are present but can't be parsed as numbers,
+    /// keeping the `limit` most common of them.
+    ///
+    /// Columns that aren't text are not checked, as their values are always numbers or
+    /// always missing.
+    pub fn unparseable_values(
+        &self,
+        lf: &LazyFrame,
+        column_name: &str,
+        limit: usize,
+    ) -> Result<Unparseable> {
+        let mut lf = lf.clone();
+        if lf.collect_schema()?.get(column_name) != Some(&DataType::String) {
+            return Ok(Unparseable::default());
+        }
+
+        let unparseable = col(column_name)
+            .is_not_null()
+            .and(self.to_float(column_name).is_null());
+        let df = collect(
+            lf.filter(unparseable)
+                .group_by([col(column_name)])
+                .agg([len().alias("count")])
+                .select([
+                    col(column_name),
+                    col("count"),
+                    col("count").sum().alias("total"),
+                ])
+                .sort_by_exprs(
+                    [col("count"), col(column_name)],
+                    SortMultipleOptions::default().with_order_descending_multi([true, false]),
+                )
+                .limit(limit as IdxSize),
+        )?;
+        if df.height() == 0 {
+            return Ok(Unparseable::default());
+        }
+        let values = df
+            .column(column_name)?
+            .str()?
+            .into_no_null_iter()
+            .zip(df.column("count")?.idx()?.into_no_null_iter())
+            .map(|(value, count)| UnparseableValue {
+                value: value.to_string(),
+                count: count as usize,
+            })
+            .collect();
+        Ok(Unparseable {
+            count: df.column("total")?.get(0)?.try_extract::<u64>()? as usize,
+            values,
+        })
+    }
+
+    /// In strict mode, fails with [`StrictParsingFailed`] if any value of the column is
+    /// present but can't be parsed as a number.
+    ///
+    /// This takes a pass over the column, except for columns that aren't text, whose
+    /// values are always numbers or always missing.
+    pub fn check_strict(&self, lf: &LazyFrame, column_name: &str) -> Result<()> {
+        const ROW: &str = "__row";
+        if !self.strict {
+            return Ok(());
+        }
+        let mut lf = lf.clone();
+        if lf.collect_schema()?.get(column_name) != Some(&DataType::String) {
+            return Ok(());
+        }
+
+        let unparseable = col(column_name)
+            .is_not_null()
+            .and(self.to_float(column_name).is_null());
+        let df = collect(
+            lf.with_row_index(ROW, None)
+                .filter(unparseable)
+                .select([col(ROW), col(column_name), len().alias("count")])
+                .limit(STRICT_EXAMPLES as IdxSize),
+        )?;
+        if df.height() == 0 {
+            return Ok(());
+        }
+        let examples = df
+            .column(ROW)?
+            .idx()?
+            .into_no_null_iter()
+            .zip(df.column(column_name)?.str()?.into_no_null_iter())
+            .map(|(row, value)| (row, value.to_string()))
+            .collect();
+        Err(StrictParsingFailed {
+            column: column_name.to_string(),
+            file: None,
+            count: df.column("count")?.get(0)?.try_extract()?,
+            examples,
+        }
+        .into())
+    }
+}
+
+/// The error of strict parsing when values of a column can't be parsed as numbers.
+#[derive(Debug)]
+pub struct StrictParsingFailed {
+    pub column: String,
+    /// The file the values were read from, when the input's files are analyzed
+    /// separately.
+    pub file: Option<String>,
+    /// The number of values that can't be parsed.
+    pub count: u64,
+    /// The first few of them with their row numbers, counting from 0 among the rows left
+    /// after the input's filter and sampling.
+    pub examples: Vec<(IdxSize, String)>,
+}
+
+impl StrictParsingFailed {
+    /// Describes the values, naming the input they were read from and what their row
+    /// numbers count.
+    pub fn describe(&self, input: &InputOptions) -> String {
+        let source = self.file.clone().unwrap_or_else(|| input_name(input));
+        let several =
+            self.file.is_none() && expand_paths(&input.paths).is_ok_and(|paths| paths.len() > 1);
+        let across = if several {
+            " across the files in turn"
+        } else {
+            ""
+        };
+        let after = match (input.filter.is_some(), input.sample.is_some()) {
+            (true, true) => ", after the filter and sampling",
+            (true, false) => ", after the filter",
+            (false, true) => ", after sampling",
+            (false, false) => "",
+        };
+        self.message(
+            &format!(" of '{}'", source),
+            &format!("{}{}", across, after),
+        )
+    }
+
+    fn message(&self, source: &str, rows: &str) -> String {
+        let examples: Vec<String> = self
+            .examples
+            .iter()
+            .map(|(row, value)| format!("row {}: '{}'", row, value))
+            .collect();
+        format!(
+            "Column '{}'{} has {} value(s) that can't be parsed as numbers, e.g. {} (rows \
+             counted from 0{})",
+            self.column,
+            source,
+            self.count,
+            examples.join(", "),
+            rows
+        )
+    }
+}
+
+impl fmt::Display for StrictParsingFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message("", ""))
+    }
+}
+
+impl std::error::Error for StrictParsingFailed {}
+
+/// Identifies the group a set of statistics was calculated for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupKey {
+    /// The name of the column the data was grouped by.
+    pub column: String,
+    /// The group's value in that column, or `None` for the group of nulls.
+    pub value: Option<String>,
+}
+
+/// Calculates descriptive statistics for the specified columns of a LazyFrame.
+///
+/// This function uses the Polars lazy API to build an optimized query plan,
+/// which is ideal for performance on large datasets. The statistics for every
+/// column are calculated in a single pass over the data.
+///
+/// When `options.group_by` is given, the statistics are calculated per distinct value of
+/// that column and the results are ordered by column, then by group value, or by
+/// `options.sort_by` and limited to `options.top` groups. `options.time_buckets` groups
+/// the rows by time interval instead, each group's value being the interval's start.
+///
+/// Date and datetime columns get their range and counts per year and month instead of
+/// the numeric statistics, text columns without any numbers their lengths, and boolean
+/// columns their true and false counts.
+pub fn compute_stats(mut lf: LazyFrame, options: &StatsOptions) -> Result<Vec<SelectedStats>> {
+    let column_names = &options.columns;
+    let quantiles = options.quantiles.as_slice();
+    let group_by = options.group_by.as_deref();
+
+    // Reject out-of-range quantiles up front rather than letting the query fail mid-way.
+    if let Some(q) = quantiles.iter().find(|q| !(0.0..=1.0).contains(*q)) {
+        bail!(
+            "Quantile {} is out of range, expected a value between 0 and 1",
+            q
+        );
+    }
+    if let Some(level) = options.confidence_level
+        && !(level > 0.0 && level < 1.0)
+    {
+        bail!(
+            "Confidence level {} is out of range, expected a value between 0 and 1",
+            level
+        );
+    }
+    if let Some(trim) = options.trim
+        && !(0.0..0.5).contains(&trim)
+    {
+        bail!(
+            "Trim proportion {} is out of range, expected at least 0 and less than 0.5",
+            trim
+        );
+    }
+
+    if !options.thresholds.windows(2).all(|pair| pair[0] < pair[1]) {
+        bail!(
+            "Thresholds must be distinct and in increasing order, got {:?}",
+            options.thresholds
+        );
+    }
+    if let Some(t) = options.thresholds.iter().find(|t| t.is_nan()) {
+        bail!("Threshold {} is not a number", t);
+    }
+    if options.time_buckets.is_some() {
+        if group_by.is_some() {
+            bail!("Time buckets can't be combined with grouping by a column");
+        }
+        if options.mad || options.mode {
+            bail!("The MAD and mode can't be calculated per time bucket");
+        }
+    }
+
+    // Columns that aren't in the input are left for the query to report.
+    let schema = lf.collect_schema()?;
+    let kinds: Vec<ColumnKind> = column_names
+        .iter()
+        .map(|name| schema.get(name).map_or(ColumnKind::Numeric, ColumnKind::of))
+        .collect();
+    for (name, kind) in column_names.iter().zip(&kinds) {
+        if kind.may_be_numeric() {
+            options.parsing.check_strict(&lf, name)?;
+        }
+    }
+    if let Some(weight_column) = &options.weight_column {
+        options.parsing.check_strict(&lf, weight_column)?;
+    }
+
+    // With `approx`, the median and quantiles are estimated by sketches the query fills
+    // in, so it can stream. Time buckets aren't a column to tell the sketches' groups by.
+    let sketches: Vec<Option<Sketches>> = kinds
+        .iter()
+        .map(|kind| (options.sketches_quantiles() && kind.may_be_numeric()).then(Sketches::default))
+        .collect();
+
+    // Build a query plan to calculate all statistics for all columns in a single pass.
+    let aggregations: Vec<Expr> = column_names
+        .iter()
+        .enumerate()
+        .flat_map(|(i, column_name)| {
+            column_aggregations(i, column_name, kinds[i], options, sketches[i].as_ref())
+        })
+        .collect();
+
+    // Trimming slices the sorted values, which the streaming engine can't be trusted with.
+    let collect = if options.trim.is_some() {
+        collect_in_memory
+    } else {
+        collect
+    };
+
+    // Execute the query. This materializes the result into a DataFrame.
+    // Without grouping the resulting DataFrame has a single row with our calculated stats;
+    // with grouping it has one row per group, sorted by the group value.
+    let group_by = group_by.or(options.time_buckets.as_ref().map(|b| b.column.as_str()));
+    let Some(group_column) = group_by else {
+        let stats_df = collect(lf.clone().select(aggregations))?;
+
+        let mut stats = column_names
+            .iter()
+            .enumerate()
+            .map(|(i, column_name)| {
+                let mut stats =
+                    extract_stats(&stats_df, 0, i, column_name, kinds[i], options, None)?;
+                if let Some(sketches) = &sketches[i] {
+                    sketches.fill(&mut stats)?;
+                }
+                Ok(stats)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if options.mad {
+            add_mads(lf.clone(), &mut stats, &kinds, options)?;
+        }
+        if options.mode {
+            add_modes(lf.clone(), &mut stats, options)?;
+        }
+        add_period_counts(lf, &mut stats, &kinds, options)?;
+        return Ok(stats);
+    };
+
+    let grouped = match &options.time_buckets {
+        Some(buckets) => time_bucketed(lf.clone(), buckets)?.agg(aggregations),
+        None => lf.clone().group_by([col(group_column)]).agg(aggregations),
+    };
+    let order = |grouped: LazyFrame| {
+        let grouped = match options.sort_by {
+            // Ties are ordered by group value, and groups whose statistic is null come last.
+            Some(sort) => grouped.sort(
+                [stat_alias(0, sort.stat_name()).as_str(), group_column],
+                SortMultipleOptions::default()
+                    .with_order_descending_multi([true, false])
+                    .with_nulls_last(true),
+            ),
+            None => grouped.sort([group_column], SortMultipleOptions::default()),
+        };
+        match options.top {
+            Some(top) => grouped.limit(top as IdxSize),
+            None => grouped,
+        }
+    };
+    let stats_df = match (&sketches[0], options.sort_by) {
+        // A sketched median is only known once the query has run, so the groups are
+        // ordered by it afterwards.
+        (Some(sketches), Some(GroupSort::Median)) => {
+            let mut stats_df = collect(grouped)?;
+            let medians: Float64Chunked = stats_df
+                .column(group_column)?
+                .as_materialized_series()
+                .iter()
+                .map(|value| {
+                    let group = GroupKey {
+                        column: group_column.to_string(),
+                        value: any_value_to_string(value),
+                    };
+                    sketches.quantile(Some(&group), 0.5)
+                })
+                .collect();
+            stats_df.with_column(medians.with_name(stat_alias(0, "median").into()))?;
+            collect(order(stats_df.lazy()))?
+        }
+        _ => collect(order(grouped))?,
+    };
+
+    let group_values = stats_df.column(group_column)?;
+    let mut stats = Vec::with_capacity(column_names.len() * stats_df.height());
+    for (i, column_name) in column_names.iter().enumerate() {
+        for row in 0..stats_df.height() {
+            let group = GroupKey {
+                column: group_column.to_string(),
+                value: any_value_to_string(group_values.get(row)?),
+            };
+            let mut group_stats = extract_stats(
+                &stats_df,
+                row,
+                i,
+                column_name,
+                kinds[i],
+                options,
+                Some(group),
+            )?;
+            if let Some(sketches) = &sketches[i] {
+                sketches.fill(&mut group_stats)?;
+            }
+            stats.push(group_stats);
+        }
+    }
+    if options.mad {
+        add_mads(lf.clone(), &mut stats, &kinds, options)?;
+    }
+    if options.mode {
+        add_modes(lf.clone(), &mut stats, options)?;
+    }
+    add_period_counts(lf, &mut stats, &kinds, options)?;
+    Ok(stats)
+}
+
+/// Groups the rows by the time interval they fall in, using Polars' dynamic group by.
+///
+/// Rows whose time is missing or doesn't parse are left out.
+fn time_bucketed(mut lf: LazyFrame, buckets: &TimeBuckets) -> Result<LazyGroupBy> {
+    let every = Duration::try_parse(&buckets.every).map_err(|_| {
+        anyhow!(
+            "Invalid interval '{}', expected a duration such as 1h, 1d, 1w, 1mo or 1y",
+            buckets.every
+        )
+    })?;
+    let column = buckets.column.as_str();
+    let time = temporal_column(&mut lf, column)?;
+
+    let options = DynamicGroupOptions {
+        every,
+        period: every,
+        offset: Duration::parse("0ns"),
+        ..Default::default()
+    };
+    // The dynamic group by expects the rows in time order.
+    Ok(lf
+        .with_column(time)
+        .drop_nulls(Some(cols([column])))
+        .sort([column], SortMultipleOptions::default())
+        .group_by_dynamic(col(column), [], options))
+}
+
+/// Fills in the median absolute deviation of every requested column.
+///
+/// The deviations are taken from the medians of the first pass, so this second pass
+/// aggregates a plain expression per column and stays lazy. When grouping, each group's
+/// deviations are taken from the group's own median.
+fn add_mads(
+    lf: LazyFrame,
+    stats: &mut [SelectedStats],
+    kinds: &[ColumnKind],
+    options: &StatsOptions,
+) -> Result<()> {
+    let to_mad = |mad: Option<f64>| Mad {
+        mad,
+        scaled: mad.map(|m| m * MAD_SCALE),
+    };
+
+    let Some(group_column) = options.group_by.as_deref() else {
+        let aggregations: Vec<Expr> = stats
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                if !s.is_numeric() {
+                    return lit(NULL)
+                        .cast(DataType::Float64)
+                        .alias(stat_alias(i, "mad"));
+                }
+                let median = lit(s.median.unwrap_or(f64::NAN));
+                (options.parsing.to_float(&s.column) - median)
+                    .abs()
+                    .median()
+                    .alias(stat_alias(i, "mad"))
+            })
+            .collect();
+        let mads_df = collect(lf.select(aggregations))?;
+        for (i, entry) in stats.iter_mut().enumerate() {
+            let mad = mads_df.column(&stat_alias(i, "mad"))?.get(0)?;
+            let mad = match mad {
+                AnyValue::Null => None,
+                av => Some(av.try_extract::<f64>()?).filter(|m| !m.is_nan()),
+            };
+            entry.mad = Some(to_mad(mad));
+        }
+        return Ok(());
+    };
+
+    let aggregations: Vec<Expr> = options
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(i, column_name)| {
+            if !kinds[i].may_be_numeric() {
+                return lit(NULL)
+                    .cast(DataType::Float64)
+                    .alias(stat_alias(i, "mad"));
+            }
+            let values = options.parsing.to_float(column_name);
+            (values.clone() - values.median())
+                .abs()
+                .median()
+                .alias(stat_alias(i, "mad"))
+        })
+        .collect();
+    let mads_df = collect(lf.group_by([col(group_column)]).agg(aggregations))?;
+
+    let groups = mads_df.column(group_column)?;
+    let group_rows = (0..mads_df.height())
+        .map(|row| Ok((any_value_to_string(groups.get(row)?), row)))
+        .collect::<Result<Vec<_>>>()?;
+    for (i, column_name) in options.columns.iter().enumerate() {
+        let mads = mads_df.column(&stat_alias(i, "mad"))?;
+        for entry in stats.iter_mut().filter(|s| &s.column == column_name) {
+            let group = entry.group.as_ref().and_then(|g| g.value.clone());
+            let mad = match group_rows.iter().find(|(key, _)| *key == group) {
+                Some((_, row)) => mads.get(*row)?.try_extract::<f64>().ok(),
+                None => None,
+            };
+            entry.mad = Some(to_mad(mad));
+        }
+    }
+    Ok(())
+}
+
+/// Fills in the most frequent value(s) of every requested column.
+///
+/// The modes come from a separate pass per column that counts each distinct raw value,
+/// so they work for categorical columns too, and keeps every value tied for the top count.
+fn add_modes(lf: LazyFrame, stats: &mut [SelectedStats], options: &StatsOptions) -> Result<()> {
+    const FREQUENCY: &str = "__frequency";
+    let group_by = options.group_by.as_deref();
+
+    for column_name in &options.columns {
+        let keys: Vec<&str> = group_by.into_iter().chain([column_name.as_str()]).collect();
+        let key_exprs: Vec<Expr> = keys.iter().map(|key| col(*key)).collect();
+        let max_frequency = match group_by {
+            Some(group_column) => col(FREQUENCY).max().over([col(group_column)]),
+            None => col(FREQUENCY).max(),
+        };
+        let modes_df = collect(
+            lf.clone()
+                .filter(col(column_name.as_str()).is_not_null())
+                .group_by(key_exprs)
+                .agg([len().alias(FREQUENCY)])
+                .filter(col(FREQUENCY).eq(max_frequency))
+                .sort(keys.clone(), SortMultipleOptions::default()),
+        )?;
+
+        // Modes keyed by group value; the key is always `None` without grouping.
+        let values = modes_df.column(column_name)?;
+        let groups = group_by.map(|g| modes_df.column(g)).transpose()?;
+        let mut modes: Vec<(Option<String>, Mode)> = Vec::new();
+        for row in 0..modes_df.height() {
+            let group = match groups {
+                Some(groups) => any_value_to_string(groups.get(row)?),
+                None => None,
+            };
+            if modes.last().is_none_or(|(last, _)| *last != group) {
+                let mode = Mode {
+                    values: Vec::new(),
+                    count: 0,
+                };
+                modes.push((group.clone(), mode));
+            }
+            let (_, mode) = modes.last_mut().expect("a mode was just pushed");
+            if mode.values.len() < MAX_MODES {
+                mode.values.extend(any_value_to_string(values.get(row)?));
+            }
+            mode.count += 1;
+        }
+
+        for entry in stats.iter_mut().filter(|s| &s.column == column_name) {
+            let group = entry.group.as_ref().and_then(|g| g.value.clone());
+            let mode = modes
+                .iter()
+                .find(|(key, _)| *key == group)
+                .map(|(_, mode)| mode.clone())
+                .unwrap_or(Mode {
+                    values: Vec::new(),
+                    count: 0,
+                });
+            entry.mode = Some(mode);
+        }
+    }
+    Ok(())
+}
+
+/// Fills in the counts per year and month of every date or datetime column.
+///
+/// This takes a pass per such column, counting the values per month; the years are
+/// summed from the months. Without a group column to key on, time buckets don't get
+/// these counts.
+fn add_period_counts(
+    lf: LazyFrame,
+    stats: &mut [SelectedStats],
+    kinds: &[ColumnKind],
+    options: &StatsOptions,
+) -> Result<()> {
+    const MONTH: &str = "__month";
+    const COUNT: &str = "__count";
+    let group_by = options.group_by.as_deref();
+
+    for (column_name, _) in options
+        .columns
+        .iter()
+        .zip(kinds)
+        .filter(|(_, kind)| **kind == ColumnKind::Temporal)
+    {
+        let month = col(column_name.as_str())
+            .dt()
+            .strftime("%Y-%m")
+            .alias(MONTH);
+        let mut keys: Vec<Expr> = group_by.map(col).into_iter().collect();
+        keys.push(month);
+        let mut sort_keys: Vec<&str> = group_by.into_iter().collect();
+        sort_keys.push(MONTH);
+        let counts_df = collect(
+            lf.clone()
+                .filter(col(column_name.as_str()).is_not_null())
+                .group_by(keys)
+                .agg([len().alias(COUNT)])
+                .sort(sort_keys, SortMultipleOptions::default()),
+        )?;
+
+        let months = counts_df.column(MONTH)?;
+        let counts = counts_df.column(COUNT)?;
+        let groups = group_by.map(|g| counts_df.column(g)).transpose()?;
+        let mut rows = Vec::with_capacity(counts_df.height());
+        for row in 0..counts_df.height() {
+            let group = match groups {
+                Some(groups) => any_value_to_string(groups.get(row)?),
+                None => None,
+            };
+            let period = any_value_to_string(months.get(row)?).unwrap_or_default();
+            let count = counts.get(row)?.try_extract::<u32>()? as usize;
+            rows.push((group, PeriodCount { period, count }));
+        }
+
+        for entry in stats.iter_mut().filter(|s| &s.column == column_name) {
+            let Some(dates) = &mut entry.dates else {
+                continue;
+            };
+            if entry.group.is_some() && group_by.is_none() {
+                continue;
+            }
+            let group = entry.group.as_ref().and_then(|g| g.value.clone());
+            for (_, month) in rows.iter().filter(|(key, _)| *key == group) {
+                let year = &month.period[..4];
+                match dates.years.last_mut() {
+                    Some(last) if last.period == year => last.count += month.count,
+                    _ => dates.years.push(PeriodCount {
+                        period: year.to_string(),
+                        count: month.count,
+                    }),
+                }
+                dates.months.push(month.clone());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Renders a value as plain text, without the quotes `AnyValue` adds to strings.
+pub(crate) fn any_value_to_string(value: AnyValue) -> Option<String> {
+    match value {
+        AnyValue::Null => None,
+        AnyValue::String(s) => Some(s.to_string()),
+        AnyValue::StringOwned(s) => Some(s.to_string()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// Builds the name of a statistic's result column for the column at `index`.
+///
+/// Prefixing with the column's position keeps the names unique even when the
+/// requested column names themselves collide with a statistic name.
+fn stat_alias(index: usize, stat_name: &str) -> String {
+    format!("{}_{}", index, stat_name)
+}
+
+/// The t-digests of a column's numeric values, one per group, filled in as the
+/// statistics query streams through the values.
+#[derive(Clone, Default)]
+struct Sketches(Arc<Mutex<HashMap<Option<String>, Sketch>>>);
+
+/// The t-digest of a group's values, with the number of values fed to it. The digest
+/// leaves NaNs out, which the query counts like any other number.
+#[derive(Default)]
+struct Sketch {
+    digest: TDigest,
+    values: usize,
+}
+
+impl Sketch {
+    fn add(&mut self, value: f64) {
+        self.digest.add(value);
+        self.values += 1;
+    }
+}
+
+impl Sketches {
+    /// Returns an aggregation that adds each value to the sketch of its group in
+    /// `group_column`, or to a single sketch without one.
+    ///
+    /// The sketches are filled as a side effect of the aggregation, so [`Sketches::fill`]
+    /// checks that every value went through it exactly once.
+    fn feed(&self, values: Expr, group_column: Option<&str>) -> Expr {
+        let sketches = self.clone();
+        let groups: Vec<Expr> = group_column.map(col).into_iter().collect();
+        values
+            .map_many(
+                move |columns: &mut [Column]| {
+                    let values = columns[0].as_materialized_series().f64()?;
+                    let mut digests = sketches.0.lock().unwrap_or_else(PoisonError::into_inner);
+                    match columns.get(1) {
+                        None => {
+                            let sketch = digests.entry(None).or_default();
+                            values.iter().flatten().for_each(|value| sketch.add(value));
+                        }
+                        Some(groups) => {
+                            for (row, value) in values.iter().enumerate() {
+                                if let Some(value) = value {
+                                    let group = any_value_to_string(groups.get(row)?);
+                                    digests.entry(group).or_default().add(value);
+                                }
+                            }
+                        }
+                    }
+                    Ok(Some(columns[0].clone()))
+                },
+                &groups,
+                GetOutput::same_type(),
+            )
+            .null_count()
+    }
+
+    /// Estimates the quantile `q` of the values of a group, or of all the values.
+    fn quantile(&self, group: Option<&GroupKey>, q: f64) -> Option<f64> {
+        let mut digests = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        digests
+            .get_mut(&group.and_then(|g| g.value.clone()))?
+            .digest
+            .quantile(q)
+    }
+
+    /// Sets the estimated median and quantiles of the statistics of a numeric column,
+    /// failing if its sketch wasn't fed each of the column's numbers once.
+    fn fill(&self, stats: &mut SelectedStats) -> Result<()> {
+        if !stats.is_numeric() {
+            return Ok(());
+        }
+        let numbers = stats.count + stats.null_count - stats.numeric_null_count;
+        let fed = {
+            let digests = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+            let group = stats.group.as_ref().and_then(|g| g.value.clone());
+            digests.get(&group).map_or(0, |sketch| sketch.values)
+        };
+        if fed != numbers {
+            bail!(
+                "The sketch of column '{}' was fed {} values instead of {}; run without \
+                 --approx to calculate the exact median and quantiles",
+                stats.column,
+                fed,
+                numbers
+            );
+        }
+        stats.median = self.quantile(stats.group.as_ref(), 0.5);
+        for q in &mut stats.quantiles {
+            q.value = self.quantile(stats.group.as_ref(), q.quantile);
+        }
+        Ok(())
+    }
+}
+
+/// Builds the aggregation expressions for a single column. With `sketches`, the median
+/// and quantiles are estimated by them instead.
+fn column_aggregations(
+    index: usize,
+    column_name: &str,
+    kind: ColumnKind,
+    options: &StatsOptions,
+    sketches: Option<&Sketches>,
+) -> Vec<Expr> {
+    let alias = |stat_name: &str| stat_alias(index, stat_name);
+    // Distinct values are counted on the raw column, so this works for any type.
+    let distinct = col(column_name).drop_nulls();
+    let n_unique = if options.approx {
+        distinct.approx_n_unique()
+    } else {
+        distinct.n_unique()
+    };
+    let counts = [
+        // The `count` aggregation works on any type, no cast needed.
+        col(column_name).count().alias(alias("count")),
+        col(column_name).null_count().alias(alias("null_count")),
+        n_unique.alias(alias("n_unique")),
+    ];
+
+    if kind == ColumnKind::Temporal {
+        // The span is measured on the values as microseconds since the epoch.
+        let micros = col(column_name)
+            .cast(DataType::Datetime(TimeUnit::Microseconds, None))
+            .cast(DataType::Int64);
+        let mut aggregations = counts.to_vec();
+        aggregations.extend([
+            col(column_name).min().alias(alias("date_min")),
+            col(column_name).max().alias(alias("date_max")),
+            (micros.clone().max() - micros.min()).alias(alias("span")),
+        ]);
+        return aggregations;
+    }
+
+    if kind == ColumnKind::Boolean {
+        let mut aggregations = counts.to_vec();
+        aggregations.extend([
+            col(column_name).sum().alias(alias("true_count")),
+            col(column_name).not().sum().alias(alias("false_count")),
+        ]);
+        return aggregations;
+    }
+
+    // For numeric stats, we first clean and cast the column to f64.
+    let values = options.parsing.to_float(column_name);
+    let mut aggregations = counts.to_vec();
+    if kind == ColumnKind::Text {
+        // Whether these or the numeric statistics are reported depends on the values.
+        let lengths = col(column_name).str().len_chars();
+        aggregations.extend([
+            lengths.clone().min().alias(alias("min_length")),
+            lengths.clone().max().alias(alias("max_length")),
+            lengths.mean().alias(alias("mean_length")),
+            col(column_name)
+                .eq(lit(""))
+                .sum()
+                .alias(alias("empty_count")),
+        ]);
+    }
+    aggregations.extend([
+        values
+            .clone()
+            .null_count()
+            .alias(alias("numeric_null_count")),
+        values.clone().min().alias(alias("min")),
+        values.clone().max().alias(alias("max")),
+        values.clone().sum().alias(alias("sum")),
+        values.clone().mean().alias(alias("mean")),
+        values.clone().std(options.ddof).alias(alias("std")),
+        values.clone().var(options.ddof).alias(alias("var")),
+    ]);
+
+    if let Some(sketches) = sketches {
+        aggregations.push(
+            sketches
+                .feed(values.clone(), options.group_by.as_deref())
+                .alias(alias("sketched")),
+        );
+    } else {
+        aggregations.push(values.clone().median().alias(alias("median")));
+        // Each requested quantile becomes its own aggregation, named by its position in the list.
+        aggregations.extend(options.quantiles.iter().enumerate().map(|(q_index, q)| {
+            values
+                .clone()
+                .quantile(lit(*q), QuantileMethod::Linear)
+                .alias(alias(&format!("quantile_{}", q_index)))
+        }));
+    }
+
+    if let Some(weight_column) = &options.weight_column {
+        let weights = options.parsing.to_float(weight_column);
+        // Only weights paired with a value count towards the total weight.
+        let total_weight = weights.clone().filter(values.clone().is_not_null()).sum();
+        let weighted_sum = (values.clone() * weights).sum();
+        aggregations.extend([
+            weighted_sum.clone().alias(alias("weighted_sum")),
+            when(total_weight.clone().eq(lit(0.0)))
+                .then(lit(NULL).cast(DataType::Float64))
+                .otherwise(weighted_sum / total_weight)
+                .alias(alias("weighted_mean")),
+        ]);
+    }
+
+    if let Some(trim) = options.trim {
+        // Cut the same number of values, rounded down, from each end of the sorted values.
+        let sorted = values.clone().drop_nulls().sort(SortOptions::default());
+        let n = values.clone().count().cast(DataType::Int64);
+        let cut = (n.clone().cast(DataType::Float64) * lit(trim))
+            .floor()
+            .cast(DataType::Int64);
+        let kept = n.clone() - cut.clone() * lit(2);
+        let trimmed = sorted.slice(cut.clone(), kept);
+        aggregations.push(trimmed.clone().mean().alias(alias("trimmed_mean")));
+
+        if options.winsorize {
+            // The cut values are replaced by the smallest and largest values that were kept.
+            let clamped =
+                cut.cast(DataType::Float64) * (trimmed.clone().min() + trimmed.clone().max());
+            aggregations.push(
+                ((trimmed.sum() + clamped) / n.cast(DataType::Float64))
+                    .alias(alias("winsorized_mean")),
+            );
+        }
+    }
+
+    if options.extended {
+        let min = values.clone().min();
+        aggregations.extend([
+            // Averaging logarithms avoids overflowing the product of many values.
+            when(min.clone().gt(lit(0.0)))
+                .then(values.clone().log(std::f64::consts::E).mean().exp())
+                .when(min.clone().eq(lit(0.0)))
+                .then(lit(0.0))
+                .otherwise(lit(NULL).cast(DataType::Float64))
+                .alias(alias("geometric_mean")),
+            when(min.gt(lit(0.0)))
+                .then(
+                    values.clone().count().cast(DataType::Float64)
+                        / (lit(1.0) / values.clone()).sum(),
+                )
+                .otherwise(lit(NULL).cast(DataType::Float64))
+                .alias(alias("harmonic_mean")),
+        ]);
+    }
+
+    for (t_index, threshold) in options.thresholds.iter().enumerate() {
+        aggregations.extend([
+            values
+                .clone()
+                .lt(lit(*threshold))
+                .sum()
+                .alias(alias(&format!("below_{}", t_index))),
+            values
+                .clone()
+                .gt_eq(lit(*threshold))
+                .sum()
+                .alias(alias(&format!("at_or_above_{}", t_index))),
+        ]);
+    }
+
+    if options.moments {
+        aggregations.extend([
+            values.clone().skew(false).alias(alias("skew")),
+            values
+                .clone()
+                .kurtosis(true, false)
+                .alias(alias("kurtosis")),
+        ]);
+    }
+
+    aggregations
+}
+
+/// Extracts the statistics for the column at `index` from the given row of the results DataFrame.
+fn extract_stats(
+    stats_df: &DataFrame,
+    row: usize,
+    index: usize,
+    column_name: &str,
+    kind: ColumnKind,
+    options: &StatsOptions,
+    group: Option<GroupKey>,
+) -> Result<SelectedStats> {
+    // The counts are a special case as they're u32s, not optional f64s.
+    let get_count = |stat_name: &str| -> Result<usize> {
+        let any_value = stats_df.column(&stat_alias(index, stat_name))?.get(row)?;
+        Ok(any_value.try_extract::<u32>()? as usize)
+    };
+
+    let count = get_count("count")?;
+    let null_count = get_count("null_count")?;
+    let numeric_null_count = if kind.may_be_numeric() {
+        get_count("numeric_null_count")?
+    } else {
+        null_count
+    };
+    let rows = count + null_count;
+    let percent_of_rows = |n: usize| (rows > 0).then(|| n as f64 / rows as f64 * 100.0);
+
+    // Text columns get text statistics when none of their values could be parsed as a
+    // number, and are treated as numbers otherwise.
+    let as_text = kind == ColumnKind::Text && count > 0 && numeric_null_count == rows;
+    let numeric = kind.may_be_numeric() && !as_text;
+
+    // Helper to extract an optional f64 stat value from the results DataFrame. Only
+    // numeric columns have these, so the others get `None` throughout.
+    let get_optional_f64 = |stat_name: &str| -> Result<Option<f64>> {
+        if !numeric {
+            return Ok(None);
+        }
+        let any_value = stats_df.column(&stat_alias(index, stat_name))?.get(row)?;
+        match any_value {
+            AnyValue::Null => Ok(None),
+            // The `try_extract` method will handle the conversion from AnyValue to f64.
+            // The `?` will propagate any PolarsError, which gets converted into an anyhow::Error.
+            av => Ok(Some(av.try_extract()?)),
+        }
+    };
+
+    // Threshold counts likewise only apply to numeric columns.
+    let get_numeric_count =
+        |stat_name: &str| -> Result<usize> { if numeric { get_count(stat_name) } else { Ok(0) } };
+
+    // Extract all the required stats using the helpers.
+    let stats = SelectedStats {
+        column: column_name.to_string(),
+        file: None,
+        group,
+        sample: None,
+        count,
+        null_count,
+        null_percent: percent_of_rows(null_count),
+        numeric_null_count,
+        numeric_null_percent: percent_of_rows(numeric_null_count),
+        n_unique: get_count("n_unique")?,
+        min: get_optional_f64("min")?,
+        max: get_optional_f64("max")?,
+        sum: get_optional_f64("sum")?,
+        mean: get_optional_f64("mean")?,
+        // Sketched values are filled in afterwards.
+        median: if options.sketches_quantiles() {
+            None
+        } else {
+            get_optional_f64("median")?
+        },
+        std: get_optional_f64("std")?,
+        var: get_optional_f64("var")?,
+        quantiles: options
+            .quantiles
+            .iter()
+            .enumerate()
+            .map(|(q_index, q)| {
+                Ok(QuantileValue {
+                    quantile: *q,
+                    value: if options.sketches_quantiles() {
+                        None
+                    } else {
+                        get_optional_f64(&format!("quantile_{}", q_index))?
+                    },
+                })
+            })
+            .collect::<Result<_>>()?,
+        moments: if options.moments {
+            Some(Moments {
+                skew: get_optional_f64("skew")?,
+                kurtosis: get_optional_f64("kurtosis")?,
+            })
+        } else {
+            None
+        },
+        confidence: None,
+        bootstrap: None,
+        mad: None,
+        extended: if options.extended {
+            Some(ExtendedStats {
+                geometric_mean: get_optional_f64("geometric_mean")?,
+                harmonic_mean: get_optional_f64("harmonic_mean")?,
+            })
+        } else {
+            None
+        },
+        mode: None,
+        weighted: match &options.weight_column {
+            Some(weight_column) => Some(Weighted {
+                weight_column: weight_column.clone(),
+                sum: get_optional_f64("weighted_sum")?,
+                mean: get_optional_f64("weighted_mean")?,
+            }),
+            None => None,
+        },
+        trimmed: match options.trim {
+            Some(trim) => Some(TrimmedMean {
+                trim,
+                mean: get_optional_f64("trimmed_mean")?,
+            }),
+            None => None,
+        },
+        winsorized: match options.trim {
+            Some(trim) if options.winsorize => Some(TrimmedMean {
+                trim,
+                mean: get_optional_f64("winsorized_mean")?,
+            }),
+            _ => None,
+        },
+        thresholds: if options.thresholds.is_empty() {
+            None
+        } else {
+            let counts = options
+                .thresholds
+                .iter()
+                .enumerate()
+                .map(|(t_index, threshold)| {
+                    Ok(ThresholdCount {
+                        threshold: *threshold,
+                        below: get_numeric_count(&format!("below_{}", t_index))?,
+                        at_or_above: get_numeric_count(&format!("at_or_above_{}", t_index))?,
+                    })
+                })
+                .collect::<Result<_>>()?;
+            Some(ThresholdCounts::new(counts))
+        },
+        dates: if kind == ColumnKind::Temporal {
+            let get_string = |stat_name: &str| -> Result<Option<String>> {
+                let column = stats_df.column(&stat_alias(index, stat_name))?;
+                Ok(any_value_to_string(column.get(row)?))
+            };
+            let span = stats_df.column(&stat_alias(index, "span"))?.get(row)?;
+            Some(DateStats {
+                min: get_string("date_min")?,
+                max: get_string("date_max")?,
+                span_days: match span {
+                    AnyValue::Null => None,
+                    av => Some(av.try_extract::<i64>()? as f64 / MICROSECONDS_PER_DAY),
+                },
+                // Filled in by a separate pass.
+                years: Vec::new(),
+                months: Vec::new(),
+            })
+        } else {
+            None
+        },
+        boolean: if kind == ColumnKind::Boolean {
+            let level = options.confidence_level.unwrap_or(DEFAULT_CONFIDENCE_LEVEL);
+            Some(BooleanStats::new(
+                get_count("true_count")?,
+                get_count("false_count")?,
+                level,
+            ))
+        } else {
+            None
+        },
+        text: if as_text {
+            let get_length = |stat_name: &str| -> Result<Option<f64>> {
+                match stats_df.column(&stat_alias(index, stat_name))?.get(row)? {
+                    AnyValue::Null => Ok(None),
+                    av => Ok(Some(av.try_extract()?)),
+                }
+            };
+            Some(TextStats {
+                min_length: get_length("min_length")?.map(|l| l as usize),
+                max_length: get_length("max_length")?.map(|l| l as usize),
+                mean_length: get_length("mean_length")?,
+                empty_count: get_count("empty_count")?,
+            })
+        } else {
+            None
+        },
+    };
+
+    // The standard error always uses the sample standard deviation, whatever the ddof.
+    // Both only count the values that parsed as numbers, which the mean and var cover.
+    let numeric_count = rows - numeric_null_count;
+    let confidence = options.confidence_level.map(|level| {
+        let sample_std = stats.var.filter(|_| numeric_count >= 2).map(|var| {
+            let ddof = options.ddof as f64;
+            (var * (numeric_count as f64 - ddof) / (numeric_count as f64 - 1.0)).sqrt()
+        });
+        MeanConfidence::new(level, numeric_count, stats.mean, sample_std)
+    });
+
+    Ok(SelectedStats {
+        confidence,
+        ..stats
+    })
+}
@@ -0,0 +1,340 @@
+//! Rendering of results through user-written templates, in a subset of the Handlebars
+//! syntax, so they can be formatted exactly as needed without post-processing JSON.
+//!
+//! Templates see the results as they are serialized to JSON, and support:
+//!
+//! - `{{path}}` to insert a value, e.g. `{{input}}`, `{{column}}`, `{{moments.skew}}` or
+//!   `{{quantiles.0.value}}`. `this` is the current value, `../` goes up a level and
+//!   `@index`, `@first` and `@last` describe the current `each` entry. Values are
+//!   inserted as they are, without HTML escaping; `{{{path}}}` is accepted too.
+//! - `{{round path digits}}` to insert a number with a fixed number of decimals.
+//! - `{{#each path}}...{{else}}...{{/each}}` to repeat a section for every entry of a
+//!   list or mapping, or for nothing.
+//! - `{{#if path}}...{{else}}...{{/if}}` and `{{#unless path}}...{{/unless}}`, where
+//!   missing values, `null`, `false`, `0`, `""` and empty lists count as false.
+//! - `{{! comments }}`, and `~` at either end of a tag, like `{{~#each stats~}}`, to
+//!   trim the whitespace next to it.
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde_json::Value;
+
+/// A parsed template.
+#[derive(Debug, Clone)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    Value(String),
+    Helper(String, Vec<String>),
+    Block {
+        kind: Block,
+        path: String,
+        body: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Block {
+    Each,
+    If,
+    Unless,
+}
+
+impl Block {
+    fn name(self) -> &'static str {
+        match self {
+            Block::Each => "each",
+            Block::If => "if",
+            Block::Unless => "unless",
+        }
+    }
+}
+
+/// A block being parsed, with the nodes before it and, after an `else`, its body.
+struct OpenBlock {
+    kind: Block,
+    path: String,
+    outer: Vec<Node>,
+    body: Option<Vec<Node>>,
+}
+
+impl Template {
+    /// Reads and parses a template file.
+    pub fn from_file(path: &str) -> Result<Self> {
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read template '{}'", path))?;
+        source
+            .parse()
+            .with_context(|| format!("Invalid template '{}'", path))
+    }
+
+    /// Renders the template with the given value as its context.
+    pub fn render(&self, context: &Value) -> Result<String> {
+        let mut out = String::new();
+        let scope = Scope {
+            value: context,
+            parent: None,
+            entry: None,
+        };
+        render_nodes(&self.nodes, &scope, &mut out)?;
+        Ok(out)
+    }
+}
+
+impl std::str::FromStr for Template {
+    type Err = anyhow::Error;
+
+    fn from_str(source: &str) -> Result<Self> {
+        let mut stack: Vec<OpenBlock> = Vec::new();
+        let mut nodes: Vec<Node> = Vec::new();
+        let mut rest = source;
+        let mut trim_next = false;
+        while !rest.is_empty() {
+            let (text, tag) = match rest.find("{{") {
+                Some(start) => (&rest[..start], Some(&rest[start..])),
+                None => (rest, None),
+            };
+            let mut text = text.to_string();
+            if trim_next {
+                text = text.trim_start().to_string();
+            }
+            let Some(tag) = tag else {
+                nodes.push(Node::Text(text));
+                break;
+            };
+
+            let triple = tag.starts_with("{{{");
+            let (open, close) = if triple { ("{{{", "}}}") } else { ("{{", "}}") };
+            let end = tag
+                .find(close)
+                .ok_or_else(|| anyhow!("A tag isn't closed: '{}'", first_line(tag)))?;
+            let mut inner = &tag[open.len()..end];
+            rest = &tag[end + close.len()..];
+            if let Some(trimmed) = inner.strip_prefix('~') {
+                text = text.trim_end().to_string();
+                inner = trimmed;
+            }
+            trim_next = inner.ends_with('~');
+            let inner = inner.strip_suffix('~').unwrap_or(inner).trim();
+            if !text.is_empty() {
+                nodes.push(Node::Text(text));
+            }
+
+            if inner.starts_with('!') {
+                continue;
+            }
+            if let Some(opening) = inner.strip_prefix('#') {
+                let (name, path) = opening
+                    .split_once(char::is_whitespace)
+                    .unwrap_or((opening, ""));
+                let kind = match name {
+                    "each" => Block::Each,
+                    "if" => Block::If,
+                    "unless" => Block::Unless,
+                    _ => bail!("Unknown block '{}'", name),
+                };
+                if path.trim().is_empty() {
+                    bail!("The '{}' block needs a value", name);
+                }
+                stack.push(OpenBlock {
+                    kind,
+                    path: path.trim().to_string(),
+                    outer: std::mem::take(&mut nodes),
+                    body: None,
+                });
+            } else if inner == "else" {
+                let Some(block) = stack.last_mut() else {
+                    bail!("'else' outside of a block");
+                };
+                if block.body.is_some() {
+                    bail!("The '{}' block has more than one 'else'", block.kind.name());
+                }
+                block.body = Some(std::mem::take(&mut nodes));
+            } else if let Some(name) = inner.strip_prefix('/') {
+                let name = name.trim();
+                let Some(block) = stack.pop() else {
+                    bail!("'{{{{/{}}}}}' closes no block", name);
+                };
+                if name != block.kind.name() {
+                    bail!(
+                        "'{{{{/{}}}}}' closes an '{}' block",
+                        name,
+                        block.kind.name()
+                    );
+                }
+                let (body, otherwise) = match block.body {
+                    Some(body) => (body, std::mem::replace(&mut nodes, block.outer)),
+                    None => (std::mem::replace(&mut nodes, block.outer), Vec::new()),
+                };
+                nodes.push(Node::Block {
+                    kind: block.kind,
+                    path: block.path,
+                    body,
+                    otherwise,
+                });
+            } else {
+                let mut words = inner.split_whitespace().map(str::to_string);
+                let first = words.next().ok_or_else(|| anyhow!("An empty tag"))?;
+                let args: Vec<String> = words.collect();
+                if args.is_empty() {
+                    nodes.push(Node::Value(first));
+                } else {
+                    nodes.push(Node::Helper(first, args));
+                }
+            }
+        }
+        if let Some(block) = stack.last() {
+            bail!(
+                "The '{}' block of '{}' is never closed",
+                block.kind.name(),
+                block.path
+            );
+        }
+        Ok(Template { nodes })
+    }
+}
+
+/// The first line of some template text, for error messages.
+fn first_line(text: &str) -> &str {
+    text.lines().next().unwrap_or(text)
+}
+
+/// The value a template is rendering, with the values around it.
+struct Scope<'a> {
+    value: &'a Value,
+    parent: Option<&'a Scope<'a>>,
+    /// The position and number of entries, within an `each` block.
+    entry: Option<(usize, usize)>,
+}
+
+impl Scope<'_> {
+    /// Looks up a path such as `this`, `../input`, `@index` or `moments.skew`.
+    fn lookup(&self, path: &str) -> Value {
+        if let Some(parent_path) = path.strip_prefix("../") {
+            return self
+                .parent
+                .map_or(Value::Null, |parent| parent.lookup(parent_path));
+        }
+        if let Some((index, len)) = self.entry {
+            match path {
+                "@index" => return Value::from(index),
+                "@first" => return Value::from(index == 0),
+                "@last" => return Value::from(index + 1 == len),
+                _ => {}
+            }
+        }
+        let path = path.strip_prefix("this").unwrap_or(path);
+        let mut value = self.value;
+        for key in path.split(['.', '/']).filter(|k| !k.is_empty()) {
+            value = match value {
+                Value::Object(map) => map.get(key).unwrap_or(&Value::Null),
+                Value::Array(items) => key
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|i| items.get(i))
+                    .unwrap_or(&Value::Null),
+                _ => &Value::Null,
+            };
+        }
+        value.clone()
+    }
+}
+
+fn render_nodes(nodes: &[Node], scope: &Scope, out: &mut String) -> Result<()> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Value(path) => out.push_str(&display(&scope.lookup(path))),
+            Node::Helper(name, args) => out.push_str(&helper(name, args, scope)?),
+            Node::Block {
+                kind: Block::Each,
+                path,
+                body,
+                otherwise,
+            } => {
+                let value = scope.lookup(path);
+                let entries: Vec<&Value> = match &value {
+                    Value::Array(items) => items.iter().collect(),
+                    Value::Object(map) => map.values().collect(),
+                    _ => Vec::new(),
+                };
+                if entries.is_empty() {
+                    render_nodes(otherwise, scope, out)?;
+                }
+                for (i, entry) in entries.iter().enumerate() {
+                    let inner = Scope {
+                        value: entry,
+                        parent: Some(scope),
+                        entry: Some((i, entries.len())),
+                    };
+                    render_nodes(body, &inner, out)?;
+                }
+            }
+            Node::Block {
+                kind,
+                path,
+                body,
+                otherwise,
+            } => {
+                let truthy = is_truthy(&scope.lookup(path));
+                if truthy == (*kind == Block::If) {
+                    render_nodes(body, scope, out)?;
+                } else {
+                    render_nodes(otherwise, scope, out)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Applies a helper to its arguments: paths, numbers or quoted strings.
+fn helper(name: &str, args: &[String], scope: &Scope) -> Result<String> {
+    let arg = |i: usize| -> Value {
+        let Some(arg) = args.get(i) else {
+            return Value::Null;
+        };
+        if let Some(text) = arg.strip_prefix('"').and_then(|a| a.strip_suffix('"')) {
+            return Value::from(text);
+        }
+        match arg.parse::<f64>() {
+            Ok(number) => Value::from(number),
+            Err(_) => scope.lookup(arg),
+        }
+    };
+    match name {
+        "round" => {
+            let digits = arg(1).as_f64().unwrap_or(0.0).max(0.0) as usize;
+            Ok(match arg(0).as_f64() {
+                Some(number) => format!("{:.*}", digits, number),
+                None => String::new(),
+            })
+        }
+        _ => bail!("Unknown helper '{}'", name),
+    }
+}
+
+/// Formats a value for the output: text as it is, missing values as nothing, and lists
+/// and mappings as JSON.
+fn display(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64() != Some(0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(_) => true,
+    }
+}
@@ -0,0 +1,316 @@
+//! An interactive terminal explorer of the input: its columns, the statistics and a
+//! histogram of the selected column, and quick filters, all calculated on demand.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use polars::prelude::*;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Bar, BarChart, BarGroup, Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::histogram::{Histogram, HistogramOptions, compute_histogram};
+use crate::input::{InputOptions, input_name, scan};
+use crate::output::stat_rows;
+use crate::preview::count_rows;
+use crate::stats::{NumericParsing, StatsOptions, compute_stats};
+
+/// The quantiles shown with the other statistics.
+const QUANTILES: [f64; 2] = [0.25, 0.75];
+
+/// The widest the column list gets, as a fraction of the screen.
+const MAX_LIST_SHARE: usize = 3;
+
+/// What to explore, and how.
+#[derive(Debug, Clone, Default)]
+pub struct TuiOptions {
+    /// The input to read. Its filter is the one in effect at the start.
+    pub input: InputOptions,
+    /// How text values are cleaned before being parsed as numbers.
+    pub parsing: NumericParsing,
+    /// The column selected at the start, or the first one.
+    pub column: Option<String>,
+}
+
+/// Opens the explorer on the terminal, until the user quits.
+pub fn explore(options: TuiOptions) -> Result<()> {
+    // Read the input first, so problems with it are reported like for other commands.
+    let mut explorer = Explorer::new(options)?;
+    let mut terminal = ratatui::init();
+    let result = explorer.run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+/// The statistics and histogram of a column, or why they couldn't be calculated.
+struct ColumnView {
+    stats: Result<Vec<(String, String)>, String>,
+    histogram: Result<Histogram, String>,
+}
+
+/// Whether keys move around or edit the filter.
+enum Mode {
+    Browse,
+    Filter(String),
+}
+
+struct Explorer {
+    options: TuiOptions,
+    lf: LazyFrame,
+    /// The columns with their types, in file order.
+    columns: Vec<(String, String)>,
+    rows: u64,
+    selected: usize,
+    /// The scroll position of the column list.
+    list: ListState,
+    /// The number of columns the list showed when last drawn, to page through them.
+    page: usize,
+    views: HashMap<String, ColumnView>,
+    mode: Mode,
+    /// A message for the status line, such as an invalid filter.
+    message: Option<String>,
+}
+
+impl Explorer {
+    fn new(options: TuiOptions) -> Result<Self> {
+        let mut lf = scan(&options.input)?;
+        let columns: Vec<(String, String)> = lf
+            .collect_schema()?
+            .iter()
+            .map(|(name, dtype)| (name.to_string(), dtype.to_string()))
+            .collect();
+        let selected = match &options.column {
+            Some(column) => columns
+                .iter()
+                .position(|(name, _)| name == column)
+                .ok_or_else(|| anyhow::anyhow!("No column named '{}'", column))?,
+            None => 0,
+        };
+        let rows = count_rows(lf.clone())?;
+        Ok(Self {
+            options,
+            lf,
+            columns,
+            rows,
+            selected,
+            list: ListState::default(),
+            page: 1,
+            views: HashMap::new(),
+            mode: Mode::Browse,
+            message: None,
+        })
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            if let Some((name, _)) = self.columns.get(self.selected).cloned()
+                && !self.views.contains_key(&name)
+            {
+                self.message = Some(format!("Calculating the statistics of '{}'...", name));
+                terminal.draw(|frame| self.render(frame))?;
+                self.message = None;
+                let view = self.view(&name);
+                self.views.insert(name, view);
+            }
+            terminal.draw(|frame| self.render(frame))?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if !self.handle_key(key)? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Calculates the statistics and histogram of a column.
+    fn view(&self, column: &str) -> ColumnView {
+        let options = StatsOptions {
+            columns: vec![column.to_string()],
+            quantiles: QUANTILES.to_vec(),
+            parsing: self.options.parsing.clone(),
+            ..Default::default()
+        };
+        let stats = compute_stats(self.lf.clone(), &options)
+            .map(|stats| stats.first().map(stat_rows).unwrap_or_default())
+            .map_err(|err| format!("{:#}", err));
+        let histogram_options = HistogramOptions {
+            parsing: self.options.parsing.clone(),
+            ..Default::default()
+        };
+        let histogram = compute_histogram(self.lf.clone(), column, &histogram_options)
+            .map_err(|err| format!("{:#}", err));
+        ColumnView { stats, histogram }
+    }
+
+    /// Acts on a key press, returning false when the user quits.
+    fn handle_key(&mut self, key: KeyEvent) -> Result<bool> {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Ok(false);
+        }
+        self.message = None;
+        if let Mode::Filter(text) = &mut self.mode {
+            match key.code {
+                KeyCode::Esc => self.mode = Mode::Browse,
+                KeyCode::Enter => {
+                    let filter = (!text.trim().is_empty()).then(|| text.trim().to_string());
+                    self.mode = Mode::Browse;
+                    self.apply_filter(filter);
+                }
+                KeyCode::Backspace => {
+                    text.pop();
+                }
+                KeyCode::Char(c) => text.push(c),
+                _ => {}
+            }
+            return Ok(true);
+        }
+
+        let last = self.columns.len().saturating_sub(1);
+        let page = self.page.max(1);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.selected = (self.selected + 1).min(last),
+            KeyCode::PageUp => self.selected = self.selected.saturating_sub(page),
+            KeyCode::PageDown => self.selected = (self.selected + page).min(last),
+            KeyCode::Home | KeyCode::Char('g') => self.selected = 0,
+            KeyCode::End | KeyCode::Char('G') => self.selected = last,
+            KeyCode::Char('/') | KeyCode::Char('f') => {
+                let current = self.options.input.filter.clone().unwrap_or_default();
+                self.mode = Mode::Filter(current);
+            }
+            KeyCode::Char('c') => self.apply_filter(None),
+            _ => {}
+        }
+        Ok(true)
+    }
+
+    /// Re-reads the input with another filter, keeping the current one if it fails.
+    fn apply_filter(&mut self, filter: Option<String>) {
+        let mut input = self.options.input.clone();
+        input.filter = filter;
+        let result = scan(&input).and_then(|lf| Ok((count_rows(lf.clone())?, lf)));
+        match result {
+            Ok((rows, lf)) => {
+                self.options.input = input;
+                self.lf = lf;
+                self.rows = rows;
+                self.views.clear();
+            }
+            Err(err) => self.message = Some(format!("Invalid filter: {:#}", err)),
+        }
+    }
+
+    /// Draws the title, the column list, the selected column's details and the status line.
+    fn render(&mut self, frame: &mut Frame) {
+        let [title_area, body, status_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let mut title = format!("{} | {} row(s)", input_name(&self.options.input), self.rows);
+        if let Some(filter) = &self.options.input.filter {
+            title.push_str(&format!(" | filter: {}", filter));
+        }
+        frame.render_widget(Line::from(title).bold(), title_area);
+
+        let name_width = self.columns.iter().map(|(n, _)| n.chars().count()).max();
+        let dtype_width = self.columns.iter().map(|(_, d)| d.chars().count()).max();
+        let list_width = (name_width.unwrap_or(0) + dtype_width.unwrap_or(0) + 5)
+            .min(body.width as usize / MAX_LIST_SHARE)
+            .max(12);
+        let [list_area, detail_area] =
+            Layout::horizontal([Constraint::Length(list_width as u16), Constraint::Min(0)])
+                .areas(body);
+
+        let name_width = name_width.unwrap_or(0);
+        let items = self
+            .columns
+            .iter()
+            .map(|(name, dtype)| ListItem::new(format!("{:<w$} {}", name, dtype, w = name_width)));
+        let list = List::new(items)
+            .block(Block::bordered().title("Columns"))
+            .highlight_style(Style::new().reversed());
+        self.list.select(Some(self.selected));
+        self.page = list_area.height.saturating_sub(2) as usize;
+        frame.render_stateful_widget(list, list_area, &mut self.list);
+        self.render_details(frame, detail_area);
+
+        let status = match (&self.mode, &self.message) {
+            (Mode::Filter(text), _) => {
+                format!("Filter: {}_  (Enter to apply, Esc to cancel)", text)
+            }
+            (Mode::Browse, Some(message)) => message.clone(),
+            (Mode::Browse, None) => {
+                "Up/Down: select column  /: filter  c: clear filter  q: quit".to_string()
+            }
+        };
+        frame.render_widget(Line::from(status).dim(), status_area);
+    }
+
+    /// Draws the statistics and histogram of the selected column.
+    fn render_details(&self, frame: &mut Frame, area: Rect) {
+        let Some((name, _)) = self.columns.get(self.selected) else {
+            frame.render_widget(Paragraph::new("The input has no columns."), area);
+            return;
+        };
+        let Some(view) = self.views.get(name) else {
+            return;
+        };
+
+        let stats: Vec<Line> = match &view.stats {
+            Ok(rows) => {
+                let label_width = rows.iter().map(|(l, _)| l.chars().count()).max();
+                rows.iter()
+                    .map(|(label, value)| {
+                        let w = label_width.unwrap_or(0);
+                        Line::from(format!("{:<w$}  {}", label, value, w = w))
+                    })
+                    .collect()
+            }
+            Err(err) => vec![Line::from(err.clone())],
+        };
+        let [stats_area, histogram_area] = Layout::vertical([
+            Constraint::Length(stats.len() as u16 + 2),
+            Constraint::Min(0),
+        ])
+        .areas(area);
+        let block = Block::bordered().title(format!("Statistics of '{}'", name));
+        frame.render_widget(Paragraph::new(stats).block(block), stats_area);
+
+        let block = Block::bordered().title("Histogram");
+        match &view.histogram {
+            Ok(histogram) => {
+                let bars: Vec<Bar> = histogram
+                    .bins
+                    .iter()
+                    .map(|b| {
+                        Bar::default()
+                            .label(Line::from(format!("{:.4} - {:.4}", b.lower, b.upper)))
+                            .value(b.count as u64)
+                    })
+                    .collect();
+                let chart = BarChart::default()
+                    .block(block)
+                    .direction(Direction::Horizontal)
+                    .bar_width(1)
+                    .bar_gap(0)
+                    .data(BarGroup::default().bars(&bars));
+                frame.render_widget(chart, histogram_area);
+            }
+            Err(err) => {
+                let text = format!("No histogram: {}", err);
+                frame.render_widget(Paragraph::new(text).block(block), histogram_area);
+            }
+        }
+    }
+}
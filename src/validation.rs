@@ -0,0 +1,391 @@
+//! Data-quality rules checked against every row, read from a rules file such as
+//!
+//! ```yaml
+//! # rules.yaml
+//! Amount Received: not_null, >= 0, <= 1e9
+//! Currency: in [USD, EUR]
+//! "Account ID":
+//!   - unique
+//!   - matches ^[0-9A-F]+$
+//! ```
+//!
+//! Each entry names a column and lists its rules, either on the same line separated by
+//! commas or as a list below it. Only this flat subset of YAML is read.
+//!
+//! The rules are:
+//!
+//! - `not_null`: the value is present.
+//! - `unique`: no other row has the same value.
+//! - `< N`, `<= N`, `> N`, `>= N`, `== N`, `!= N`: the value parses as a number and
+//!   compares with `N` as stated.
+//! - `in [A, B, ...]`: the value is one of those listed.
+//! - `matches REGEX`: the value matches the regular expression.
+//!
+//! Except for `not_null`, missing values satisfy every rule.
+
+use std::fmt;
+use std::fs;
+use std::str::FromStr;
+
+use anyhow::{Context, Result, anyhow, bail};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::assertions::Comparison;
+use crate::engine::collect;
+use crate::outliers::ROW_COLUMN;
+use crate::stats::NumericParsing;
+
+/// A single rule every value of a column must satisfy.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rule {
+    /// The value is present.
+    NotNull,
+    /// No other row has the same value.
+    Unique,
+    /// The value parses as a number and compares with the threshold as stated.
+    Compare(Comparison, f64),
+    /// The value is one of these.
+    In(Vec<String>),
+    /// The value matches this regular expression.
+    Matches(String),
+}
+
+impl FromStr for Rule {
+    type Err = anyhow::Error;
+
+    fn from_str(source: &str) -> Result<Self> {
+        let source = unquote(source.trim());
+        if let Some((symbol, comparison)) = Comparison::ALL
+            .iter()
+            .find(|(symbol, _)| source.starts_with(symbol))
+        {
+            let threshold = source[symbol.len()..].trim();
+            let threshold = threshold
+                .parse()
+                .map_err(|_| anyhow!("Invalid number '{}' in rule '{}'", threshold, source))?;
+            return Ok(Rule::Compare(*comparison, threshold));
+        }
+        let (name, argument) = source
+            .split_once(char::is_whitespace)
+            .map_or((source, ""), |(name, argument)| (name, argument.trim()));
+        match (name, argument) {
+            ("not_null", "") => Ok(Rule::NotNull),
+            ("unique", "") => Ok(Rule::Unique),
+            ("in", list) => {
+                let Some(list) = list.strip_prefix('[').and_then(|l| l.strip_suffix(']')) else {
+                    bail!("Expected a list like [A, B] in rule '{}'", source);
+                };
+                let values: Vec<String> = split_list(list)
+                    .iter()
+                    .map(|value| unquote(value).to_string())
+                    .collect();
+                if values.is_empty() {
+                    bail!("The list of rule '{}' is empty", source);
+                }
+                Ok(Rule::In(values))
+            }
+            ("matches", pattern) if !pattern.is_empty() => {
+                Ok(Rule::Matches(unquote(pattern).to_string()))
+            }
+            _ => bail!(
+                "Unknown rule '{}', expected not_null, unique, a comparison like '>= 0', \
+                 'in [A, B]' or 'matches REGEX'",
+                source
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rule::NotNull => write!(f, "not_null"),
+            Rule::Unique => write!(f, "unique"),
+            Rule::Compare(comparison, threshold) => {
+                write!(f, "{} {}", comparison.symbol(), threshold)
+            }
+            Rule::In(values) => write!(f, "in [{}]", values.join(", ")),
+            Rule::Matches(pattern) => write!(f, "matches {}", pattern),
+        }
+    }
+}
+
+impl Rule {
+    /// Builds an expression that is true for the rows violating the rule.
+    fn violation(&self, column: &str, parsing: &NumericParsing) -> Expr {
+        let present = col(column).is_not_null();
+        let text = || col(column).cast(DataType::String);
+        let satisfied = match self {
+            Rule::NotNull => return col(column).is_null(),
+            Rule::Unique => len().over([col(column)]).eq(lit(1)),
+            Rule::Compare(comparison, threshold) => {
+                comparison.expr(parsing.to_float(column), lit(*threshold))
+            }
+            Rule::In(values) => values
+                .iter()
+                .map(|value| text().eq(lit(value.as_str())))
+                .reduce(|any, is_value| any.or(is_value))
+                .expect("the list of values is never empty"),
+            Rule::Matches(pattern) => text().str().contains(lit(pattern.as_str()), true),
+        };
+        // Values that can't be parsed leave the comparison null, which is a violation.
+        present.and(satisfied.fill_null(lit(false)).not())
+    }
+}
+
+/// The rules of one column.
+#[derive(Debug, Clone)]
+pub struct ColumnRules {
+    /// The name of the column.
+    pub column: String,
+    /// The rules every value of the column must satisfy.
+    pub rules: Vec<Rule>,
+}
+
+/// Reads the rules from a rules file.
+pub fn read_rules(path: &str) -> Result<Vec<ColumnRules>> {
+    let text =
+        fs::read_to_string(path).with_context(|| format!("Failed to read rules '{}'", path))?;
+    parse_rules(&text).with_context(|| format!("Invalid rules file '{}'", path))
+}
+
+/// Parses the rules of each column, as described in the [module docs](self).
+pub fn parse_rules(text: &str) -> Result<Vec<ColumnRules>> {
+    let mut columns: Vec<ColumnRules> = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = strip_comment(line).trim_end();
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let parse = |rule: &str| {
+            rule.parse::<Rule>()
+                .with_context(|| format!("On line {}", number + 1))
+        };
+
+        if let Some(rule) = trimmed.strip_prefix("- ") {
+            let Some(last) = columns.last_mut() else {
+                bail!("Rule on line {} comes before any column", number + 1);
+            };
+            last.rules.push(parse(rule)?);
+        } else if line.starts_with(char::is_whitespace) {
+            bail!("Unexpected indentation on line {}", number + 1);
+        } else {
+            let (column, rules) = split_entry(line)
+                .ok_or_else(|| anyhow!("Expected 'column: rules' on line {}", number + 1))?;
+            // A flow sequence such as `[not_null, ">= 0"]` lists the rules too.
+            let rules = rules
+                .strip_prefix('[')
+                .and_then(|r| r.strip_suffix(']'))
+                .unwrap_or(rules);
+            columns.push(ColumnRules {
+                column: unquote(column.trim()).to_string(),
+                rules: split_list(rules)
+                    .iter()
+                    .map(|rule| parse(rule))
+                    .collect::<Result<_>>()?,
+            });
+        }
+    }
+    if let Some(empty) = columns.iter().find(|c| c.rules.is_empty()) {
+        bail!("Column '{}' has no rules", empty.column);
+    }
+    Ok(columns)
+}
+
+/// Options controlling validation.
+#[derive(Debug, Clone, Default)]
+pub struct ValidationOptions {
+    /// The most violating rows to collect for each rule.
+    pub samples: usize,
+    /// How text values are cleaned before being parsed as numbers.
+    pub parsing: NumericParsing,
+}
+
+/// The outcome of checking one rule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleResult {
+    /// The name of the column.
+    pub column: String,
+    /// The rule, as written in the rules file.
+    pub rule: String,
+    /// The number of rows violating the rule.
+    pub violations: usize,
+    /// The numbers of the first violating rows, counting from 0.
+    pub sample_rows: Vec<usize>,
+}
+
+/// The outcome of checking every rule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationReport {
+    /// The number of rows checked.
+    pub rows: usize,
+    /// One result per rule, in the order of the rules file.
+    pub results: Vec<RuleResult>,
+}
+
+impl ValidationReport {
+    /// The results of the rules that some rows violate.
+    pub fn failures(&self) -> impl Iterator<Item = &RuleResult> {
+        self.results.iter().filter(|r| r.violations > 0)
+    }
+}
+
+/// The outcome of validation, with samples of the violating rows.
+pub struct Validation {
+    /// The outcome of checking every rule.
+    pub report: ValidationReport,
+    /// The first violating rows of each rule, with their row numbers, in the order of
+    /// the results. Empty for the rules no row violates.
+    pub samples: Vec<DataFrame>,
+}
+
+/// Checks every row against the rules.
+///
+/// The violations of every rule are counted in a single pass; each violated rule then
+/// takes another pass to collect its first violating rows.
+pub fn validate(
+    lf: LazyFrame,
+    rules: &[ColumnRules],
+    options: &ValidationOptions,
+) -> Result<Validation> {
+    let checks: Vec<(&str, &Rule, Expr)> = rules
+        .iter()
+        .flat_map(|column| {
+            column.rules.iter().map(|rule| {
+                let violation = rule.violation(&column.column, &options.parsing);
+                (column.column.as_str(), rule, violation)
+            })
+        })
+        .collect();
+
+    for column in rules {
+        if column
+            .rules
+            .iter()
+            .any(|rule| matches!(rule, Rule::Compare(..)))
+        {
+            options.parsing.check_strict(&lf, &column.column)?;
+        }
+    }
+
+    let mut counts = vec![len().alias("rows")];
+    counts.extend(
+        checks
+            .iter()
+            .enumerate()
+            .map(|(i, (_, _, violation))| violation.clone().sum().alias(format!("{}", i))),
+    );
+    let counts_df = collect(lf.clone().select(counts))?;
+    let get_count = |name: &str| -> Result<usize> {
+        Ok(match counts_df.column(name)?.get(0)? {
+            AnyValue::Null => 0,
+            av => av.try_extract::<u64>()? as usize,
+        })
+    };
+
+    let mut results = Vec::with_capacity(checks.len());
+    let mut samples = Vec::with_capacity(checks.len());
+    for (i, (column, rule, violation)) in checks.into_iter().enumerate() {
+        let violations = get_count(&i.to_string())?;
+        let rows = if violations > 0 && options.samples > 0 {
+            collect(
+                lf.clone()
+                    .with_row_index(ROW_COLUMN, None)
+                    .filter(violation)
+                    .limit(options.samples as IdxSize),
+            )?
+        } else {
+            DataFrame::empty()
+        };
+        let sample_rows = match rows.column(ROW_COLUMN) {
+            Ok(numbers) => numbers
+                .idx()?
+                .into_no_null_iter()
+                .map(|row| row as usize)
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        results.push(RuleResult {
+            column: column.to_string(),
+            rule: rule.to_string(),
+            violations,
+            sample_rows,
+        });
+        samples.push(rows);
+    }
+
+    Ok(Validation {
+        report: ValidationReport {
+            rows: get_count("rows")?,
+            results,
+        },
+        samples,
+    })
+}
+
+/// Splits a `column: rules` entry at the colon following the column name, which may be
+/// quoted.
+fn split_entry(line: &str) -> Option<(&str, &str)> {
+    let start = match line.chars().next()? {
+        quote @ ('"' | '\'') => line[1..].find(quote)? + 2,
+        _ => 0,
+    };
+    let colon = start + line[start..].find(':')?;
+    Some((&line[..colon], line[colon + 1..].trim()))
+}
+
+/// Splits a comma-separated list, ignoring commas within brackets or quotes.
+fn split_list(list: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut depth = 0;
+    let mut quote = None;
+    let mut start = 0;
+    for (i, c) in list.char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            (_, Some(_)) => {}
+            ('[', None) => depth += 1,
+            (']', None) => depth -= 1,
+            (',', None) if depth == 0 => {
+                items.push(list[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(list[start..].trim());
+    items.retain(|item| !item.is_empty());
+    items
+}
+
+/// Removes a `#` comment, unless the `#` is within quotes or part of a word.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (i, c) in line.char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            ('#', None) if previous.is_whitespace() => return &line[..i],
+            _ => {}
+        }
+        previous = c;
+    }
+    line
+}
+
+/// Removes matching single or double quotes around a value.
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|v| v.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    value
+}
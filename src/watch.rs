@@ -0,0 +1,83 @@
+//! Watching input files for changes, to re-run an analysis whenever they're updated.
+
+use std::path::PathBuf;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, channel};
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::input::{InputOptions, expand_paths};
+
+/// How long to wait for a burst of related events (e.g. an editor's truncate and write)
+/// to settle before reporting a change.
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Watches the files of an input for modifications.
+///
+/// The parent directories are watched rather than the files themselves, so that files
+/// replaced by an atomic rename (as many editors and tools do) keep being noticed.
+pub struct InputWatcher {
+    // Kept alive for as long as events are wanted; dropping it stops the watch.
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+    files: Vec<PathBuf>,
+}
+
+impl InputWatcher {
+    /// Starts watching the files of `input`. Glob patterns are expanded once, up front.
+    pub fn new(input: &InputOptions) -> Result<Self> {
+        if input.paths.is_empty() || input.paths.iter().any(|p| p == "-") {
+            bail!("Watching requires input files, stdin cannot be watched");
+        }
+
+        let files = expand_paths(&input.paths)?
+            .iter()
+            .map(|path| {
+                std::fs::canonicalize(path).with_context(|| format!("Cannot watch '{}'", path))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let (sender, events) = channel();
+        let mut watcher = notify::recommended_watcher(sender)?;
+        let mut directories: Vec<_> = files.iter().filter_map(|f| f.parent()).collect();
+        directories.sort();
+        directories.dedup();
+        for directory in directories {
+            watcher.watch(directory, RecursiveMode::NonRecursive)?;
+        }
+
+        Ok(InputWatcher {
+            _watcher: watcher,
+            events,
+            files,
+        })
+    }
+
+    /// Blocks until one of the watched files changes.
+    pub fn wait_for_change(&self) -> Result<()> {
+        loop {
+            let event = self.events.recv()??;
+            if !self.is_relevant(&event) {
+                continue;
+            }
+
+            // Swallow the rest of the burst so one save triggers one re-run.
+            loop {
+                match self.events.recv_timeout(DEBOUNCE) {
+                    Ok(event) => {
+                        event?;
+                    }
+                    Err(RecvTimeoutError::Timeout) => return Ok(()),
+                    Err(RecvTimeoutError::Disconnected) => bail!("The file watcher stopped"),
+                }
+            }
+        }
+    }
+
+    /// Whether the event modified one of the watched files, rather than just reading it
+    /// or touching a neighbouring file.
+    fn is_relevant(&self, event: &Event) -> bool {
+        !event.kind.is_access() && event.paths.iter().any(|path| self.files.contains(path))
+    }
+}
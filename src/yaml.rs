@@ -0,0 +1,108 @@
+//! Rendering of results as YAML, for pipelines that consume it more naturally than JSON.
+//!
+//! The results are serialized to JSON values first, so YAML output has exactly the
+//! fields and names of the JSON output, in block style.
+
+use serde_json::Value;
+
+/// Renders a JSON value as a YAML document, without a trailing newline.
+pub fn to_yaml(value: &Value) -> String {
+    let mut out = String::new();
+    match value {
+        Value::Object(map) if !map.is_empty() => write_block(&mut out, value, 0),
+        Value::Array(items) if !items.is_empty() => write_block(&mut out, value, 0),
+        _ => out.push_str(&scalar(value)),
+    }
+    out.truncate(out.trim_end().len());
+    out
+}
+
+/// Writes a non-empty mapping or sequence, one entry per line at the given indentation.
+fn write_block(out: &mut String, value: &Value, indent: usize) {
+    let pad = " ".repeat(indent);
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                out.push_str(&format!("{}{}:", pad, quote(key)));
+                write_nested(out, value, indent + 2, indent);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                if is_block(item) {
+                    // Render the entry as a block, then put the dash in place of the
+                    // indentation of its first line.
+                    let mut entry = String::new();
+                    write_block(&mut entry, item, indent + 2);
+                    out.push_str(&format!("{}- {}", pad, &entry[indent + 2..]));
+                } else {
+                    out.push_str(&format!("{}- {}\n", pad, scalar(item)));
+                }
+            }
+        }
+        _ => out.push_str(&format!("{}{}\n", pad, scalar(value))),
+    }
+}
+
+/// Writes the value of a mapping entry after its key: on the same line for scalars, and
+/// indented on the following lines otherwise. Sequences stay at the key's indentation.
+fn write_nested(out: &mut String, value: &Value, indent: usize, key_indent: usize) {
+    match value {
+        Value::Object(_) if is_block(value) => {
+            out.push('\n');
+            write_block(out, value, indent);
+        }
+        Value::Array(_) if is_block(value) => {
+            out.push('\n');
+            write_block(out, value, key_indent);
+        }
+        _ => out.push_str(&format!(" {}\n", scalar(value))),
+    }
+}
+
+/// Whether the value is written as a block: a non-empty mapping or sequence.
+fn is_block(value: &Value) -> bool {
+    match value {
+        Value::Object(map) => !map.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        _ => false,
+    }
+}
+
+/// Formats a scalar, or an empty mapping or sequence, on a single line.
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => quote(s),
+        Value::Object(_) => "{}".to_string(),
+        Value::Array(_) => "[]".to_string(),
+    }
+}
+
+/// Quotes a string if YAML would otherwise read it as something else, such as a number,
+/// a boolean or the start of a nested structure. Double-quoted YAML strings use the same
+/// escapes as JSON.
+fn quote(s: &str) -> String {
+    let reserved = [
+        "null", "~", "true", "false", "yes", "no", "on", "off", "y", "n",
+    ];
+    let needs_quotes = s.is_empty()
+        || s.trim() != s
+        || reserved.iter().any(|r| s.eq_ignore_ascii_case(r))
+        || s.parse::<f64>().is_ok()
+        || s.starts_with([
+            '-', '?', ':', ',', '[', ']', '{', '}', '#', '&', '*', '!', '|', '>', '\'', '"', '%',
+            '@', '`',
+        ])
+        || s.ends_with(':')
+        || s.contains(": ")
+        || s.contains(" #")
+        || s.chars().any(char::is_control);
+    if needs_quotes {
+        Value::String(s.to_string()).to_string()
+    } else {
+        s.to_string()
+    }
+}